@@ -1,13 +1,18 @@
 use std::env::current_exe;
+use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, ValueEnum};
-use lumina_node::network::Network;
+use lumina_node::network::{Network, NetworkConfig};
 use serde_repr::Serialize_repr;
+use tokio::task::spawn_blocking;
 
+use crate::config::{self, Config, LogFormat};
 use crate::native;
+use crate::rpc;
 #[cfg(feature = "browser-node")]
 use crate::server;
+use crate::store;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize_repr)]
 #[repr(u8)]
@@ -20,9 +25,18 @@ pub(crate) enum ArgNetwork {
 }
 
 #[derive(Debug, Parser)]
+#[allow(clippy::large_enum_variant)]
 pub(crate) enum CliArgs {
     /// Run native node locally
     Node(native::Params),
+    /// Create or print the node's configuration
+    #[command(subcommand)]
+    Config(config::ConfigCmd),
+    /// Generate an auth token for the node's RPC server
+    Auth(rpc::AuthParams),
+    /// Manage the store of a stopped node
+    #[command(subcommand)]
+    Store(store::StoreCmd),
     /// Serve compiled wasm node to be run in the browser
     #[cfg(feature = "browser-node")]
     Browser(server::Params),
@@ -43,30 +57,70 @@ pub async fn run() -> Result<()> {
         CliArgs::parse()
     };
 
-    let _guard = init_tracing();
+    // Logging format of the node is a part of its configuration
+    let node_config = match args {
+        CliArgs::Node(ref params) => Some(Config::load(params).await?),
+        _ => None,
+    };
+    let log_format = node_config
+        .as_ref()
+        .map(|config| config.log_format)
+        .unwrap_or_default();
+
+    let _guard = init_tracing(log_format);
 
     match args {
-        CliArgs::Node(args) => native::run(args).await,
+        CliArgs::Node(_) => native::run(node_config.expect("config loaded above")).await,
+        CliArgs::Config(cmd) => config::run(cmd).await,
+        CliArgs::Auth(args) => rpc::auth(args).await,
+        CliArgs::Store(cmd) => store::run(cmd).await,
         #[cfg(feature = "browser-node")]
         CliArgs::Browser(args) => server::run(args).await,
     }
 }
 
-fn init_tracing() -> tracing_appender::non_blocking::WorkerGuard {
+fn init_tracing(format: LogFormat) -> tracing_appender::non_blocking::WorkerGuard {
     let (non_blocking, guard) = tracing_appender::non_blocking(std::io::stdout());
 
     let filter = tracing_subscriber::EnvFilter::builder()
         .with_default_directive(tracing_subscriber::filter::LevelFilter::INFO.into())
         .from_env_lossy();
 
-    tracing_subscriber::fmt()
+    let builder = tracing_subscriber::fmt()
         .with_env_filter(filter)
-        .with_writer(non_blocking)
-        .init();
+        .with_writer(non_blocking);
+
+    match format {
+        LogFormat::Full => builder.init(),
+        LogFormat::Compact => builder.compact().init(),
+        LogFormat::Pretty => builder.pretty().init(),
+        LogFormat::Json => builder.json().init(),
+    }
 
     guard
 }
 
+/// Load the definition of a custom network from the JSON file, or get the config
+/// of the canonical network if the file is not provided.
+pub(crate) async fn network_config(
+    network: ArgNetwork,
+    path: Option<&Path>,
+) -> Result<NetworkConfig> {
+    let Some(path) = path else {
+        return Ok(NetworkConfig::canonical(network.into()));
+    };
+
+    let json = spawn_blocking({
+        let path = path.to_owned();
+        move || std::fs::read_to_string(path)
+    })
+    .await?
+    .with_context(|| format!("failed to read network config from {}", path.display()))?;
+
+    NetworkConfig::from_json(&json)
+        .with_context(|| format!("invalid network config in {}", path.display()))
+}
+
 impl From<ArgNetwork> for Network {
     fn from(network: ArgNetwork) -> Network {
         match network {
@@ -0,0 +1,493 @@
+//! Configuration of the native node.
+//!
+//! Configuration is read from a TOML file, whose keys are the same as the long
+//! names of the `lumina node` arguments. Values from the file are overridden by
+//! the environment variables (`LUMINA_<KEY>`) and then by the command line arguments.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use celestia_types::hash::Hash;
+use clap::{Parser, Subcommand, ValueEnum};
+use directories::ProjectDirs;
+use libp2p::Multiaddr;
+use lumina_node::node::{
+    DEFAULT_MAX_CONNECTIONS, DEFAULT_MAX_CONNECTIONS_PER_PEER, DEFAULT_MAX_INBOUND_CONNECTIONS,
+    DEFAULT_MAX_OUTBOUND_CONNECTIONS, DEFAULT_MAX_SAMPLES_NEEDED, DEFAULT_PEER_BAN_DURATION,
+};
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_blocking;
+
+use crate::common::ArgNetwork;
+use crate::native::{parse_duration, Params};
+
+/// Default maximum number of headers in batch while syncing.
+pub(crate) const DEFAULT_SYNC_BATCH_SIZE: u64 = 512;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ConfigCmd {
+    /// Write the effective configuration to the config file
+    Init(InitParams),
+    /// Print the effective configuration as TOML
+    Show(Params),
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct InitParams {
+    #[command(flatten)]
+    pub(crate) params: Params,
+
+    /// Overwrite the config file if it already exists.
+    #[arg(long = "force")]
+    pub(crate) force: bool,
+}
+
+/// Format of the logs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LogFormat {
+    /// Human readable, single line per event
+    #[default]
+    Full,
+    /// Shorter version of the full format
+    Compact,
+    /// Human readable, multiple lines per event
+    Pretty,
+    /// Newline delimited JSON
+    Json,
+}
+
+/// Configuration of the native node.
+///
+/// Windows which are not set are derived from the syncing window when starting the node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Config {
+    #[serde(with = "network_serde")]
+    pub(crate) network: ArgNetwork,
+    pub(crate) network_config: Option<PathBuf>,
+    pub(crate) listen: Vec<Multiaddr>,
+    pub(crate) bootnodes: Vec<Multiaddr>,
+    pub(crate) store: Option<PathBuf>,
+    pub(crate) keypair: Option<PathBuf>,
+    pub(crate) import_celestia_key: Option<PathBuf>,
+    pub(crate) trusted_hash: Option<Hash>,
+    pub(crate) trusted_height: Option<u64>,
+    pub(crate) sync_batch_size: u64,
+    #[serde(with = "duration_opt_serde")]
+    pub(crate) syncing_window: Option<Duration>,
+    pub(crate) archival: bool,
+    #[serde(with = "duration_opt_serde")]
+    pub(crate) sampling_window: Option<Duration>,
+    #[serde(with = "duration_opt_serde")]
+    pub(crate) pruning_window: Option<Duration>,
+    pub(crate) no_pruning: bool,
+    pub(crate) max_samples: usize,
+    pub(crate) full_storage: bool,
+    #[serde(with = "duration_serde")]
+    pub(crate) peer_ban_duration: Duration,
+    pub(crate) max_inbound_connections: usize,
+    pub(crate) max_outbound_connections: usize,
+    pub(crate) max_connections: usize,
+    pub(crate) max_connections_per_peer: usize,
+    pub(crate) rpc_listen: Option<SocketAddr>,
+    pub(crate) rpc_skip_auth: bool,
+    pub(crate) metrics_listen: Option<SocketAddr>,
+    pub(crate) log_format: LogFormat,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            network: ArgNetwork::default(),
+            network_config: None,
+            listen: Vec::new(),
+            bootnodes: Vec::new(),
+            store: None,
+            keypair: None,
+            import_celestia_key: None,
+            trusted_hash: None,
+            trusted_height: None,
+            sync_batch_size: DEFAULT_SYNC_BATCH_SIZE,
+            syncing_window: None,
+            archival: false,
+            sampling_window: None,
+            pruning_window: None,
+            no_pruning: false,
+            max_samples: DEFAULT_MAX_SAMPLES_NEEDED,
+            full_storage: false,
+            peer_ban_duration: DEFAULT_PEER_BAN_DURATION,
+            max_inbound_connections: DEFAULT_MAX_INBOUND_CONNECTIONS,
+            max_outbound_connections: DEFAULT_MAX_OUTBOUND_CONNECTIONS,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            max_connections_per_peer: DEFAULT_MAX_CONNECTIONS_PER_PEER,
+            rpc_listen: None,
+            rpc_skip_auth: false,
+            metrics_listen: None,
+            log_format: LogFormat::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the effective configuration.
+    ///
+    /// The config file is taken from `--config` or, if not provided, from the default
+    /// location if it exists there.
+    pub(crate) async fn load(params: &Params) -> Result<Config> {
+        let path = match params.config {
+            Some(ref path) => Some(path.clone()),
+            None => Some(default_config_path()?).filter(|path| path.exists()),
+        };
+
+        let config = match path {
+            Some(path) => read_config(path).await?,
+            None => Config::default(),
+        };
+
+        config.merged(params)
+    }
+
+    /// Override the configuration with the arguments and environment variables.
+    fn merged(mut self, params: &Params) -> Result<Config> {
+        if let Some(network) = params.network {
+            self.network = network;
+        }
+        if params.network_config.is_some() {
+            self.network_config.clone_from(&params.network_config);
+        }
+        if !params.listen_addrs.is_empty() {
+            self.listen.clone_from(&params.listen_addrs);
+        }
+        if !params.bootnodes.is_empty() {
+            self.bootnodes.clone_from(&params.bootnodes);
+        }
+        if params.store.is_some() {
+            self.store.clone_from(&params.store);
+        }
+        if params.keypair.is_some() {
+            self.keypair.clone_from(&params.keypair);
+        }
+        if params.import_celestia_key.is_some() {
+            self.import_celestia_key
+                .clone_from(&params.import_celestia_key);
+        }
+        if params.trusted_hash.is_some() {
+            self.trusted_hash = params.trusted_hash;
+        }
+        if params.trusted_height.is_some() {
+            self.trusted_height = params.trusted_height;
+        }
+        if let Some(batch_size) = params.sync_batch_size {
+            self.sync_batch_size = batch_size;
+        }
+        if params.syncing_window.is_some() {
+            self.syncing_window = params.syncing_window;
+        }
+        if let Some(archival) = params.archival {
+            self.archival = archival;
+        }
+        if params.sampling_window.is_some() {
+            self.sampling_window = params.sampling_window;
+        }
+        if params.pruning_window.is_some() {
+            self.pruning_window = params.pruning_window;
+        }
+        if let Some(no_pruning) = params.no_pruning {
+            self.no_pruning = no_pruning;
+        }
+        if let Some(max_samples) = params.max_samples {
+            self.max_samples = max_samples;
+        }
+        if let Some(full_storage) = params.full_storage {
+            self.full_storage = full_storage;
+        }
+        if let Some(duration) = params.peer_ban_duration {
+            self.peer_ban_duration = duration;
+        }
+        if let Some(max) = params.max_inbound_connections {
+            self.max_inbound_connections = max;
+        }
+        if let Some(max) = params.max_outbound_connections {
+            self.max_outbound_connections = max;
+        }
+        if let Some(max) = params.max_connections {
+            self.max_connections = max;
+        }
+        if let Some(max) = params.max_connections_per_peer {
+            self.max_connections_per_peer = max;
+        }
+        if params.rpc_listen.is_some() {
+            self.rpc_listen = params.rpc_listen;
+        }
+        if let Some(skip_auth) = params.rpc_skip_auth {
+            self.rpc_skip_auth = skip_auth;
+        }
+        if params.metrics_listen.is_some() {
+            self.metrics_listen = params.metrics_listen;
+        }
+        if let Some(format) = params.log_format {
+            self.log_format = format;
+        }
+
+        self.validate()?;
+
+        Ok(self)
+    }
+
+    /// Check that the options don't conflict with each other.
+    fn validate(&self) -> Result<()> {
+        if self.archival && self.syncing_window.is_some() {
+            bail!("archival cannot be used together with syncing-window");
+        }
+
+        if self.no_pruning && self.pruning_window.is_some() {
+            bail!("no-pruning cannot be used together with pruning-window");
+        }
+
+        if self.trusted_height.is_some() && self.trusted_hash.is_none() {
+            bail!("trusted-height requires trusted-hash");
+        }
+
+        if self.rpc_skip_auth && self.rpc_listen.is_none() {
+            bail!("rpc-skip-auth requires rpc-listen");
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) async fn run(cmd: ConfigCmd) -> Result<()> {
+    match cmd {
+        ConfigCmd::Init(args) => init(args).await,
+        ConfigCmd::Show(params) => {
+            let config = Config::load(&params).await?;
+            print!("{}", toml::to_string(&config)?);
+            Ok(())
+        }
+    }
+}
+
+/// Write the configuration created from the defaults, environment variables and
+/// arguments to the config file.
+async fn init(args: InitParams) -> Result<()> {
+    let path = match args.params.config {
+        Some(ref path) => path.clone(),
+        None => default_config_path()?,
+    };
+
+    let config = Config::default().merged(&args.params)?;
+    let content = toml::to_string(&config)?;
+
+    spawn_blocking(move || {
+        use std::fs;
+
+        if path.exists() && !args.force {
+            bail!(
+                "Config file {} already exists, use --force to overwrite it",
+                path.display()
+            );
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&path, content)
+            .with_context(|| format!("failed to write config to {}", path.display()))?;
+        println!("Written config to {}", path.display());
+
+        Ok(())
+    })
+    .await?
+}
+
+async fn read_config(path: PathBuf) -> Result<Config> {
+    let content = spawn_blocking({
+        let path = path.clone();
+        move || std::fs::read_to_string(path)
+    })
+    .await?
+    .with_context(|| format!("failed to read config from {}", path.display()))?;
+
+    toml::from_str(&content).with_context(|| format!("invalid config in {}", path.display()))
+}
+
+pub(crate) fn default_config_path() -> Result<PathBuf> {
+    Ok(ProjectDirs::from("co", "eiger", "lumina")
+        .context("failed to construct project path")?
+        .config_dir()
+        .join("config.toml"))
+}
+
+/// Format a duration in the biggest unit accepted by [`parse_duration`] which
+/// represents it exactly. Subsecond part is dropped.
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    [(24 * 60 * 60, 'd'), (60 * 60, 'h'), (60, 'm')]
+        .into_iter()
+        .find(|(unit_secs, _)| secs != 0 && secs.is_multiple_of(*unit_secs))
+        .map(|(unit_secs, unit)| format!("{}{unit}", secs / unit_secs))
+        .unwrap_or_else(|| format!("{secs}s"))
+}
+
+mod duration_serde {
+    use super::*;
+    use serde::{de, Deserializer, Serializer};
+
+    pub(super) fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format_duration(*duration))
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        parse_duration(&s).map_err(de::Error::custom)
+    }
+}
+
+mod duration_opt_serde {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub(super) fn serialize<S>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match duration {
+            Some(duration) => duration_serde::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        duration_serde::deserialize(deserializer).map(Some)
+    }
+}
+
+mod network_serde {
+    use super::*;
+    use serde::{de, Deserializer, Serializer};
+
+    pub(super) fn serialize<S>(network: &ArgNetwork, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let value = network.to_possible_value().expect("no skipped networks");
+        serializer.serialize_str(value.get_name())
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<ArgNetwork, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        ArgNetwork::from_str(&s, true).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(args: &[&str]) -> Params {
+        Params::try_parse_from(std::iter::once("lumina").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn duration_format_roundtrip() {
+        for s in ["30d", "1h", "90m", "61s", "0s"] {
+            assert_eq!(format_duration(parse_duration(s).unwrap()), s);
+        }
+
+        assert_eq!(format_duration(Duration::from_secs(2 * 24 * 60 * 60)), "2d");
+        assert_eq!(format_duration(Duration::from_secs(120)), "2m");
+    }
+
+    #[test]
+    fn config_toml_roundtrip() {
+        let config = Config::default()
+            .merged(&params(&[
+                "--network",
+                "mocha",
+                "--listen",
+                "/ip4/0.0.0.0/tcp/2121",
+                "--syncing-window",
+                "7d",
+                "--max-samples",
+                "8",
+                "--rpc-listen",
+                "127.0.0.1:26658",
+                "--log-format",
+                "json",
+            ]))
+            .unwrap();
+
+        let content = toml::to_string(&config).unwrap();
+        let parsed: Config = toml::from_str(&content).unwrap();
+
+        assert_eq!(parsed, config);
+        assert_eq!(parsed.network, ArgNetwork::Mocha);
+        assert_eq!(
+            parsed.syncing_window,
+            Some(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+        assert_eq!(parsed.sampling_window, None);
+        assert_eq!(parsed.log_format, LogFormat::Json);
+    }
+
+    #[test]
+    fn args_override_file() {
+        let file: Config = toml::from_str(
+            r#"
+            network = "arabica"
+            pruning-window = "10d"
+            max-samples = 4
+            full-storage = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(file.sync_batch_size, DEFAULT_SYNC_BATCH_SIZE);
+
+        let config = file
+            .clone()
+            .merged(&params(&["--max-samples", "32", "--full-storage=false"]))
+            .unwrap();
+
+        assert_eq!(config.network, ArgNetwork::Arabica);
+        assert_eq!(
+            config.pruning_window,
+            Some(Duration::from_secs(10 * 24 * 60 * 60))
+        );
+        assert_eq!(config.max_samples, 32);
+        assert!(!config.full_storage);
+
+        // Pruning window applies only to the samples in archival mode
+        let config = file.clone().merged(&params(&["--archival"])).unwrap();
+        assert!(config.archival);
+
+        // Archival mode conflicts with the syncing window
+        file.merged(&params(&["--archival", "--syncing-window", "1d"]))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        toml::from_str::<Config>("syncing-windw = \"1d\"").unwrap_err();
+    }
+}
@@ -2,8 +2,12 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 mod common;
+mod config;
+mod metrics;
 mod native;
+mod rpc;
 #[cfg(feature = "browser-node")]
 mod server;
+mod store;
 
 pub use common::run;
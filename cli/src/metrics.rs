@@ -0,0 +1,49 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use lumina_node::blockstore::RedbBlockstore;
+use lumina_node::store::RedbStore;
+use lumina_node::Node;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Content type of the Prometheus (OpenMetrics) text format.
+const METRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+type NativeNode = Node<RedbBlockstore, RedbStore>;
+
+/// Start serving the node's metrics on `http://<listen_addr>/metrics`.
+pub(crate) async fn start(
+    listen_addr: SocketAddr,
+    node: Arc<NativeNode>,
+) -> Result<JoinHandle<()>> {
+    let app = Router::new()
+        .route("/metrics", get(serve_metrics))
+        .with_state(node);
+
+    let listener = TcpListener::bind(&listen_addr).await?;
+    info!(
+        "Metrics served on http://{}/metrics",
+        listener.local_addr()?
+    );
+
+    Ok(tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app.into_make_service()).await {
+            error!("Metrics server stopped: {e}");
+        }
+    }))
+}
+
+async fn serve_metrics(State(node): State<Arc<NativeNode>>) -> Response {
+    match node.metrics().await {
+        Ok(metrics) => ([(header::CONTENT_TYPE, METRICS_CONTENT_TYPE)], metrics).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
@@ -1,62 +1,251 @@
 use std::env;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
+use base64::prelude::*;
 use celestia_rpc::prelude::*;
 use celestia_rpc::Client;
+use celestia_types::hash::Hash;
 use clap::Parser;
 use directories::ProjectDirs;
-use libp2p::{identity, multiaddr::Protocol, Multiaddr};
+use libp2p::identity::Keypair;
+use libp2p::{multiaddr::Protocol, Multiaddr};
 use lumina_node::blockstore::RedbBlockstore;
 use lumina_node::events::NodeEvent;
-use lumina_node::network::{canonical_network_bootnodes, network_id, Network};
-use lumina_node::node::{Node, NodeConfig};
+use lumina_node::node::{ConnectionLimits, Node, NodeConfig, TrustedCheckpoint};
 use lumina_node::store::{RedbStore, Store};
+use serde::Deserialize;
 use tokio::task::spawn_blocking;
 use tracing::info;
 use tracing::warn;
 
-use crate::common::ArgNetwork;
+use crate::common::{network_config, ArgNetwork};
+use crate::config::{Config, LogFormat};
+use crate::metrics;
+use crate::rpc;
 
 const CELESTIA_LOCAL_BRIDGE_RPC_ADDR: &str = "ws://localhost:26658";
 
+/// Arguments of the native node.
+///
+/// Every argument can also be provided in the config file or as a `LUMINA_*`
+/// environment variable. Arguments take precedence over the environment
+/// variables, which take precedence over the config file.
 #[derive(Debug, Parser)]
 pub(crate) struct Params {
-    /// Network to connect.
-    #[arg(short, long, value_enum, default_value_t)]
-    pub(crate) network: ArgNetwork,
+    /// Path to the TOML config file.
+    ///
+    /// Defaults to `config.toml` in the user's config directory, if it exists.
+    #[arg(short, long = "config", env = "LUMINA_CONFIG")]
+    pub(crate) config: Option<PathBuf>,
+
+    /// Network to connect. Defaults to mainnet.
+    #[arg(short, long, value_enum, env = "LUMINA_NETWORK")]
+    pub(crate) network: Option<ArgNetwork>,
+
+    /// Path to the JSON definition of a custom network. Takes precedence over `--network`.
+    ///
+    /// Bootnodes, trusted hash and windows of the network are used unless provided explicitly.
+    #[arg(long = "network-config", env = "LUMINA_NETWORK_CONFIG")]
+    pub(crate) network_config: Option<PathBuf>,
 
     /// Listening addresses. Can be used multiple times.
-    #[arg(short, long = "listen")]
+    #[arg(short, long = "listen", env = "LUMINA_LISTEN", value_delimiter = ',')]
     pub(crate) listen_addrs: Vec<Multiaddr>,
 
     /// Bootnode multiaddr, including peer id. Can be used multiple times.
-    #[arg(short, long = "bootnode")]
+    #[arg(
+        short,
+        long = "bootnode",
+        env = "LUMINA_BOOTNODES",
+        value_delimiter = ','
+    )]
     pub(crate) bootnodes: Vec<Multiaddr>,
 
     /// Persistent header store path.
-    #[arg(short, long = "store")]
+    #[arg(short, long = "store", env = "LUMINA_STORE")]
     pub(crate) store: Option<PathBuf>,
+
+    /// Path to the keypair used as the node's identity.
+    ///
+    /// If the file doesn't exist, a new keypair is generated and saved there.
+    #[arg(short, long = "keypair", env = "LUMINA_KEYPAIR")]
+    pub(crate) keypair: Option<PathBuf>,
+
+    /// Import a p2p key from the celestia-node keystore and use it as the node's identity.
+    ///
+    /// In celestia-node the key is located at `<node_store>/keys/OAZHALLLMV4Q`.
+    #[arg(long = "import-celestia-key", env = "LUMINA_IMPORT_CELESTIA_KEY")]
+    pub(crate) import_celestia_key: Option<PathBuf>,
+
+    /// Hash of a trusted header used as the root of trust when syncing.
+    #[arg(long = "trusted-hash", env = "LUMINA_TRUSTED_HASH")]
+    pub(crate) trusted_hash: Option<Hash>,
+
+    /// Height of the trusted header. Requires `--trusted-hash`.
+    #[arg(long = "trusted-height", env = "LUMINA_TRUSTED_HEIGHT")]
+    pub(crate) trusted_height: Option<u64>,
+
+    /// Maximum number of headers in batch while syncing. Defaults to 512.
+    #[arg(long = "sync-batch-size", env = "LUMINA_SYNC_BATCH_SIZE")]
+    pub(crate) sync_batch_size: Option<u64>,
+
+    /// Headers older than this window are not synced, e.g. `30d` or `12h`.
+    #[arg(long = "syncing-window", env = "LUMINA_SYNCING_WINDOW", value_parser = parse_duration)]
+    pub(crate) syncing_window: Option<Duration>,
+
+    /// Backfill headers down to the genesis, ignoring the syncing window.
+    ///
+    /// Headers are never pruned in this mode, only the samples are.
+    #[arg(
+        long = "archival",
+        env = "LUMINA_ARCHIVAL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub(crate) archival: Option<bool>,
+
+    /// Blocks older than this window are not sampled, e.g. `30d` or `12h`.
+    ///
+    /// Defaults to the smaller of 30 days and the syncing window.
+    #[arg(long = "sampling-window", env = "LUMINA_SAMPLING_WINDOW", value_parser = parse_duration)]
+    pub(crate) sampling_window: Option<Duration>,
+
+    /// Headers and samples older than this window are removed, e.g. `30d` or `12h`.
+    ///
+    /// Defaults to the syncing window extended by 1 hour.
+    #[arg(long = "pruning-window", env = "LUMINA_PRUNING_WINDOW", value_parser = parse_duration)]
+    pub(crate) pruning_window: Option<Duration>,
+
+    /// Never remove any headers and samples.
+    #[arg(
+        long = "no-pruning",
+        env = "LUMINA_NO_PRUNING",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub(crate) no_pruning: Option<bool>,
+
+    /// Maximum number of samples taken from each block.
+    #[arg(long = "max-samples", env = "LUMINA_MAX_SAMPLES")]
+    pub(crate) max_samples: Option<usize>,
+
+    /// Retrieve and keep the original data rows of the sampled blocks, serving them to other nodes.
+    #[arg(
+        long = "full-storage",
+        env = "LUMINA_FULL_STORAGE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub(crate) full_storage: Option<bool>,
+
+    /// For how long misbehaving peers are banned, e.g. `30m` or `12h`.
+    #[arg(long = "peer-ban-duration", env = "LUMINA_PEER_BAN_DURATION", value_parser = parse_duration)]
+    pub(crate) peer_ban_duration: Option<Duration>,
+
+    /// Maximum number of inbound connections, `0` means no limit. Trusted peers and
+    /// bootnodes are not counted.
+    #[arg(
+        long = "max-inbound-connections",
+        env = "LUMINA_MAX_INBOUND_CONNECTIONS"
+    )]
+    pub(crate) max_inbound_connections: Option<usize>,
+
+    /// Maximum number of outbound connections, `0` means no limit. Trusted peers and
+    /// bootnodes are not counted.
+    #[arg(
+        long = "max-outbound-connections",
+        env = "LUMINA_MAX_OUTBOUND_CONNECTIONS"
+    )]
+    pub(crate) max_outbound_connections: Option<usize>,
+
+    /// Maximum number of connections, `0` means no limit. Trusted peers and
+    /// bootnodes are not counted.
+    #[arg(long = "max-connections", env = "LUMINA_MAX_CONNECTIONS")]
+    pub(crate) max_connections: Option<usize>,
+
+    /// Maximum number of connections with a single peer, `0` means no limit.
+    #[arg(
+        long = "max-connections-per-peer",
+        env = "LUMINA_MAX_CONNECTIONS_PER_PEER"
+    )]
+    pub(crate) max_connections_per_peer: Option<usize>,
+
+    /// Address to serve the JSON-RPC API on, e.g. `127.0.0.1:26658`.
+    ///
+    /// Requests need an auth token, which can be created with `lumina auth`.
+    #[arg(long = "rpc-listen", env = "LUMINA_RPC_LISTEN")]
+    pub(crate) rpc_listen: Option<SocketAddr>,
+
+    /// Serve the JSON-RPC API without requiring an auth token.
+    #[arg(
+        long = "rpc-skip-auth",
+        env = "LUMINA_RPC_SKIP_AUTH",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub(crate) rpc_skip_auth: Option<bool>,
+
+    /// Address to serve Prometheus metrics on, e.g. `127.0.0.1:9090`.
+    ///
+    /// Metrics are available on the `/metrics` path.
+    #[arg(long = "metrics-listen", env = "LUMINA_METRICS_LISTEN")]
+    pub(crate) metrics_listen: Option<SocketAddr>,
+
+    /// Format of the logs.
+    #[arg(long = "log-format", value_enum, env = "LUMINA_LOG_FORMAT")]
+    pub(crate) log_format: Option<LogFormat>,
 }
 
-pub(crate) async fn run(args: Params) -> Result<()> {
-    let network = args.network.into();
-    let p2p_local_keypair = identity::Keypair::generate_ed25519();
+pub(crate) async fn run(config: Config) -> Result<()> {
+    let network = network_config(config.network, config.network_config.as_deref()).await?;
 
-    let p2p_bootnodes = if args.bootnodes.is_empty() {
-        match network {
-            Network::Private => fetch_bridge_multiaddrs(CELESTIA_LOCAL_BRIDGE_RPC_ADDR).await?,
-            network => canonical_network_bootnodes(network).collect(),
-        }
+    let p2p_bootnodes = if !config.bootnodes.is_empty() {
+        config.bootnodes
+    } else if config.network_config.is_none() && config.network == ArgNetwork::Private {
+        fetch_bridge_multiaddrs(CELESTIA_LOCAL_BRIDGE_RPC_ADDR).await?
     } else {
-        args.bootnodes
+        network.bootnodes.clone()
     };
 
-    let network_id = network_id(network).to_owned();
+    let network_id = network.id.clone();
+
+    let keypair_path = match config.keypair {
+        Some(path) => path,
+        None => default_keypair_path(&network_id)?,
+    };
+
+    if let Some(path) = config.import_celestia_key {
+        import_celestia_node_key(&path, &keypair_path).await?;
+    }
+
+    let p2p_local_keypair = load_or_generate_keypair(&keypair_path).await?;
+    info!("Local peer id: {}", p2p_local_keypair.public().to_peer_id());
+
+    let sync_trusted_checkpoint = config.trusted_hash.map(|hash| match config.trusted_height {
+        Some(height) => TrustedCheckpoint::with_height(height, hash),
+        None => TrustedCheckpoint::new(hash),
+    });
+
+    let rpc_jwt_secret = if config.rpc_listen.is_none() {
+        None
+    } else if config.rpc_skip_auth {
+        warn!("RPC authentication is disabled");
+        None
+    } else {
+        let path = rpc::default_jwt_secret_path(&network_id)?;
+        Some(rpc::load_or_generate_jwt_secret(&path).await?)
+    };
 
     info!("Initializing store");
-    let db = open_db(args.store, &network_id).await?;
+    let db = open_db(config.store, &network_id).await?;
     let store = RedbStore::new(db.clone()).await?;
     let blockstore = RedbBlockstore::new(db);
 
@@ -67,17 +256,45 @@ pub(crate) async fn run(args: Params) -> Result<()> {
         info!("Initialised store, present headers: {stored_ranges}");
     }
 
-    let (_node, mut events) = Node::new_subscribed(NodeConfig {
-        network_id,
+    let p2p_connection_limits = ConnectionLimits {
+        max_inbound: connection_limit(config.max_inbound_connections),
+        max_outbound: connection_limit(config.max_outbound_connections),
+        max_total: connection_limit(config.max_connections),
+        max_per_peer: connection_limit(config.max_connections_per_peer),
+    };
+
+    let (node, mut events) = Node::new_subscribed(NodeConfig {
+        network,
         p2p_local_keypair,
         p2p_bootnodes,
-        p2p_listen_on: args.listen_addrs,
-        sync_batch_size: 512,
+        p2p_listen_on: config.listen,
+        p2p_peer_ban_duration: config.peer_ban_duration,
+        p2p_connection_limits,
+        sync_batch_size: config.sync_batch_size,
+        sync_trusted_checkpoint,
+        sync_window: config.syncing_window,
+        sync_archival: config.archival,
+        sampling_window: config.sampling_window,
+        sampling_max_samples_needed: config.max_samples,
+        sampling_full_storage: config.full_storage,
+        pruning_window: config.pruning_window,
+        pruning_disabled: config.no_pruning,
         blockstore,
         store,
     })
     .await
     .context("Failed to start node")?;
+    let node = Arc::new(node);
+
+    let _rpc_server = match config.rpc_listen {
+        Some(listen_addr) => Some(rpc::start(listen_addr, node.clone(), rpc_jwt_secret).await?),
+        None => None,
+    };
+
+    let _metrics_server = match config.metrics_listen {
+        Some(listen_addr) => Some(metrics::start(listen_addr, node.clone()).await?),
+        None => None,
+    };
 
     while let Ok(ev) = events.recv().await {
         match ev.event {
@@ -91,7 +308,10 @@ pub(crate) async fn run(args: Params) -> Result<()> {
     Ok(())
 }
 
-async fn open_db(path: Option<PathBuf>, network_id: &str) -> Result<Arc<redb::Database>> {
+pub(crate) async fn open_db(
+    path: Option<PathBuf>,
+    network_id: &str,
+) -> Result<Arc<redb::Database>> {
     let network_id = network_id.to_owned();
 
     spawn_blocking(move || {
@@ -102,11 +322,7 @@ async fn open_db(path: Option<PathBuf>, network_id: &str) -> Result<Arc<redb::Da
             return Ok(Arc::new(db));
         }
 
-        let cache_dir = ProjectDirs::from("co", "eiger", "lumina")
-            .context("failed to construct project path")?
-            .cache_dir()
-            .join(&network_id)
-            .to_owned();
+        let cache_dir = network_dir(&network_id)?;
 
         let old_cache_dir = ProjectDirs::from("co", "eiger", "celestia")
             .context("failed to construct project path")?
@@ -127,7 +343,7 @@ async fn open_db(path: Option<PathBuf>, network_id: &str) -> Result<Arc<redb::Da
         // Directories need to pre-exist
         fs::create_dir_all(&cache_dir)?;
 
-        let path = cache_dir.join("db");
+        let path = default_db_path(&network_id)?;
         let db = redb::Database::create(path)?;
 
         Ok(Arc::new(db))
@@ -135,6 +351,147 @@ async fn open_db(path: Option<PathBuf>, network_id: &str) -> Result<Arc<redb::Da
     .await?
 }
 
+pub(crate) fn network_dir(network_id: &str) -> Result<PathBuf> {
+    Ok(ProjectDirs::from("co", "eiger", "lumina")
+        .context("failed to construct project path")?
+        .cache_dir()
+        .join(network_id))
+}
+
+pub(crate) fn default_db_path(network_id: &str) -> Result<PathBuf> {
+    Ok(network_dir(network_id)?.join("db"))
+}
+
+fn default_keypair_path(network_id: &str) -> Result<PathBuf> {
+    Ok(network_dir(network_id)?.join("keypair"))
+}
+
+/// Load the keypair from the given path or generate a new one and save it there.
+async fn load_or_generate_keypair(path: &Path) -> Result<Keypair> {
+    let path = path.to_owned();
+
+    spawn_blocking(move || {
+        use std::fs;
+
+        if path.exists() {
+            let bytes = fs::read(&path)
+                .with_context(|| format!("failed to read keypair from {}", path.display()))?;
+            let keypair = Keypair::from_protobuf_encoding(&bytes)
+                .with_context(|| format!("invalid keypair in {}", path.display()))?;
+
+            return Ok(keypair);
+        }
+
+        let keypair = Keypair::generate_ed25519();
+        write_keypair(&path, &keypair)?;
+        info!("Generated new keypair in {}", path.display());
+
+        Ok(keypair)
+    })
+    .await?
+}
+
+/// Import the p2p key of celestia-node and save it as lumina's keypair.
+async fn import_celestia_node_key(src: &Path, dst: &Path) -> Result<()> {
+    /// Representation of a key in celestia-node's keystore.
+    #[derive(Deserialize)]
+    struct KeystoreKey {
+        body: String,
+    }
+
+    let src = src.to_owned();
+    let dst = dst.to_owned();
+
+    spawn_blocking(move || {
+        use std::fs;
+
+        let data = fs::read(&src)
+            .with_context(|| format!("failed to read celestia-node key {}", src.display()))?;
+        let key: KeystoreKey =
+            serde_json::from_slice(&data).context("invalid celestia-node keystore entry")?;
+        let bytes = BASE64_STANDARD
+            .decode(key.body)
+            .context("invalid celestia-node key encoding")?;
+        let keypair =
+            Keypair::from_protobuf_encoding(&bytes).context("invalid celestia-node p2p key")?;
+
+        if dst.exists() {
+            let existing = fs::read(&dst)
+                .with_context(|| format!("failed to read keypair from {}", dst.display()))?;
+
+            if existing == keypair.to_protobuf_encoding()? {
+                return Ok(());
+            }
+
+            bail!(
+                "Keypair {} already exists, remove it first to import a different one",
+                dst.display()
+            );
+        }
+
+        write_keypair(&dst, &keypair)?;
+        info!("Imported celestia-node key to {}", dst.display());
+
+        Ok(())
+    })
+    .await?
+}
+
+fn write_keypair(path: &Path, keypair: &Keypair) -> Result<()> {
+    use std::fs;
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let bytes = keypair.to_protobuf_encoding()?;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("failed to create keypair file {}", path.display()))?;
+    file.write_all(&bytes)?;
+
+    Ok(())
+}
+
+/// Parse a duration in a form of a number followed by an optional unit,
+/// one of `s`, `m`, `h` or `d`. Number without a unit is treated as seconds.
+pub(crate) fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let (num, unit_secs) = match s.char_indices().last() {
+        Some((idx, 's')) => (&s[..idx], 1),
+        Some((idx, 'm')) => (&s[..idx], 60),
+        Some((idx, 'h')) => (&s[..idx], 60 * 60),
+        Some((idx, 'd')) => (&s[..idx], 24 * 60 * 60),
+        _ => (s, 1),
+    };
+
+    let num: u64 = num
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid duration: {s}"))?;
+    let secs = num
+        .checked_mul(unit_secs)
+        .with_context(|| format!("duration too big: {s}"))?;
+
+    Ok(Duration::from_secs(secs))
+}
+
+/// Convert the configured connection limit, where `0` means no limit.
+fn connection_limit(max: usize) -> Option<usize> {
+    (max != 0).then_some(max)
+}
+
 fn is_sled_db(path: impl AsRef<Path>) -> bool {
     let path = path.as_ref();
     path.join("blobs").is_dir() && path.join("conf").is_file() && path.join("db").is_file()
@@ -168,3 +525,104 @@ async fn fetch_bridge_multiaddrs(ws_url: &str) -> Result<Vec<Multiaddr>> {
 
     Ok(addrs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn celestia_node_key(keypair: &Keypair) -> String {
+        let body = BASE64_STANDARD.encode(keypair.to_protobuf_encoding().unwrap());
+        serde_json::json!({ "name": "p2p-key", "body": body }).to_string()
+    }
+
+    #[test]
+    fn zero_connection_limit_is_unlimited() {
+        let config: Config = toml::from_str(
+            r#"
+            max-connections = 0
+            max-connections-per-peer = 2
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(connection_limit(config.max_connections), None);
+        assert_eq!(connection_limit(config.max_connections_per_peer), Some(2));
+        assert_eq!(
+            connection_limit(config.max_inbound_connections),
+            Some(lumina_node::node::DEFAULT_MAX_INBOUND_CONNECTIONS)
+        );
+    }
+
+    #[tokio::test]
+    async fn generated_keypair_is_reloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("network").join("keypair");
+
+        let generated = load_or_generate_keypair(&path).await.unwrap();
+        assert!(path.is_file());
+
+        let reloaded = load_or_generate_keypair(&path).await.unwrap();
+        assert_eq!(
+            generated.public().to_peer_id(),
+            reloaded.public().to_peer_id()
+        );
+    }
+
+    #[tokio::test]
+    async fn load_invalid_keypair() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keypair");
+        fs::write(&path, b"not a keypair").unwrap();
+
+        load_or_generate_keypair(&path).await.unwrap_err();
+        // invalid keypair must not be overwritten
+        assert_eq!(fs::read(&path).unwrap(), b"not a keypair");
+    }
+
+    #[tokio::test]
+    async fn import_celestia_node_keypair() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("p2p-key");
+        let dst = dir.path().join("network").join("keypair");
+
+        let keypair = Keypair::generate_ed25519();
+        fs::write(&src, celestia_node_key(&keypair)).unwrap();
+
+        import_celestia_node_key(&src, &dst).await.unwrap();
+        let loaded = load_or_generate_keypair(&dst).await.unwrap();
+        assert_eq!(loaded.public().to_peer_id(), keypair.public().to_peer_id());
+
+        // importing the same key again is a no-op
+        import_celestia_node_key(&src, &dst).await.unwrap();
+
+        // importing a different key doesn't overwrite the existing one
+        fs::write(&src, celestia_node_key(&Keypair::generate_ed25519())).unwrap();
+        import_celestia_node_key(&src, &dst).await.unwrap_err();
+        let loaded = load_or_generate_keypair(&dst).await.unwrap();
+        assert_eq!(loaded.public().to_peer_id(), keypair.public().to_peer_id());
+    }
+
+    #[tokio::test]
+    async fn import_malformed_celestia_node_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("p2p-key");
+        let dst = dir.path().join("keypair");
+
+        for content in [
+            "not json",
+            r#"{"name": "p2p-key"}"#,
+            r#"{"name": "p2p-key", "body": "not base64!"}"#,
+            r#"{"name": "p2p-key", "body": "AAECAw=="}"#,
+        ] {
+            fs::write(&src, content).unwrap();
+            import_celestia_node_key(&src, &dst).await.unwrap_err();
+            assert!(!dst.exists());
+        }
+
+        import_celestia_node_key(&dir.path().join("missing"), &dst)
+            .await
+            .unwrap_err();
+        assert!(!dst.exists());
+    }
+}
@@ -0,0 +1,873 @@
+//! JSON-RPC server exposing a subset of the celestia-node API.
+
+use std::fmt::Display;
+use std::future::{ready, Ready};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use celestia_rpc::blob::BlobsAtHeight;
+use celestia_rpc::share::GetRangeResponse;
+use celestia_rpc::{BlobServer, HeaderServer, P2PServer, ShareServer};
+use celestia_types::blob::Commitment;
+use celestia_types::hash::Hash;
+use celestia_types::nmt::{Namespace, NamespaceProof};
+use celestia_types::p2p::{
+    AddrInfo, BandwidthStats, Connectedness, PeerId, Reachability, ResourceManagerStats,
+};
+use celestia_types::{
+    Blob, ExtendedDataSquare, ExtendedHeader, NamespacedShares, Share, SyncState, TxConfig,
+};
+use clap::{Parser, ValueEnum};
+use futures::future::Either;
+use futures::{Stream, StreamExt};
+use jsonrpsee::core::{async_trait, RpcResult, SubscriptionResult};
+use jsonrpsee::server::middleware::rpc::{RpcServiceBuilder, RpcServiceT};
+use jsonrpsee::server::{HttpRequest, Server, ServerHandle};
+use jsonrpsee::types::{ErrorObject, ErrorObjectOwned, Request};
+use jsonrpsee::{
+    Extensions, MethodResponse, PendingSubscriptionSink, RpcModule, SubscriptionMessage,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use lumina_node::blockstore::RedbBlockstore;
+use lumina_node::node::Node;
+use lumina_node::store::{RedbStore, SamplingStatus};
+use serde::{Deserialize, Serialize};
+use tokio::select;
+use tokio::task::spawn_blocking;
+use tracing::{debug, info};
+
+use crate::common::{network_config, ArgNetwork};
+use crate::native::network_dir;
+
+/// Error code used by celestia-node for errors returned from the API methods.
+const API_ERROR_CODE: i32 = 1;
+/// Length of newly generated JWT secrets.
+const JWT_SECRET_LEN: usize = 32;
+
+type RpcNode = Node<RedbBlockstore, RedbStore>;
+
+/// Permission levels of the API, the same as in celestia-node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Permission {
+    Public,
+    Read,
+    Write,
+    Admin,
+}
+
+impl Permission {
+    /// All permissions granted together with this one, e.g. `admin` grants every permission.
+    fn granted(self) -> Vec<Permission> {
+        Permission::value_variants()
+            .iter()
+            .copied()
+            .filter(|perm| *perm <= self)
+            .collect()
+    }
+}
+
+/// Permissions granted to the request, inserted into the request extensions.
+#[derive(Debug, Clone)]
+struct Permissions(Vec<Permission>);
+
+/// Claims of the JWT token, compatible with celestia-node.
+#[derive(Debug, Serialize, Deserialize)]
+struct JwtClaims {
+    #[serde(rename = "Allow")]
+    allow: Vec<Permission>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exp: Option<u64>,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct AuthParams {
+    /// Permission level of the token.
+    #[arg(value_enum)]
+    pub(crate) permission: Permission,
+
+    /// Network the node is running on.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub(crate) network: ArgNetwork,
+
+    /// Path to the JSON definition of a custom network. Takes precedence over `--network`.
+    #[arg(long = "network-config")]
+    pub(crate) network_config: Option<PathBuf>,
+
+    /// Path to the JWT secret used to sign the token.
+    #[arg(long = "jwt-secret")]
+    pub(crate) jwt_secret: Option<PathBuf>,
+}
+
+/// Print a new auth token for the RPC server of the node.
+pub(crate) async fn auth(args: AuthParams) -> Result<()> {
+    let secret_path = match args.jwt_secret {
+        Some(path) => path,
+        None => {
+            let network = network_config(args.network, args.network_config.as_deref()).await?;
+            default_jwt_secret_path(&network.id)?
+        }
+    };
+
+    let secret = load_or_generate_jwt_secret(&secret_path).await?;
+    println!("{}", create_token(&secret, args.permission.granted())?);
+
+    Ok(())
+}
+
+pub(crate) fn default_jwt_secret_path(network_id: &str) -> Result<PathBuf> {
+    Ok(network_dir(network_id)?.join("jwt_secret"))
+}
+
+/// Load the JWT secret from the given path or generate a new one and save it there.
+pub(crate) async fn load_or_generate_jwt_secret(path: &Path) -> Result<Vec<u8>> {
+    let path = path.to_owned();
+
+    spawn_blocking(move || {
+        use std::fs;
+        use std::io::Write;
+
+        if path.exists() {
+            let secret = fs::read(&path)
+                .with_context(|| format!("failed to read JWT secret from {}", path.display()))?;
+
+            if secret.is_empty() {
+                bail!("JWT secret in {} is empty", path.display());
+            }
+
+            return Ok(secret);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let secret: [u8; JWT_SECRET_LEN] = rand::random();
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options
+            .open(&path)
+            .with_context(|| format!("failed to create JWT secret file {}", path.display()))?;
+        file.write_all(&secret)?;
+        info!("Generated new JWT secret in {}", path.display());
+
+        Ok(secret.to_vec())
+    })
+    .await?
+}
+
+/// Create a HS256 JWT token granting given permissions.
+fn create_token(secret: &[u8], allow: Vec<Permission>) -> Result<String> {
+    let claims = JwtClaims { allow, exp: None };
+    let token = jsonwebtoken::encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(secret),
+    )?;
+
+    Ok(token)
+}
+
+/// Verify HS256 JWT token and return permissions it grants.
+///
+/// Tokens created by celestia-node don't expire, so the expiry is checked only if present.
+fn verify_token(secret: &[u8], token: &str) -> Result<Vec<Permission>> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.required_spec_claims.clear();
+
+    let claims: JwtClaims =
+        jsonwebtoken::decode(token, &DecodingKey::from_secret(secret), &validation)?.claims;
+
+    Ok(claims.allow)
+}
+
+/// Permissions granted to a request with a given `Authorization` header value.
+fn request_permissions(secret: Option<&[u8]>, auth_header: Option<&str>) -> Vec<Permission> {
+    let Some(secret) = secret else {
+        // authentication is disabled
+        return Permission::Admin.granted();
+    };
+
+    let Some(token) = auth_header.and_then(|value| value.strip_prefix("Bearer ")) else {
+        return vec![Permission::Public];
+    };
+
+    verify_token(secret, token.trim()).unwrap_or_else(|e| {
+        debug!("Rejected auth token: {e}");
+        vec![Permission::Public]
+    })
+}
+
+/// Start the JSON-RPC server for the node.
+///
+/// If `jwt_secret` is `None`, authentication is disabled and every request is
+/// granted all the permissions.
+pub(crate) async fn start(
+    listen_addr: SocketAddr,
+    node: Arc<RpcNode>,
+    jwt_secret: Option<Vec<u8>>,
+) -> Result<(ServerHandle, SocketAddr)> {
+    let jwt_secret = jwt_secret.map(Arc::new);
+
+    let http_middleware = tower::ServiceBuilder::new().map_request(move |mut req: HttpRequest| {
+        let auth_header = req
+            .headers()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok());
+        let perms = request_permissions(jwt_secret.as_deref().map(Vec::as_slice), auth_header);
+
+        req.extensions_mut().insert(Permissions(perms));
+        req
+    });
+    let rpc_middleware = RpcServiceBuilder::new().layer_fn(|service| PermissionCheck { service });
+
+    let server = Server::builder()
+        .set_http_middleware(http_middleware)
+        .set_rpc_middleware(rpc_middleware)
+        .build(listen_addr)
+        .await
+        .with_context(|| format!("failed to bind RPC server to {listen_addr}"))?;
+
+    let local_addr = server.local_addr()?;
+    info!("RPC server listening on {local_addr}");
+
+    Ok((server.start(rpc_module(node)?), local_addr))
+}
+
+fn rpc_module(node: Arc<RpcNode>) -> Result<RpcModule<()>> {
+    let server = RpcServer { node };
+    let mut module = RpcModule::new(());
+
+    module.merge(HeaderServer::into_rpc(server.clone()))?;
+    module.merge(ShareServer::into_rpc(server.clone()))?;
+    module.merge(BlobServer::into_rpc(server.clone()))?;
+    module.merge(P2PServer::into_rpc(server))?;
+
+    Ok(module)
+}
+
+/// Permission required to invoke the given method, the same as in celestia-node.
+fn required_permission(method: &str) -> Permission {
+    match method.split_once('.') {
+        Some(("blob", "Submit")) => Permission::Write,
+        Some(("header" | "share" | "blob", _)) => Permission::Read,
+        _ => Permission::Admin,
+    }
+}
+
+fn check_permission(extensions: &Extensions, method: &str) -> Result<(), ErrorObjectOwned> {
+    let permission = required_permission(method);
+    let granted = extensions
+        .get::<Permissions>()
+        .is_some_and(|perms| perms.0.contains(&permission));
+
+    if granted {
+        Ok(())
+    } else {
+        let permission = permission.to_possible_value().expect("no skipped variants");
+
+        Err(api_error(format!(
+            "missing permission to invoke '{method}' (need '{}')",
+            permission.get_name()
+        )))
+    }
+}
+
+/// RPC middleware rejecting calls without the permission required by the method.
+#[derive(Clone)]
+struct PermissionCheck<S> {
+    service: S,
+}
+
+impl<'a, S> RpcServiceT<'a> for PermissionCheck<S>
+where
+    S: RpcServiceT<'a>,
+{
+    type Future = Either<S::Future, Ready<MethodResponse>>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        match check_permission(request.extensions(), request.method_name()) {
+            Ok(()) => Either::Left(self.service.call(request)),
+            Err(e) => Either::Right(ready(MethodResponse::error(request.id, e))),
+        }
+    }
+}
+
+/// Implementation of the celestia-node API on top of the [`Node`].
+#[derive(Clone)]
+struct RpcServer {
+    node: Arc<RpcNode>,
+}
+
+#[async_trait]
+impl HeaderServer for RpcServer {
+    async fn header_get_by_hash(&self, hash: Hash) -> RpcResult<ExtendedHeader> {
+        self.node.get_header_by_hash(&hash).await.map_err(api_error)
+    }
+
+    async fn header_get_by_height(&self, height: u64) -> RpcResult<ExtendedHeader> {
+        self.node
+            .get_header_by_height(height)
+            .await
+            .map_err(api_error)
+    }
+
+    async fn header_get_range_by_height(
+        &self,
+        from: ExtendedHeader,
+        to: u64,
+    ) -> RpcResult<Vec<ExtendedHeader>> {
+        self.node
+            .get_headers(from.height().value() + 1..to)
+            .await
+            .map_err(api_error)
+    }
+
+    async fn header_local_head(&self) -> RpcResult<ExtendedHeader> {
+        self.node.get_local_head_header().await.map_err(api_error)
+    }
+
+    async fn header_network_head(&self) -> RpcResult<ExtendedHeader> {
+        self.node
+            .get_network_head_header()
+            .await
+            .map_err(api_error)?
+            .ok_or_else(|| api_error("network head not known yet"))
+    }
+
+    async fn header_subscribe(&self, pending: PendingSubscriptionSink) -> SubscriptionResult {
+        let headers = self
+            .node
+            .subscribe_headers()
+            .filter_map(|header| async move {
+                header
+                    .inspect_err(|e| debug!("Header subscription: {e}"))
+                    .ok()
+            });
+
+        forward_subscription(pending, headers).await
+    }
+
+    async fn header_sync_state(&self) -> RpcResult<SyncState> {
+        Err(not_supported("header.SyncState"))
+    }
+
+    async fn header_sync_wait(&self) -> RpcResult<()> {
+        Err(not_supported("header.SyncWait"))
+    }
+
+    async fn header_wait_for_height(&self, height: u64) -> RpcResult<ExtendedHeader> {
+        self.node.wait_for_height(height).await.map_err(api_error)
+    }
+}
+
+#[async_trait]
+impl ShareServer for RpcServer {
+    async fn share_get_eds(&self, _root: ExtendedHeader) -> RpcResult<ExtendedDataSquare> {
+        Err(not_supported("share.GetEDS"))
+    }
+
+    async fn share_get_range(
+        &self,
+        _height: u64,
+        _start: usize,
+        _end: usize,
+    ) -> RpcResult<GetRangeResponse> {
+        Err(not_supported("share.GetRange"))
+    }
+
+    async fn share_get_share(
+        &self,
+        _root: ExtendedHeader,
+        _row: u64,
+        _col: u64,
+    ) -> RpcResult<Share> {
+        Err(not_supported("share.GetShare"))
+    }
+
+    async fn share_get_shares_by_namespace(
+        &self,
+        _root: ExtendedHeader,
+        _namespace: Namespace,
+    ) -> RpcResult<NamespacedShares> {
+        Err(not_supported("share.GetSharesByNamespace"))
+    }
+
+    async fn share_shares_available(&self, root: ExtendedHeader) -> RpcResult<()> {
+        let height = root.height().value();
+
+        match self
+            .node
+            .get_sampling_metadata(height)
+            .await
+            .map_err(api_error)?
+        {
+            Some(metadata) if metadata.status == SamplingStatus::Accepted => Ok(()),
+            _ => Err(api_error(format!("data not available for height {height}"))),
+        }
+    }
+}
+
+#[async_trait]
+impl BlobServer for RpcServer {
+    async fn blob_get(
+        &self,
+        height: u64,
+        namespace: Namespace,
+        commitment: Commitment,
+    ) -> RpcResult<Blob> {
+        self.node
+            .request_blob(namespace, height, commitment)
+            .await
+            .map_err(api_error)?
+            .ok_or_else(|| api_error("blob: not found"))
+    }
+
+    async fn blob_get_all(
+        &self,
+        height: u64,
+        namespaces: Vec<Namespace>,
+    ) -> RpcResult<Option<Vec<Blob>>> {
+        let mut blobs = Vec::new();
+
+        for namespace in namespaces {
+            let namespace_blobs = self
+                .node
+                .request_blobs(namespace, height)
+                .await
+                .map_err(api_error)?;
+            blobs.extend(namespace_blobs);
+        }
+
+        // celestia-node returns null instead of an empty list
+        Ok((!blobs.is_empty()).then_some(blobs))
+    }
+
+    async fn blob_get_proof(
+        &self,
+        _height: u64,
+        _namespace: Namespace,
+        _commitment: Commitment,
+    ) -> RpcResult<Vec<NamespaceProof>> {
+        Err(not_supported("blob.GetProof"))
+    }
+
+    async fn blob_included(
+        &self,
+        _height: u64,
+        _namespace: Namespace,
+        _proof: NamespaceProof,
+        _commitment: Commitment,
+    ) -> RpcResult<bool> {
+        Err(not_supported("blob.Included"))
+    }
+
+    async fn blob_submit(&self, _blobs: Vec<Blob>, _opts: TxConfig) -> RpcResult<u64> {
+        Err(not_supported("blob.Submit"))
+    }
+
+    async fn blob_subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        namespace: Namespace,
+    ) -> SubscriptionResult {
+        let blobs = self
+            .node
+            .subscribe_blobs(namespace, None, false)
+            .filter_map(|blobs| async move {
+                let blobs = blobs
+                    .inspect_err(|e| debug!("Blob subscription: {e}"))
+                    .ok()?;
+
+                Some(BlobsAtHeight {
+                    height: blobs.height,
+                    // celestia-node returns null instead of an empty list
+                    blobs: (!blobs.blobs.is_empty()).then_some(blobs.blobs),
+                })
+            });
+
+        forward_subscription(pending, blobs).await
+    }
+}
+
+#[async_trait]
+impl P2PServer for RpcServer {
+    async fn p2p_bandwidth_for_peer(&self, _peer_id: PeerId) -> RpcResult<BandwidthStats> {
+        Err(not_supported("p2p.BandwidthForPeer"))
+    }
+
+    async fn p2p_bandwidth_for_protocol(&self, _protocol_id: String) -> RpcResult<BandwidthStats> {
+        Err(not_supported("p2p.BandwidthForProtocol"))
+    }
+
+    async fn p2p_bandwidth_stats(&self) -> RpcResult<BandwidthStats> {
+        Err(not_supported("p2p.BandwidthStats"))
+    }
+
+    async fn p2p_block_peer(&self, _peer_id: PeerId) -> RpcResult<()> {
+        Err(not_supported("p2p.BlockPeer"))
+    }
+
+    async fn p2p_close_peer(&self, _peer_id: PeerId) -> RpcResult<()> {
+        Err(not_supported("p2p.ClosePeer"))
+    }
+
+    async fn p2p_connect(&self, _address: AddrInfo) -> RpcResult<()> {
+        Err(not_supported("p2p.Connect"))
+    }
+
+    async fn p2p_connectedness(&self, _peer_id: PeerId) -> RpcResult<Connectedness> {
+        Err(not_supported("p2p.Connectedness"))
+    }
+
+    async fn p2p_info(&self) -> RpcResult<AddrInfo> {
+        let addrs = self.node.listeners().await.map_err(api_error)?;
+
+        Ok(AddrInfo {
+            id: PeerId(*self.node.local_peer_id()),
+            addrs,
+        })
+    }
+
+    async fn p2p_is_protected(&self, _peer_id: PeerId, _tag: String) -> RpcResult<bool> {
+        Err(not_supported("p2p.IsProtected"))
+    }
+
+    async fn p2p_list_blocked_peers(&self) -> RpcResult<Vec<PeerId>> {
+        Err(not_supported("p2p.ListBlockedPeers"))
+    }
+
+    async fn p2p_nat_status(&self) -> RpcResult<Reachability> {
+        Err(not_supported("p2p.NATStatus"))
+    }
+
+    async fn p2p_peer_info(&self, _peer_id: PeerId) -> RpcResult<AddrInfo> {
+        Err(not_supported("p2p.PeerInfo"))
+    }
+
+    async fn p2p_peers(&self) -> RpcResult<Vec<PeerId>> {
+        let peers = self.node.connected_peers().await.map_err(api_error)?;
+        Ok(peers.into_iter().map(PeerId).collect())
+    }
+
+    async fn p2p_protect(&self, _peer_id: PeerId, _tag: String) -> RpcResult<()> {
+        Err(not_supported("p2p.Protect"))
+    }
+
+    async fn p2p_pub_sub_peers(&self, _topic: String) -> RpcResult<Option<Vec<PeerId>>> {
+        Err(not_supported("p2p.PubSubPeers"))
+    }
+
+    async fn p2p_resource_state(&self) -> RpcResult<ResourceManagerStats> {
+        Err(not_supported("p2p.ResourceState"))
+    }
+
+    async fn p2p_unblock_peer(&self, _peer_id: PeerId) -> RpcResult<()> {
+        Err(not_supported("p2p.UnblockPeer"))
+    }
+
+    async fn p2p_unprotect(&self, _peer_id: PeerId, _tag: String) -> RpcResult<bool> {
+        Err(not_supported("p2p.Unprotect"))
+    }
+}
+
+/// Accept the subscription and forward items of the stream until either of them is closed.
+async fn forward_subscription<T>(
+    pending: PendingSubscriptionSink,
+    stream: impl Stream<Item = T> + Send,
+) -> SubscriptionResult
+where
+    T: Serialize,
+{
+    let sink = pending.accept().await?;
+    let mut stream = pin!(stream);
+
+    loop {
+        let item = select! {
+            _ = sink.closed() => break,
+            item = stream.next() => item,
+        };
+
+        let Some(item) = item else {
+            break;
+        };
+
+        sink.send(SubscriptionMessage::from_json(&item)?).await?;
+    }
+
+    Ok(())
+}
+
+fn not_supported(method: &str) -> ErrorObjectOwned {
+    api_error(format!("method '{method}' is not supported by lumina"))
+}
+
+fn api_error(e: impl Display) -> ErrorObjectOwned {
+    ErrorObject::owned(API_ERROR_CODE, e.to_string(), None::<()>)
+}
+
+#[cfg(test)]
+mod tests {
+    use celestia_rpc::prelude::*;
+    use celestia_rpc::Client;
+    use celestia_types::test_utils::ExtendedHeaderGenerator;
+    use jsonrpsee::core::client::ClientT;
+    use jsonrpsee::core::ClientError;
+    use jsonrpsee::rpc_params;
+    use libp2p::identity::Keypair;
+    use lumina_node::network::Network;
+    use lumina_node::node::{
+        ConnectionLimits, NodeConfig, DEFAULT_MAX_SAMPLES_NEEDED, DEFAULT_PEER_BAN_DURATION,
+    };
+    use lumina_node::store::Store;
+    use lumina_node::test_utils::ExtendedHeaderGeneratorExt;
+
+    use super::*;
+
+    const SECRET: &[u8] = b"secret";
+
+    #[test]
+    fn token_roundtrip() {
+        let secret = b"secret";
+        let token = create_token(secret, Permission::Write.granted()).unwrap();
+
+        assert_eq!(
+            verify_token(secret, &token).unwrap(),
+            vec![Permission::Public, Permission::Read, Permission::Write]
+        );
+        verify_token(b"other secret", &token).unwrap_err();
+    }
+
+    #[test]
+    fn request_permissions_from_header() {
+        let secret = b"secret";
+        let token = create_token(secret, Permission::Admin.granted()).unwrap();
+
+        assert_eq!(
+            request_permissions(Some(secret), Some(&format!("Bearer {token}"))),
+            Permission::Admin.granted()
+        );
+        assert_eq!(
+            request_permissions(Some(secret), None),
+            vec![Permission::Public]
+        );
+        assert_eq!(
+            request_permissions(Some(secret), Some("Bearer invalid")),
+            vec![Permission::Public]
+        );
+        assert_eq!(request_permissions(None, None), Permission::Admin.granted());
+    }
+
+    #[test]
+    fn reject_token_with_other_algorithm() {
+        let claims = JwtClaims {
+            allow: Permission::Admin.granted(),
+            exp: None,
+        };
+        let token = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS512),
+            &claims,
+            &EncodingKey::from_secret(SECRET),
+        )
+        .unwrap();
+
+        verify_token(SECRET, &token).unwrap_err();
+    }
+
+    #[test]
+    fn reject_expired_token() {
+        let claims = JwtClaims {
+            allow: Permission::Admin.granted(),
+            exp: Some(jsonwebtoken::get_current_timestamp() - 3600),
+        };
+        let token = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(SECRET),
+        )
+        .unwrap();
+
+        verify_token(SECRET, &token).unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn header_namespace() {
+        let server = TestServer::start().await;
+        let client = server.client(Permission::Read).await;
+
+        assert_eq!(client.header_local_head().await.unwrap(), server.head);
+        assert_eq!(
+            client
+                .header_get_by_height(server.head.height().value())
+                .await
+                .unwrap(),
+            server.head
+        );
+    }
+
+    #[tokio::test]
+    async fn share_namespace() {
+        let server = TestServer::start().await;
+        let client = server.client(Permission::Read).await;
+
+        // only the head was sampled
+        client.share_shares_available(&server.head).await.unwrap();
+
+        let first = client.header_get_by_height(1).await.unwrap();
+        let e = client.share_shares_available(&first).await.unwrap_err();
+        assert_call_error(e, "data not available for height 1");
+    }
+
+    #[tokio::test]
+    async fn blob_namespace() {
+        let server = TestServer::start().await;
+        let client = server.client(Permission::Write).await;
+
+        let e = client
+            .blob_get_all(100, &[Namespace::new_v0(&[1, 2, 3]).unwrap()])
+            .await
+            .unwrap_err();
+        assert_call_error(e, "not found");
+
+        let e = client
+            .blob_submit(&[], TxConfig::default())
+            .await
+            .unwrap_err();
+        assert_call_error(e, "method 'blob.Submit' is not supported");
+    }
+
+    #[tokio::test]
+    async fn p2p_namespace() {
+        let server = TestServer::start().await;
+        let client = server.client(Permission::Admin).await;
+
+        let info = client.p2p_info().await.unwrap();
+        assert_eq!(info.id.0, server.peer_id);
+        assert!(client.p2p_peers().await.unwrap().is_empty());
+
+        // the client sends methods without a return value as notifications
+        let e = client
+            .request::<(), _>("p2p.BlockPeer", rpc_params![info.id])
+            .await
+            .unwrap_err();
+        assert_call_error(e, "method 'p2p.BlockPeer' is not supported");
+    }
+
+    #[tokio::test]
+    async fn unauthorised_calls() {
+        let server = TestServer::start().await;
+
+        let client = Client::new(&server.url, None).await.unwrap();
+        let e = client.header_local_head().await.unwrap_err();
+        assert_call_error(
+            e,
+            "missing permission to invoke 'header.LocalHead' (need 'read')",
+        );
+
+        let token = create_token(b"other secret", Permission::Admin.granted()).unwrap();
+        let client = Client::new(&server.url, Some(&token)).await.unwrap();
+        let e = client.header_local_head().await.unwrap_err();
+        assert_call_error(
+            e,
+            "missing permission to invoke 'header.LocalHead' (need 'read')",
+        );
+
+        let client = server.client(Permission::Read).await;
+        let e = client
+            .blob_submit(&[], TxConfig::default())
+            .await
+            .unwrap_err();
+        assert_call_error(
+            e,
+            "missing permission to invoke 'blob.Submit' (need 'write')",
+        );
+        let e = client.p2p_info().await.unwrap_err();
+        assert_call_error(e, "missing permission to invoke 'p2p.Info' (need 'admin')");
+    }
+
+    struct TestServer {
+        _handle: ServerHandle,
+        url: String,
+        peer_id: libp2p::PeerId,
+        head: ExtendedHeader,
+    }
+
+    impl TestServer {
+        /// Start the server for a node with 10 stored headers, of which only the head was sampled.
+        async fn start() -> TestServer {
+            let store = RedbStore::in_memory().await.unwrap();
+            let mut gen = ExtendedHeaderGenerator::new();
+            store.insert(gen.next_many_verified(10)).await.unwrap();
+            let head = store.get_head().await.unwrap();
+            store
+                .update_sampling_metadata(head.height().value(), SamplingStatus::Accepted, vec![])
+                .await
+                .unwrap();
+
+            let node = Node::new(NodeConfig {
+                network: Network::Private.into(),
+                p2p_local_keypair: Keypair::generate_ed25519(),
+                p2p_bootnodes: vec![],
+                p2p_listen_on: vec![],
+                p2p_peer_ban_duration: DEFAULT_PEER_BAN_DURATION,
+                p2p_connection_limits: ConnectionLimits::default(),
+                sync_batch_size: 512,
+                sync_trusted_checkpoint: None,
+                sync_window: None,
+                sync_archival: false,
+                sampling_window: None,
+                sampling_max_samples_needed: DEFAULT_MAX_SAMPLES_NEEDED,
+                sampling_full_storage: false,
+                pruning_window: None,
+                pruning_disabled: false,
+                blockstore: RedbBlockstore::in_memory().unwrap(),
+                store,
+            })
+            .await
+            .unwrap();
+            let peer_id = *node.local_peer_id();
+
+            let (handle, addr) = start(
+                "127.0.0.1:0".parse().unwrap(),
+                Arc::new(node),
+                Some(SECRET.to_vec()),
+            )
+            .await
+            .unwrap();
+
+            TestServer {
+                _handle: handle,
+                url: format!("http://{addr}"),
+                peer_id,
+                head,
+            }
+        }
+
+        async fn client(&self, permission: Permission) -> Client {
+            let token = create_token(SECRET, permission.granted()).unwrap();
+            Client::new(&self.url, Some(&token)).await.unwrap()
+        }
+    }
+
+    fn assert_call_error(e: ClientError, expected: &str) {
+        match e {
+            ClientError::Call(e) => assert!(
+                e.message().contains(expected),
+                "'{}' doesn't contain '{expected}'",
+                e.message()
+            ),
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+}
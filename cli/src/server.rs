@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use anyhow::Result;
 use axum::body::Body;
@@ -9,19 +10,20 @@ use axum::routing::get;
 use axum::{Json, Router};
 use clap::Args;
 use libp2p::Multiaddr;
-use lumina_node::network::canonical_network_bootnodes;
+use lumina_node::network::NetworkConfig;
 use rust_embed::RustEmbed;
 use serde::Serialize;
 use tokio::net::TcpListener;
 use tracing::info;
 
-use crate::common::ArgNetwork;
+use crate::common::{network_config, ArgNetwork};
 
 const SERVER_DEFAULT_BIND_ADDR: &str = "127.0.0.1:9876";
 
 #[derive(Debug, Clone, Serialize)]
 struct WasmNodeArgs {
     pub network: ArgNetwork,
+    pub network_config: Option<NetworkConfig>,
     pub bootnodes: Vec<Multiaddr>,
 }
 
@@ -39,6 +41,10 @@ pub(crate) struct Params {
     #[arg(short, long, value_enum, default_value_t)]
     pub(crate) network: ArgNetwork,
 
+    /// Path to the JSON definition of a custom network. Takes precedence over `--network`.
+    #[arg(long = "network-config")]
+    pub(crate) network_config: Option<PathBuf>,
+
     /// Listening addresses. Can be used multiple times.
     #[arg(short, long = "listen", default_value = SERVER_DEFAULT_BIND_ADDR)]
     pub(crate) listen_addr: SocketAddr,
@@ -49,15 +55,16 @@ pub(crate) struct Params {
 }
 
 pub(crate) async fn run(args: Params) -> Result<()> {
-    let network = args.network.into();
+    let network = network_config(args.network, args.network_config.as_deref()).await?;
     let bootnodes = if args.bootnodes.is_empty() {
-        canonical_network_bootnodes(network).collect()
+        network.bootnodes.clone()
     } else {
         args.bootnodes
     };
 
     let state = WasmNodeArgs {
         network: args.network,
+        network_config: args.network_config.is_some().then_some(network),
         bootnodes,
     };
 
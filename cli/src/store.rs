@@ -0,0 +1,416 @@
+//! Commands operating on the store of a stopped node.
+//!
+//! None of them starts networking, so they can be used to inspect and repair
+//! the store of a misbehaving node.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use celestia_types::hash::Hash;
+use celestia_types::ExtendedHeader;
+use clap::{Args, Parser, Subcommand};
+use futures::io::AllowStdIo;
+use lumina_node::store::{
+    export_snapshot, import_snapshot, BlockRanges, RedbStore, SamplingStatus, Store,
+};
+use serde_json::json;
+use tokio::task::spawn_blocking;
+use tracing::info;
+
+use crate::common::{network_config, ArgNetwork};
+use crate::native::{default_db_path, open_db};
+
+/// Number of headers verified at once.
+const VERIFY_BATCH_SIZE: u64 = 512;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum StoreCmd {
+    /// Print schema version, size and ranges of the stored headers and samples
+    Info(StoreParams),
+    /// Print a stored header as JSON
+    Header(HeaderParams),
+    /// Print sampling metadata of a block as JSON
+    Sampling(SamplingParams),
+    /// Verify that all the stored headers form valid chains
+    Verify(StoreParams),
+    /// Export headers to a snapshot file
+    Export(ExportParams),
+    /// Import headers from a snapshot file
+    Import(ImportParams),
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct StoreParams {
+    /// Network the store belongs to.
+    #[arg(short, long, value_enum, default_value_t)]
+    pub(crate) network: ArgNetwork,
+
+    /// Path to the JSON definition of a custom network. Takes precedence over `--network`.
+    #[arg(long = "network-config")]
+    pub(crate) network_config: Option<PathBuf>,
+
+    /// Persistent header store path.
+    #[arg(short, long = "store")]
+    pub(crate) store: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct HeaderParams {
+    #[command(flatten)]
+    pub(crate) store: StoreParams,
+
+    #[command(flatten)]
+    pub(crate) header: HeaderId,
+}
+
+#[derive(Debug, Args)]
+#[group(required = true, multiple = false)]
+pub(crate) struct HeaderId {
+    /// Height of the header.
+    #[arg(long = "height")]
+    pub(crate) height: Option<u64>,
+
+    /// Hash of the header.
+    #[arg(long = "hash")]
+    pub(crate) hash: Option<Hash>,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct SamplingParams {
+    #[command(flatten)]
+    pub(crate) store: StoreParams,
+
+    /// Height of the block.
+    pub(crate) height: u64,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct ExportParams {
+    #[command(flatten)]
+    pub(crate) store: StoreParams,
+
+    /// Path of the snapshot file to create.
+    pub(crate) output: PathBuf,
+
+    /// Lowest height to export. Defaults to the lowest stored height.
+    #[arg(long = "from")]
+    pub(crate) from: Option<u64>,
+
+    /// Highest height to export. Defaults to the highest stored height.
+    #[arg(long = "to")]
+    pub(crate) to: Option<u64>,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct ImportParams {
+    #[command(flatten)]
+    pub(crate) store: StoreParams,
+
+    /// Path of the snapshot file to import.
+    pub(crate) input: PathBuf,
+
+    /// Hash of the highest header in the snapshot, if it can't be verified
+    /// against the headers already in the store.
+    #[arg(long = "trusted-hash")]
+    pub(crate) trusted_hash: Option<Hash>,
+
+    /// Restore sampling results of the blocks from the snapshot, instead of sampling
+    /// them again. Use only for snapshots from a trusted source.
+    #[arg(long = "restore-sampling")]
+    pub(crate) restore_sampling: bool,
+}
+
+pub(crate) async fn run(cmd: StoreCmd) -> Result<()> {
+    match cmd {
+        StoreCmd::Info(args) => print_info(args).await,
+        StoreCmd::Header(args) => print_header(args).await,
+        StoreCmd::Sampling(args) => print_sampling_metadata(args).await,
+        StoreCmd::Verify(args) => verify(args).await,
+        StoreCmd::Export(args) => export(args).await,
+        StoreCmd::Import(args) => import(args).await,
+    }
+}
+
+impl StoreParams {
+    async fn network_id(&self) -> Result<String> {
+        let network = network_config(self.network, self.network_config.as_deref()).await?;
+        Ok(network.id)
+    }
+
+    async fn db_path(&self) -> Result<PathBuf> {
+        match self.store {
+            Some(ref path) => Ok(path.clone()),
+            None => default_db_path(&self.network_id().await?),
+        }
+    }
+
+    /// Open the existing store of a stopped node.
+    async fn open_existing(&self) -> Result<RedbStore> {
+        let path = self.db_path().await?;
+
+        RedbStore::open_existing(&path)
+            .await
+            .with_context(|| format!("failed to open store {}", path.display()))
+    }
+}
+
+async fn print_info(args: StoreParams) -> Result<()> {
+    let path = args.db_path().await?;
+    let store = args.open_existing().await?;
+
+    let size = spawn_blocking({
+        let path = path.clone();
+        move || std::fs::metadata(path)
+    })
+    .await??
+    .len();
+
+    println!("Path: {}", path.display());
+    println!("Size: {size} bytes");
+    println!("Schema version: {}", store.schema_version().await?);
+    println!(
+        "Stored headers: {}",
+        store.get_stored_header_ranges().await?
+    );
+    println!(
+        "Accepted samples: {}",
+        store.get_accepted_sampling_ranges().await?
+    );
+
+    Ok(())
+}
+
+async fn print_header(args: HeaderParams) -> Result<()> {
+    let store = args.store.open_existing().await?;
+
+    let header = match (args.header.height, args.header.hash) {
+        (Some(height), _) => store
+            .get_by_height(height)
+            .await
+            .with_context(|| format!("failed to get header {height}"))?,
+        (_, Some(hash)) => store
+            .get_by_hash(&hash)
+            .await
+            .with_context(|| format!("failed to get header {hash}"))?,
+        (None, None) => unreachable!("header id is required"),
+    };
+
+    println!("{}", serde_json::to_string_pretty(&header)?);
+
+    Ok(())
+}
+
+async fn print_sampling_metadata(args: SamplingParams) -> Result<()> {
+    let store = args.store.open_existing().await?;
+    let height = args.height;
+
+    let metadata = store
+        .get_sampling_metadata(height)
+        .await
+        .with_context(|| format!("failed to get sampling metadata {height}"))?;
+
+    let json = match metadata {
+        Some(metadata) => {
+            let status = match metadata.status {
+                SamplingStatus::Unknown => "unknown",
+                SamplingStatus::Accepted => "accepted",
+                SamplingStatus::Rejected => "rejected",
+            };
+            let cids: Vec<_> = metadata.cids.iter().map(|cid| cid.to_string()).collect();
+
+            json!({ "height": height, "status": status, "cids": cids })
+        }
+        None => json!({ "height": height, "status": null, "cids": [] }),
+    };
+
+    println!("{}", serde_json::to_string_pretty(&json)?);
+
+    Ok(())
+}
+
+async fn verify(args: StoreParams) -> Result<()> {
+    let store = args.open_existing().await?;
+    let ranges = store.get_stored_header_ranges().await?;
+    let mut failures = 0;
+
+    for range in ranges.as_ref() {
+        info!("Verifying headers {}-{}", range.start(), range.end());
+
+        let mut prev: Option<ExtendedHeader> = None;
+        let mut start = *range.start();
+
+        while start <= *range.end() {
+            let end = (*range.end()).min(start + VERIFY_BATCH_SIZE - 1);
+
+            match verify_batch(&store, start..=end, prev.as_ref()).await {
+                Ok(last) => prev = Some(last),
+                Err(e) => {
+                    println!("Headers {start}-{end} are invalid: {e:#}");
+                    failures += 1;
+                    prev = None;
+                }
+            }
+
+            start = end + 1;
+        }
+    }
+
+    if failures > 0 {
+        bail!("Found {failures} invalid batches of headers");
+    }
+
+    println!("All stored headers are valid: {ranges}");
+
+    Ok(())
+}
+
+/// Verify the adjacent headers of the range against each other and the previous header.
+///
+/// Returns the last header of the range.
+async fn verify_batch(
+    store: &RedbStore,
+    range: RangeInclusive<u64>,
+    prev: Option<&ExtendedHeader>,
+) -> Result<ExtendedHeader> {
+    let mut headers = store.get_range(range).await?;
+
+    for header in &headers {
+        header
+            .validate()
+            .with_context(|| format!("header {} failed validation", header.height()))?;
+    }
+
+    match prev {
+        Some(prev) => prev.verify_adjacent_range(&headers)?,
+        None => {
+            if let Some((first, rest)) = headers.split_first() {
+                first.verify_adjacent_range(rest)?;
+            }
+        }
+    }
+
+    headers.pop().context("empty range of headers")
+}
+
+async fn export(args: ExportParams) -> Result<()> {
+    let store = args.store.open_existing().await?;
+    let stored = store.get_stored_header_ranges().await?;
+
+    let (Some(tail), Some(head)) = (stored.tail(), stored.head()) else {
+        bail!("Store is empty");
+    };
+
+    let from = args.from.unwrap_or(tail);
+    let to = args.to.unwrap_or(head);
+
+    if from > to {
+        bail!("Invalid range: {from}-{to}");
+    }
+
+    // Export only the stored part of the requested range
+    let mut not_requested = BlockRanges::new();
+    if from > 1 {
+        not_requested.insert_relaxed(1..=from - 1)?;
+    }
+    if to < head {
+        not_requested.insert_relaxed(to + 1..=head)?;
+    }
+    let ranges = stored - not_requested;
+
+    let file = File::create(&args.output)
+        .with_context(|| format!("failed to create {}", args.output.display()))?;
+    let writer = AllowStdIo::new(BufWriter::new(file));
+
+    info!("Exporting headers {ranges} to {}", args.output.display());
+    let exported = export_snapshot(&store, &ranges, writer).await?;
+    info!("Exported {exported} headers");
+
+    Ok(())
+}
+
+async fn import(args: ImportParams) -> Result<()> {
+    let network_id = args.store.network_id().await?;
+    let db = open_db(args.store.store, &network_id).await?;
+    let store = RedbStore::new(db).await?;
+
+    let file = File::open(&args.input)
+        .with_context(|| format!("failed to open {}", args.input.display()))?;
+    let reader = AllowStdIo::new(BufReader::new(file));
+
+    info!("Importing headers from {}", args.input.display());
+    let imported =
+        import_snapshot(&store, reader, args.trusted_hash, args.restore_sampling).await?;
+    info!(
+        "Imported {imported} headers, present headers: {}",
+        store.get_stored_header_ranges().await?
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use celestia_types::test_utils::{invalidate, ExtendedHeaderGenerator};
+
+    fn store_params(path: &std::path::Path) -> StoreParams {
+        StoreParams {
+            network: ArgNetwork::default(),
+            network_config: None,
+            store: Some(path.to_owned()),
+        }
+    }
+
+    #[tokio::test]
+    async fn info_of_stopped_node_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db");
+
+        // Store must exist
+        print_info(store_params(&path)).await.unwrap_err();
+        assert!(!path.exists());
+
+        let store = RedbStore::open(&path).await.unwrap();
+        let mut gen = ExtendedHeaderGenerator::new();
+        store.insert(gen.next_many(10)).await.unwrap();
+
+        // Store is locked while opened by a node
+        print_info(store_params(&path)).await.unwrap_err();
+
+        drop(store);
+        print_info(store_params(&path)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_stored_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db");
+
+        let store = RedbStore::open(&path).await.unwrap();
+        let mut gen = ExtendedHeaderGenerator::new();
+        store.insert(gen.next_many(10)).await.unwrap();
+        gen.skip(5);
+        store.insert(gen.next_many(10)).await.unwrap();
+        drop(store);
+
+        verify(store_params(&path)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_invalid_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db");
+
+        let store = RedbStore::open(&path).await.unwrap();
+        let mut headers = ExtendedHeaderGenerator::new().next_many(10);
+        // passes the verification on insertion but not the validation
+        invalidate(&mut headers[5]);
+        store.insert(headers).await.unwrap();
+        drop(store);
+
+        verify(store_params(&path)).await.unwrap_err();
+    }
+}
@@ -8,7 +8,9 @@ async function fetch_config() {
 
   console.log("Received config:", json);
 
-  let config = NodeConfig.default(json.network);
+  let config = json.network_config
+    ? NodeConfig.withNetworkConfig(JSON.stringify(json.network_config))
+    : NodeConfig.default(json.network);
   if (json.bootnodes.length !== 0) {
     config.bootnodes = json.bootnodes;
   }
@@ -1,24 +1,29 @@
 //! A browser compatible wrappers for the [`lumina-node`].
 
-use js_sys::Array;
-use libp2p::identity::Keypair;
+use std::time::Duration;
+
+use js_sys::{Array, AsyncIterator};
 use serde::{Deserialize, Serialize};
-use serde_wasm_bindgen::to_value;
+use serde_wasm_bindgen::{from_value, to_value};
 use tracing::{debug, error};
 use wasm_bindgen::prelude::*;
 use web_sys::BroadcastChannel;
 
 use lumina_node::blockstore::IndexedDbBlockstore;
-use lumina_node::network::{canonical_network_bootnodes, network_id};
-use lumina_node::node::NodeConfig;
+use lumina_node::network::{canonical_network_bootnodes, NetworkConfig};
+use lumina_node::node::{
+    ConnectionLimits, NodeConfig, TrustedCheckpoint, DEFAULT_MAX_SAMPLES_NEEDED,
+    DEFAULT_PEER_BAN_DURATION,
+};
 use lumina_node::store::IndexedDbStore;
 
 use crate::commands::{CheckableResponseExt, NodeCommand, SingleHeaderQuery};
 use crate::error::{Context, Result};
+use crate::keypair::load_or_generate_keypair;
 use crate::ports::WorkerClient;
 use crate::utils::{
-    is_safari, js_value_from_display, request_storage_persistence, resolve_dnsaddr_multiaddress,
-    timeout, Network,
+    broadcast_channel_async_iterator, is_safari, js_value_from_display,
+    request_storage_persistence, resolve_dnsaddr_multiaddress, timeout, Network,
 };
 use crate::wrapper::libp2p::NetworkInfoSnapshot;
 use crate::wrapper::node::{PeerTrackerInfoSnapshot, SyncingInfoSnapshot};
@@ -29,9 +34,42 @@ use crate::wrapper::node::{PeerTrackerInfoSnapshot, SyncingInfoSnapshot};
 pub struct WasmNodeConfig {
     /// A network to connect to.
     pub network: Network,
+    /// Optional JSON definition of a custom network. If set, `network` is ignored.
+    ///
+    /// Trusted hash and windows of the custom network are used unless provided explicitly.
+    #[wasm_bindgen(js_name = networkConfig, getter_with_clone)]
+    pub network_config: Option<String>,
     /// A list of bootstrap peers to connect to.
     #[wasm_bindgen(getter_with_clone)]
     pub bootnodes: Vec<String>,
+    /// Optional hash of a trusted header used as the root of trust when syncing.
+    #[wasm_bindgen(js_name = trustedHash, getter_with_clone)]
+    pub trusted_hash: Option<String>,
+    /// Optional height of the trusted header. Used only if `trustedHash` is set.
+    #[wasm_bindgen(js_name = trustedHeight)]
+    pub trusted_height: Option<u64>,
+    /// Optional syncing window in seconds. Headers older than that are not synced.
+    #[wasm_bindgen(js_name = syncingWindowSecs)]
+    pub syncing_window_secs: Option<u64>,
+    /// Optional sampling window in seconds. Blocks older than that are not sampled.
+    ///
+    /// Defaults to the smaller of 30 days and the syncing window.
+    #[wasm_bindgen(js_name = samplingWindowSecs)]
+    pub sampling_window_secs: Option<u64>,
+    /// Optional pruning window in seconds. Headers and samples older than that are removed.
+    ///
+    /// Defaults to the syncing window extended by 1 hour.
+    #[wasm_bindgen(js_name = pruningWindowSecs)]
+    pub pruning_window_secs: Option<u64>,
+    /// Never remove any headers and samples.
+    #[wasm_bindgen(js_name = disablePruning)]
+    pub disable_pruning: bool,
+    /// Optional maximum number of samples taken from each block.
+    #[wasm_bindgen(js_name = maxSamplesNeeded)]
+    pub max_samples_needed: Option<usize>,
+    /// Optional duration in seconds for which misbehaving peers are banned.
+    #[wasm_bindgen(js_name = peerBanDurationSecs)]
+    pub peer_ban_duration_secs: Option<u64>,
 }
 
 /// `NodeClient` is responsible for steering [`NodeWorker`] by sending it commands and receiving
@@ -159,6 +197,14 @@ impl NodeClient {
         Ok(result)
     }
 
+    /// Get a snapshot of node's metrics in Prometheus text format.
+    pub async fn metrics(&self) -> Result<String> {
+        let command = NodeCommand::GetMetrics;
+        let response = self.worker.exec(command).await?;
+
+        response.into_metrics().check_variant()?
+    }
+
     /// Get all the peers that node is connected to.
     #[wasm_bindgen(js_name = connectedPeers)]
     pub async fn connected_peers(&self) -> Result<Array> {
@@ -242,6 +288,51 @@ impl NodeClient {
         headers.into()
     }
 
+    /// Request all blobs of the given namespace from the block with a given height.
+    ///
+    /// The namespace is expected in the same base64 form as in serialized blobs
+    /// and the header of the block must already be synced.
+    ///
+    /// Returns an array of javascript objects with given structure:
+    /// https://docs.rs/celestia-types/latest/celestia_types/struct.Blob.html
+    #[wasm_bindgen(js_name = requestBlobs)]
+    pub async fn request_blobs(&self, namespace: JsValue, block_height: u64) -> Result<Array> {
+        let command = NodeCommand::RequestBlobs {
+            namespace: from_value(namespace).context("could not deserialise namespace")?,
+            block_height,
+        };
+        let response = self.worker.exec(command).await?;
+        let blobs = response.into_blobs().check_variant()?;
+
+        blobs.into()
+    }
+
+    /// Request a blob with the given namespace and commitment from the block with a given height.
+    ///
+    /// Both namespace and commitment are expected in the same base64 form as in serialized
+    /// blobs and the header of the block must already be synced. Returns `undefined` if
+    /// there is no such blob in the block.
+    ///
+    /// Returns a javascript object with given structure:
+    /// https://docs.rs/celestia-types/latest/celestia_types/struct.Blob.html
+    #[wasm_bindgen(js_name = requestBlob)]
+    pub async fn request_blob(
+        &self,
+        namespace: JsValue,
+        block_height: u64,
+        commitment: JsValue,
+    ) -> Result<JsValue> {
+        let command = NodeCommand::RequestBlob {
+            namespace: from_value(namespace).context("could not deserialise namespace")?,
+            block_height,
+            commitment: from_value(commitment).context("could not deserialise commitment")?,
+        };
+        let response = self.worker.exec(command).await?;
+        let blob = response.into_blob().check_variant()?;
+
+        blob.into()
+    }
+
     /// Get current header syncing info.
     #[wasm_bindgen(js_name = syncerInfo)]
     pub async fn syncer_info(&self) -> Result<SyncingInfoSnapshot> {
@@ -304,6 +395,19 @@ impl NodeClient {
         header.into()
     }
 
+    /// Wait until the header of the given height is synced and return it.
+    ///
+    /// Returns a javascript object with given structure:
+    /// https://docs.rs/celestia-types/latest/celestia_types/struct.ExtendedHeader.html
+    #[wasm_bindgen(js_name = waitForHeight)]
+    pub async fn wait_for_height(&self, height: u64) -> Result<JsValue> {
+        let command = NodeCommand::WaitForHeight { height };
+        let response = self.worker.exec(command).await?;
+        let header = response.into_header().check_variant()?;
+
+        header.into()
+    }
+
     /// Get synced headers from the given heights range.
     ///
     /// If start of the range is undefined (None), the first returned header will be of height 1.
@@ -364,6 +468,24 @@ impl NodeClient {
 
         Ok(BroadcastChannel::new(&name).unwrap())
     }
+
+    /// Subscribe to new headers synced by the node.
+    ///
+    /// Returns an async iterator yielding javascript objects with given structure:
+    /// https://docs.rs/celestia-types/latest/celestia_types/struct.ExtendedHeader.html
+    ///
+    /// Only headers synced after subscribing are yielded, e.g.
+    /// `for await (const header of await node.subscribeHeaders()) { ... }`.
+    #[wasm_bindgen(js_name = subscribeHeaders)]
+    pub async fn subscribe_headers(&self) -> Result<AsyncIterator> {
+        let command = NodeCommand::GetHeadersChannelName;
+        let response = self.worker.exec(command).await?;
+        let name = response.into_headers_channel_name().check_variant()?;
+        let channel =
+            BroadcastChannel::new(&name).context("Failed to allocate BroadcastChannel")?;
+
+        broadcast_channel_async_iterator(channel)
+    }
 }
 
 #[wasm_bindgen(js_class = NodeConfig)]
@@ -372,16 +494,45 @@ impl WasmNodeConfig {
     pub fn default(network: Network) -> WasmNodeConfig {
         WasmNodeConfig {
             network,
+            network_config: None,
             bootnodes: canonical_network_bootnodes(network.into())
                 .map(|addr| addr.to_string())
                 .collect::<Vec<_>>(),
+            trusted_hash: None,
+            trusted_height: None,
+            syncing_window_secs: None,
+            sampling_window_secs: None,
+            pruning_window_secs: None,
+            disable_pruning: false,
+            max_samples_needed: None,
+            peer_ban_duration_secs: None,
         }
     }
 
+    /// Get the configuration with bootnodes of a custom network, defined in JSON.
+    #[wasm_bindgen(js_name = withNetworkConfig)]
+    pub fn with_network_config(json: &str) -> Result<WasmNodeConfig> {
+        let network = NetworkConfig::from_json(json)?;
+
+        Ok(WasmNodeConfig {
+            network_config: Some(json.to_owned()),
+            bootnodes: network
+                .bootnodes
+                .iter()
+                .map(|addr| addr.to_string())
+                .collect(),
+            ..WasmNodeConfig::default(Network::Private)
+        })
+    }
+
     pub(crate) async fn into_node_config(
         self,
     ) -> Result<NodeConfig<IndexedDbBlockstore, IndexedDbStore>> {
-        let network_id = network_id(self.network.into());
+        let network = match self.network_config {
+            Some(ref json) => NetworkConfig::from_json(json)?,
+            None => NetworkConfig::canonical(self.network.into()),
+        };
+        let network_id = network.id.as_str();
         let store = IndexedDbStore::new(network_id)
             .await
             .context("Failed to open the store")?;
@@ -389,7 +540,9 @@ impl WasmNodeConfig {
             .await
             .context("Failed to open the blockstore")?;
 
-        let p2p_local_keypair = Keypair::generate_ed25519();
+        let p2p_local_keypair = load_or_generate_keypair(&format!("{network_id}-keypair"))
+            .await
+            .context("Failed to load the keypair")?;
 
         let mut p2p_bootnodes = Vec::with_capacity(self.bootnodes.len());
         for addr in self.bootnodes {
@@ -400,12 +553,41 @@ impl WasmNodeConfig {
             p2p_bootnodes.extend(resolved_addrs.into_iter());
         }
 
+        let sync_trusted_checkpoint = match self.trusted_hash {
+            Some(hash) => {
+                let hash = hash
+                    .parse()
+                    .with_context(|| format!("invalid trusted hash: '{hash}'"))?;
+
+                Some(match self.trusted_height {
+                    Some(height) => TrustedCheckpoint::with_height(height, hash),
+                    None => TrustedCheckpoint::new(hash),
+                })
+            }
+            None => None,
+        };
+
         Ok(NodeConfig {
-            network_id: network_id.to_string(),
+            network,
             p2p_bootnodes,
             p2p_local_keypair,
             p2p_listen_on: vec![],
+            p2p_peer_ban_duration: self
+                .peer_ban_duration_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_PEER_BAN_DURATION),
+            p2p_connection_limits: ConnectionLimits::default(),
             sync_batch_size: 128,
+            sync_trusted_checkpoint,
+            sync_window: self.syncing_window_secs.map(Duration::from_secs),
+            sync_archival: false,
+            sampling_window: self.sampling_window_secs.map(Duration::from_secs),
+            sampling_max_samples_needed: self
+                .max_samples_needed
+                .unwrap_or(DEFAULT_MAX_SAMPLES_NEEDED),
+            sampling_full_storage: false,
+            pruning_window: self.pruning_window_secs.map(Duration::from_secs),
+            pruning_disabled: self.disable_pruning,
             blockstore,
             store,
         })
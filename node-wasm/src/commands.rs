@@ -9,6 +9,8 @@ use tracing::error;
 use wasm_bindgen::{JsError, JsValue};
 
 use celestia_types::hash::Hash;
+use celestia_types::nmt::Namespace;
+use celestia_types::Commitment;
 use lumina_node::node::{PeerTrackerInfo, SyncingInfo};
 use lumina_node::store::SamplingMetadata;
 
@@ -24,6 +26,7 @@ pub(crate) enum NodeCommand {
     IsRunning,
     StartNode(WasmNodeConfig),
     GetEventsChannelName,
+    GetHeadersChannelName,
     GetLocalPeerId,
     GetSyncerInfo,
     GetPeerTrackerInfo,
@@ -37,6 +40,7 @@ pub(crate) enum NodeCommand {
         trusted: bool,
     },
     GetListeners,
+    GetMetrics,
     RequestHeader(SingleHeaderQuery),
     GetVerifiedHeaders {
         #[serde(with = "serde_wasm_bindgen::preserve")]
@@ -48,10 +52,22 @@ pub(crate) enum NodeCommand {
         end_height: Option<u64>,
     },
     GetHeader(SingleHeaderQuery),
+    WaitForHeight {
+        height: u64,
+    },
     LastSeenNetworkHead,
     GetSamplingMetadata {
         height: u64,
     },
+    RequestBlobs {
+        namespace: Namespace,
+        block_height: u64,
+    },
+    RequestBlob {
+        namespace: Namespace,
+        block_height: u64,
+        commitment: Commitment,
+    },
     CloseWorker,
 }
 
@@ -69,6 +85,7 @@ pub(crate) enum WorkerResponse {
     IsRunning(bool),
     NodeStarted(Result<()>),
     EventsChannelName(String),
+    HeadersChannelName(String),
     LocalPeerId(String),
     SyncerInfo(Result<SyncingInfo>),
     PeerTrackerInfo(PeerTrackerInfo),
@@ -77,10 +94,13 @@ pub(crate) enum WorkerResponse {
     SetPeerTrust(Result<()>),
     Connected(Result<()>),
     Listeners(Result<Vec<Multiaddr>>),
+    Metrics(Result<String>),
     Header(JsResult<JsValue, Error>),
     Headers(JsResult<Array, Error>),
     LastSeenNetworkHead(JsResult<JsValue, Error>),
     SamplingMetadata(Result<Option<SamplingMetadata>>),
+    Blobs(JsResult<Array, Error>),
+    Blob(JsResult<JsValue, Error>),
     WorkerClosed(()),
 }
 
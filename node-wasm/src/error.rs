@@ -134,10 +134,13 @@ from_js_value! {
 from_display! {
     blockstore::Error,
     celestia_tendermint::error::Error,
+    libp2p::identity::DecodingError,
     libp2p::identity::ParseError,
     libp2p::multiaddr::Error,
+    lumina_node::network::NetworkConfigError,
     lumina_node::node::NodeError,
     lumina_node::store::StoreError,
+    rexie::Error,
     crate::worker::WorkerError,
 }
 
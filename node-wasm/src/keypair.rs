@@ -0,0 +1,52 @@
+//! Persistence of the node's identity in the IndexedDB.
+
+use js_sys::Uint8Array;
+use libp2p::identity::Keypair;
+use rexie::{ObjectStore, Rexie, TransactionMode};
+use tracing::info;
+use wasm_bindgen::JsValue;
+
+use crate::error::{Context, Result};
+
+const DB_VERSION: u32 = 1;
+const KEYPAIR_STORE_NAME: &str = "keypair";
+const LOCAL_KEYPAIR_KEY: &str = "local_keypair";
+
+/// Load the keypair from the IndexedDB database with the given name, or generate
+/// a new one and save it there if it doesn't exist yet.
+pub(crate) async fn load_or_generate_keypair(db_name: &str) -> Result<Keypair> {
+    let db = Rexie::builder(db_name)
+        .version(DB_VERSION)
+        .add_object_store(ObjectStore::new(KEYPAIR_STORE_NAME))
+        .build()
+        .await
+        .context("Failed to open the keypair database")?;
+
+    let tx = db.transaction(&[KEYPAIR_STORE_NAME], TransactionMode::ReadWrite)?;
+    let store = tx.store(KEYPAIR_STORE_NAME)?;
+    let key = JsValue::from_str(LOCAL_KEYPAIR_KEY);
+
+    let keypair = match store.get(key.clone()).await? {
+        Some(value) => {
+            let bytes = Uint8Array::new(&value).to_vec();
+            Keypair::from_protobuf_encoding(&bytes)
+                .context("Invalid keypair stored in the database")?
+        }
+        None => {
+            let keypair = Keypair::generate_ed25519();
+            let bytes = keypair.to_protobuf_encoding()?;
+
+            store
+                .put(&Uint8Array::from(&bytes[..]).into(), Some(&key))
+                .await?;
+
+            info!("Generated new keypair");
+            keypair
+        }
+    };
+
+    tx.done().await?;
+    db.close();
+
+    Ok(keypair)
+}
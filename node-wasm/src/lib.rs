@@ -4,6 +4,7 @@
 pub mod client;
 mod commands;
 pub mod error;
+mod keypair;
 mod ports;
 pub mod utils;
 mod worker;
@@ -1,27 +1,30 @@
 //! Various utilities for interacting with node from wasm.
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::fmt::{self, Debug};
 use std::future::Future;
 use std::net::{IpAddr, Ipv4Addr};
+use std::rc::Rc;
 
 use gloo_timers::future::TimeoutFuture;
-use js_sys::Math;
+use js_sys::{AsyncIterator, Math, Object, Promise, Reflect, Symbol};
 use libp2p::multiaddr::Protocol;
 use libp2p::{Multiaddr, PeerId};
 use lumina_node::network;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use tokio::sync::{mpsc, Mutex};
 use tracing::{info, warn};
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::fmt::time::UtcTime;
 use tracing_subscriber::prelude::*;
 use tracing_web::MakeConsoleWriter;
 use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
 use web_sys::{
-    DedicatedWorkerGlobalScope, MessageEvent, Request, RequestInit, RequestMode, Response,
-    SharedWorker, SharedWorkerGlobalScope, Worker,
+    BroadcastChannel, DedicatedWorkerGlobalScope, MessageEvent, Request, RequestInit, RequestMode,
+    Response, SharedWorker, SharedWorkerGlobalScope, Worker,
 };
 
 use crate::error::{Context, Error, Result};
@@ -252,6 +255,65 @@ pub(crate) fn shared_workers_supported() -> Result<bool, Error> {
     Ok(is_firefox()? || is_safari()?)
 }
 
+/// Create a javascript async iterator over the messages received on the [`BroadcastChannel`].
+///
+/// Iterator finishes when it is returned from, e.g. on `break` in `for await` loop,
+/// which also closes the channel.
+pub(crate) fn broadcast_channel_async_iterator(channel: BroadcastChannel) -> Result<AsyncIterator> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let tx = Rc::new(RefCell::new(Some(tx)));
+
+    let on_message = Closure::<dyn Fn(MessageEvent)>::new({
+        let tx = tx.clone();
+        move |ev: MessageEvent| {
+            if let Some(tx) = tx.borrow().as_ref() {
+                let _ = tx.send(ev.data());
+            }
+        }
+    });
+    channel.set_onmessage(Some(on_message.into_js_value().unchecked_ref()));
+
+    let rx = Rc::new(Mutex::new(rx));
+
+    let next = Closure::<dyn Fn() -> Promise>::new(move || {
+        let rx = rx.clone();
+
+        future_to_promise(async move {
+            let value = rx.lock().await.recv().await;
+            let done = value.is_none();
+            iterator_result(value.unwrap_or(JsValue::UNDEFINED), done)
+        })
+    });
+
+    let return_ = Closure::<dyn Fn() -> Promise>::new(move || {
+        // dropping the sender finishes the pending and future `next` calls
+        tx.borrow_mut().take();
+        channel.close();
+        Promise::resolve(&iterator_result(JsValue::UNDEFINED, true).unwrap_or(JsValue::UNDEFINED))
+    });
+
+    let iterator = Object::new();
+    Reflect::set(&iterator, &"next".into(), &next.into_js_value())?;
+    Reflect::set(&iterator, &"return".into(), &return_.into_js_value())?;
+
+    let this = iterator.clone();
+    let async_iterator = Closure::<dyn Fn() -> JsValue>::new(move || this.clone().into());
+    Reflect::set(
+        &iterator,
+        &Symbol::async_iterator(),
+        &async_iterator.into_js_value(),
+    )?;
+
+    Ok(iterator.unchecked_into())
+}
+
+fn iterator_result(value: JsValue, done: bool) -> Result<JsValue, JsValue> {
+    let result = Object::new();
+    Reflect::set(&result, &"value".into(), &value)?;
+    Reflect::set(&result, &"done".into(), &done.into())?;
+    Ok(result.into())
+}
+
 pub(crate) fn random_id() -> u32 {
     (Math::random() * f64::from(u32::MAX)).floor() as u32
 }
@@ -1,5 +1,7 @@
 use std::fmt::Debug;
+use std::pin::pin;
 
+use futures::{Stream, StreamExt};
 use js_sys::Array;
 use libp2p::{Multiaddr, PeerId};
 use serde::{Deserialize, Serialize};
@@ -11,9 +13,11 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
 use web_sys::{BroadcastChannel, SharedWorker};
 
+use celestia_types::nmt::Namespace;
+use celestia_types::{Commitment, ExtendedHeader};
 use lumina_node::blockstore::IndexedDbBlockstore;
 use lumina_node::events::{EventSubscriber, NodeEventInfo};
-use lumina_node::node::{Node, SyncingInfo};
+use lumina_node::node::{Node, SubscriptionError, SyncingInfo};
 use lumina_node::store::{IndexedDbStore, SamplingMetadata, Store};
 
 use crate::client::WasmNodeConfig;
@@ -51,6 +55,10 @@ struct NodeWorker {
 struct NodeWorkerInstance {
     node: Node<IndexedDbBlockstore, IndexedDbStore>,
     events_channel_name: String,
+    headers_channel_name: String,
+    /// Taken once the first subscriber asks for it, so that headers aren't
+    /// serialised while nobody listens.
+    headers_channel: Option<BroadcastChannel>,
 }
 
 #[wasm_bindgen]
@@ -124,9 +132,15 @@ impl NodeWorkerInstance {
 
         spawn_local(event_forwarder_task(events_sub, events_channel));
 
+        let headers_channel_name = format!("NodeHeadersChannel-{}", random_id());
+        let headers_channel = BroadcastChannel::new(&headers_channel_name)
+            .context("Failed to allocate BroadcastChannel")?;
+
         Ok(Self {
             node,
             events_channel_name: events_channel_name.to_owned(),
+            headers_channel_name,
+            headers_channel: Some(headers_channel),
         })
     }
 
@@ -156,6 +170,10 @@ impl NodeWorkerInstance {
         Ok(self.node.listeners().await?)
     }
 
+    async fn get_metrics(&mut self) -> Result<String> {
+        Ok(self.node.metrics().await?)
+    }
+
     async fn wait_connected(&mut self, trusted: bool) -> Result<()> {
         if trusted {
             self.node.wait_connected_trusted().await?;
@@ -183,6 +201,11 @@ impl NodeWorkerInstance {
         to_value(&header).context("could not serialise requested header")
     }
 
+    async fn wait_for_height(&mut self, height: u64) -> Result<JsValue> {
+        let header = self.node.wait_for_height(height).await?;
+        to_value(&header).context("could not serialise header")
+    }
+
     async fn get_verified_headers(&mut self, from: JsValue, amount: u64) -> Result<Array> {
         let verified_headers = self
             .node
@@ -228,6 +251,31 @@ impl NodeWorkerInstance {
         Ok(self.node.get_sampling_metadata(height).await?)
     }
 
+    async fn request_blobs(&mut self, namespace: Namespace, block_height: u64) -> Result<Array> {
+        let blobs = self.node.request_blobs(namespace, block_height).await?;
+        blobs
+            .iter()
+            .map(to_value)
+            .collect::<Result<Array, _>>()
+            .context("could not serialise fetched blobs")
+    }
+
+    async fn request_blob(
+        &mut self,
+        namespace: Namespace,
+        block_height: u64,
+        commitment: Commitment,
+    ) -> Result<JsValue> {
+        match self
+            .node
+            .request_blob(namespace, block_height, commitment)
+            .await?
+        {
+            Some(blob) => to_value(&blob).context("could not serialise fetched blob"),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
     async fn process_command(&mut self, command: NodeCommand) -> WorkerResponse {
         match command {
             NodeCommand::IsRunning => WorkerResponse::IsRunning(true),
@@ -240,6 +288,16 @@ impl NodeWorkerInstance {
             NodeCommand::GetEventsChannelName => {
                 WorkerResponse::EventsChannelName(self.events_channel_name.clone())
             }
+            NodeCommand::GetHeadersChannelName => {
+                if let Some(headers_channel) = self.headers_channel.take() {
+                    spawn_local(header_forwarder_task(
+                        self.node.subscribe_headers(),
+                        headers_channel,
+                    ));
+                }
+
+                WorkerResponse::HeadersChannelName(self.headers_channel_name.clone())
+            }
             NodeCommand::GetSyncerInfo => WorkerResponse::SyncerInfo(self.get_syncer_info().await),
             NodeCommand::GetPeerTrackerInfo => {
                 let peer_tracker_info = self.node.peer_tracker_info();
@@ -259,12 +317,16 @@ impl NodeWorkerInstance {
                 WorkerResponse::Connected(self.wait_connected(trusted).await)
             }
             NodeCommand::GetListeners => WorkerResponse::Listeners(self.get_listeners().await),
+            NodeCommand::GetMetrics => WorkerResponse::Metrics(self.get_metrics().await),
             NodeCommand::RequestHeader(query) => {
                 WorkerResponse::Header(self.request_header(query).await.into())
             }
             NodeCommand::GetHeader(query) => {
                 WorkerResponse::Header(self.get_header(query).await.into())
             }
+            NodeCommand::WaitForHeight { height } => {
+                WorkerResponse::Header(self.wait_for_height(height).await.into())
+            }
             NodeCommand::GetVerifiedHeaders { from, amount } => {
                 WorkerResponse::Headers(self.get_verified_headers(from, amount).await.into())
             }
@@ -282,6 +344,19 @@ impl NodeWorkerInstance {
             NodeCommand::GetSamplingMetadata { height } => {
                 WorkerResponse::SamplingMetadata(self.get_sampling_metadata(height).await)
             }
+            NodeCommand::RequestBlobs {
+                namespace,
+                block_height,
+            } => WorkerResponse::Blobs(self.request_blobs(namespace, block_height).await.into()),
+            NodeCommand::RequestBlob {
+                namespace,
+                block_height,
+                commitment,
+            } => WorkerResponse::Blob(
+                self.request_blob(namespace, block_height, commitment)
+                    .await
+                    .into(),
+            ),
             NodeCommand::CloseWorker => {
                 SharedWorker::worker_self().close();
                 WorkerResponse::WorkerClosed(())
@@ -316,3 +391,28 @@ async fn event_forwarder_task(mut events_sub: EventSubscriber, events_channel: B
 
     events_channel.close();
 }
+
+async fn header_forwarder_task(
+    headers: impl Stream<Item = Result<ExtendedHeader, SubscriptionError>>,
+    headers_channel: BroadcastChannel,
+) {
+    let mut headers = pin!(headers);
+
+    while let Some(header) = headers.next().await {
+        let header = match header {
+            Ok(header) => header,
+            Err(e) => {
+                warn!("Forwarding headers: {e}");
+                continue;
+            }
+        };
+
+        if let Ok(val) = to_value(&header) {
+            if headers_channel.post_message(&val).is_err() {
+                break;
+            }
+        }
+    }
+
+    headers_channel.close();
+}
@@ -21,6 +21,7 @@ pub struct SyncingInfoSnapshot {
     #[wasm_bindgen(getter_with_clone)]
     pub stored_headers: Vec<BlockRange>,
     pub subjective_head: u64,
+    pub backfill_remaining: Option<u64>,
 }
 
 impl From<PeerTrackerInfo> for PeerTrackerInfoSnapshot {
@@ -46,6 +47,7 @@ impl From<SyncingInfo> for SyncingInfoSnapshot {
         Self {
             stored_headers,
             subjective_head: value.subjective_head,
+            backfill_remaining: value.backfill_remaining,
         }
     }
 }
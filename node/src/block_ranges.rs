@@ -234,6 +234,11 @@ impl BlockRanges {
         self.0.iter().all(|r| r.is_empty())
     }
 
+    /// Return the number of heights in the ranges.
+    pub fn len(&self) -> u64 {
+        self.0.iter().map(|r| r.len()).sum()
+    }
+
     /// Return highest height in the range.
     pub fn head(&self) -> Option<u64> {
         self.0.last().map(|r| *r.end())
@@ -17,22 +17,33 @@
 //!    Daser executes the following procedure for every scheduled block:
 //!     - It makes sure that the block is still within the sampling window.
 //!     - It selects which random shares are going to be sampled and generates their Shwap CIDs.
+//!       In full storage mode CIDs of all the rows of the block are added too.
 //!     - It updates [`Store`] with the CIDs that are going to be sampled. Tracking of the the CIDs
 //!       is needed for pruning them later on. This is done before retrival of CIDs is started because
 //!       otherwise user could stop the node after Bitswap stores the block in the blockstore, but before
 //!       we can record that in the [`Store`], causing a leak.
+//!     - In full storage mode, retrieves all the rows first. Bitswap keeps them in the blockstore,
+//!       from where they are served to other nodes and where the samples are derived from.
 //!     - Initiates Bitswap retrival requests for the specified CIDs.
 //!     - If all CIDs are received, then the block is considered sampled and accepted.
 //!     - If we reach a timeout of 10 seconds and at least one of the CIDs is not received, then
 //!       block is considered sampled and rejected.
+//!     - In full storage mode, a rejected block is retrieved whole with shrex/eds instead, for
+//!       networks that don't serve shwap yet. If that succeeds, its rows are stored and the block
+//!       is accepted. Light nodes have no such fallback, as shrex doesn't serve single samples.
 //!     - [`Store`] is updated with the sampling result.
 //! 5. Steps 3 and 4 are repeated concurently, unless we detect that all peers have disconnected.
 //!    At that point Daser cleans the queue and moves back to step 1.
 
 use std::collections::HashSet;
+use std::convert::Infallible;
 use std::sync::Arc;
 
+use blockstore::Blockstore;
 use celestia_tendermint::Time;
+use celestia_tendermint_proto::Protobuf;
+use celestia_types::row::Row;
+use celestia_types::ExtendedDataSquare;
 use futures::future::BoxFuture;
 use futures::stream::FuturesUnordered;
 use futures::{FutureExt, StreamExt};
@@ -44,15 +55,18 @@ use web_time::{Duration, Instant};
 
 use crate::events::{EventPublisher, NodeEvent};
 use crate::executor::{spawn, JoinHandle};
-use crate::p2p::shwap::sample_cid;
+use crate::metrics::{Metrics, OpResult};
+use crate::p2p::shwap::{row_cid, sample_cid};
 use crate::p2p::{P2p, P2pError};
 use crate::store::{BlockRanges, SamplingStatus, Store, StoreError};
 
-const MAX_SAMPLES_NEEDED: usize = 16;
-
 const HOUR: u64 = 60 * 60;
 const DAY: u64 = 24 * HOUR;
-const SAMPLING_WINDOW: Duration = Duration::from_secs(30 * DAY);
+
+/// Default maximum number of samples taken from each block.
+pub const DEFAULT_MAX_SAMPLES_NEEDED: usize = 16;
+/// Default sampling window, blocks older than that are not sampled.
+pub const DEFAULT_SAMPLING_WINDOW: Duration = Duration::from_secs(30 * DAY);
 
 type Result<T, E = DaserError> = std::result::Result<T, E>;
 
@@ -66,6 +80,10 @@ pub enum DaserError {
     /// An error propagated from the [`Store`] component.
     #[error("Store: {0}")]
     Store(#[from] StoreError),
+
+    /// An error propagated from the [`Blockstore`] component.
+    #[error("Blockstore: {0}")]
+    Blockstore(#[from] blockstore::Error),
 }
 
 /// Component responsible for data availability sampling of blocks from the network.
@@ -75,22 +93,34 @@ pub(crate) struct Daser {
 }
 
 /// Arguments used to configure the [`Daser`].
-pub(crate) struct DaserArgs<S>
+pub(crate) struct DaserArgs<B, S>
 where
+    B: Blockstore,
     S: Store,
 {
     /// Handler for the peer to peer messaging.
     pub(crate) p2p: Arc<P2p>,
     /// Headers storage.
     pub(crate) store: Arc<S>,
+    /// Block storage.
+    pub(crate) blockstore: Arc<B>,
     /// Event publisher.
     pub(crate) event_pub: EventPublisher,
+    /// Blocks older than this window are not sampled.
+    pub(crate) sampling_window: Duration,
+    /// Maximum number of samples taken from each block.
+    pub(crate) max_samples_needed: usize,
+    /// Retrieve and keep all the rows of the sampled blocks.
+    pub(crate) full_storage: bool,
+    /// Metrics of the node.
+    pub(crate) metrics: Arc<Metrics>,
 }
 
 impl Daser {
     /// Create and start the [`Daser`].
-    pub(crate) fn start<S>(args: DaserArgs<S>) -> Result<Self>
+    pub(crate) fn start<B, S>(args: DaserArgs<B, S>) -> Result<Self>
     where
+        B: Blockstore + 'static,
         S: Store + 'static,
     {
         let cancellation_token = CancellationToken::new();
@@ -131,33 +161,50 @@ impl Drop for Daser {
     }
 }
 
-struct Worker<S>
+struct SamplingResult {
+    height: u64,
+    accepted: bool,
+    /// Square retrieved with shrex/eds when shwap data wasn't delivered.
+    fallback_eds: Option<ExtendedDataSquare>,
+}
+
+struct Worker<B, S>
 where
+    B: Blockstore + 'static,
     S: Store + 'static,
 {
     cancellation_token: CancellationToken,
     event_pub: EventPublisher,
     p2p: Arc<P2p>,
     store: Arc<S>,
+    blockstore: Arc<B>,
+    sampling_window: Duration,
     max_samples_needed: usize,
-    sampling_futs: FuturesUnordered<BoxFuture<'static, Result<(u64, bool)>>>,
+    full_storage: bool,
+    metrics: Arc<Metrics>,
+    sampling_futs: FuturesUnordered<BoxFuture<'static, Result<SamplingResult>>>,
     queue: BlockRanges,
     done: BlockRanges,
     ongoing: BlockRanges,
     prev_head: Option<u64>,
 }
 
-impl<S> Worker<S>
+impl<B, S> Worker<B, S>
 where
+    B: Blockstore,
     S: Store,
 {
-    fn new(args: DaserArgs<S>, cancellation_token: CancellationToken) -> Result<Worker<S>> {
+    fn new(args: DaserArgs<B, S>, cancellation_token: CancellationToken) -> Result<Worker<B, S>> {
         Ok(Worker {
             cancellation_token,
             event_pub: args.event_pub,
             p2p: args.p2p,
             store: args.store,
-            max_samples_needed: MAX_SAMPLES_NEEDED,
+            blockstore: args.blockstore,
+            sampling_window: args.sampling_window,
+            max_samples_needed: args.max_samples_needed,
+            full_storage: args.full_storage,
+            metrics: args.metrics,
             sampling_futs: FuturesUnordered::new(),
             queue: BlockRanges::default(),
             done: BlockRanges::default(),
@@ -256,7 +303,15 @@ where
                 Some(res) = self.sampling_futs.next() => {
                     // Beetswap only returns fatal errors that are not related
                     // to P2P nor networking.
-                    let (height, accepted) = res?;
+                    let SamplingResult {
+                        height,
+                        accepted,
+                        fallback_eds,
+                    } = res?;
+
+                    if let Some(eds) = fallback_eds {
+                        store_rows(&*self.blockstore, &eds, height).await?;
+                    }
 
                     let status = if accepted {
                         SamplingStatus::Accepted
@@ -309,7 +364,7 @@ where
         let square_width = header.dah.square_width();
 
         // Make sure that the block is still in the sampling window.
-        if !in_sampling_window(header.time()) {
+        if !in_sampling_window(header.time(), self.sampling_window) {
             // As soon as we reach a block that is not in the sampling
             // window, it means the rest wouldn't be either.
             self.queue
@@ -326,11 +381,21 @@ where
 
         // Update the CID list before we start sampling, otherwise it's possible for us
         // to leak CIDs causing associated blocks to never get cleaned from blockstore.
-        let cids = share_indexes
+        let mut cids = share_indexes
             .iter()
             .map(|(row, col)| sample_cid(*row, *col, height))
             .collect::<Result<Vec<_>, _>>()?;
 
+        // Only rows of the original data square are stored, parity is
+        // reconstructed from them when needed.
+        let ods_width = square_width / 2;
+
+        if self.full_storage {
+            for row in 0..ods_width {
+                cids.push(row_cid(row, height)?);
+            }
+        }
+
         // NOTE: Pruning window is always 1 hour bigger than sampling
         // window, so after `in_sampling_window` if statement we shouldn't
         // care about `StoreError::NotFound` anymore.
@@ -340,6 +405,8 @@ where
 
         let p2p = self.p2p.clone();
         let event_pub = self.event_pub.clone();
+        let metrics = self.metrics.clone();
+        let full_storage = self.full_storage;
 
         // Schedule retrival of the CIDs. This will be run later on in the `select!` loop.
         let fut = async move {
@@ -351,6 +418,26 @@ where
                 shares: share_indexes.iter().copied().collect(),
             });
 
+            let mut block_accepted = true;
+
+            if full_storage {
+                let mut futs = (0..ods_width)
+                    .map(|row| {
+                        let p2p = p2p.clone();
+                        async move { p2p.get_row_with_timeout(row, height).await }
+                    })
+                    .collect::<FuturesUnordered<_>>();
+
+                while let Some(res) = futs.next().await {
+                    match res {
+                        Ok(_) => {}
+                        // Same as with samples, invalid rows are never delivered.
+                        Err(P2pError::BitswapQueryTimeout) => block_accepted = false,
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+            }
+
             // Initialize all futures
             let mut futs = share_indexes
                 .into_iter()
@@ -364,18 +451,25 @@ where
                 })
                 .collect::<FuturesUnordered<_>>();
 
-            let mut block_accepted = true;
-
             // Run futures to completion
             while let Some((row, column, res)) = futs.next().await {
                 let share_accepted = match res {
-                    Ok(_) => true,
+                    Ok(_) => {
+                        metrics.sampled_share(OpResult::Success);
+                        true
+                    }
                     // Validation is done at Bitswap level, through `ShwapMultihasher`.
                     // If the sample is not valid, it will never be delivered to us
                     // as the data of the CID. Because of that, the only signal
                     // that data sampling verification failed is query timing out.
-                    Err(P2pError::BitswapQueryTimeout) => false,
-                    Err(e) => return Err(e.into()),
+                    Err(P2pError::BitswapQueryTimeout) => {
+                        metrics.sampled_share(OpResult::Timeout);
+                        false
+                    }
+                    Err(e) => {
+                        metrics.sampled_share(OpResult::Failure);
+                        return Err(e.into());
+                    }
                 };
 
                 block_accepted &= share_accepted;
@@ -389,13 +483,40 @@ where
                 });
             }
 
+            // Samples may be unavailable just because the network doesn't
+            // support shwap yet. Before rejecting the block, full storage nodes
+            // try to retrieve the whole square with shrex/eds, which is verified
+            // against the DAH. Its rows are stored in place of the ones that
+            // weren't delivered.
+            //
+            // Light nodes don't download whole squares and shrex has no sample
+            // level protocol, so they can't sample blocks without shwap.
+            let mut fallback_eds = None;
+
+            if !block_accepted && full_storage {
+                match p2p.get_eds(&header).await {
+                    Ok(eds) => {
+                        fallback_eds = Some(eds);
+                        block_accepted = true;
+                    }
+                    Err(e) if e.is_fatal() => return Err(e.into()),
+                    Err(e) => debug!("Retrieving EDS of block {height} failed: {e}"),
+                }
+            }
+
+            metrics.sampled_block(block_accepted);
+
             event_pub.send(NodeEvent::SamplingFinished {
                 height,
                 accepted: block_accepted,
                 took: now.elapsed(),
             });
 
-            Ok((height, block_accepted))
+            Ok(SamplingResult {
+                height,
+                accepted: block_accepted,
+                fallback_eds,
+            })
         }
         .boxed();
 
@@ -424,7 +545,7 @@ where
 }
 
 /// Returns true if `time` is within the sampling window.
-fn in_sampling_window(time: Time) -> bool {
+pub(crate) fn in_sampling_window(time: Time, sampling_window: Duration) -> bool {
     let now = Time::now();
 
     // Header is from the future! Thus, within sampling window.
@@ -436,7 +557,25 @@ fn in_sampling_window(time: Time) -> bool {
         return false;
     };
 
-    age <= SAMPLING_WINDOW
+    age <= sampling_window
+}
+
+/// Stores the rows of the original data square under the CIDs tracked for the block.
+async fn store_rows<B>(blockstore: &B, eds: &ExtendedDataSquare, height: u64) -> Result<()>
+where
+    B: Blockstore,
+{
+    for index in 0..eds.square_width() / 2 {
+        let row = Row::new(index, eds, height).expect("invalid row");
+        // until unwrap_infallible is stabilised, make sure Result is Infallible manually
+        let data: Result<_, Infallible> = row.encode_vec();
+
+        blockstore
+            .put_keyed(&row_cid(index, height)?, &data.unwrap())
+            .await?;
+    }
+
+    Ok(())
 }
 
 /// Returns unique and random indexes that will be used for sampling.
@@ -466,16 +605,19 @@ fn random_indexes(square_width: u16, max_samples_needed: usize) -> HashSet<(u16,
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blockstore::InMemoryBlockstore;
     use crate::events::{EventChannel, EventSubscriber};
     use crate::executor::sleep;
     use crate::p2p::P2pCmd;
     use crate::store::InMemoryStore;
     use crate::test_utils::{async_test, MockP2pHandle};
     use celestia_tendermint_proto::Protobuf;
+    use celestia_types::row::{Row, RowId};
     use celestia_types::sample::{Sample, SampleId};
     use celestia_types::test_utils::{generate_eds, ExtendedHeaderGenerator};
     use celestia_types::{AxisType, DataAvailabilityHeader, ExtendedDataSquare};
     use cid::Cid;
+    use libp2p::PeerId;
     use std::collections::HashMap;
     use std::time::Duration;
 
@@ -495,6 +637,11 @@ mod tests {
             event_pub: events.publisher(),
             p2p: Arc::new(mock),
             store: store.clone(),
+            blockstore: Arc::new(InMemoryBlockstore::new()),
+            sampling_window: DEFAULT_SAMPLING_WINDOW,
+            max_samples_needed: DEFAULT_MAX_SAMPLES_NEEDED,
+            full_storage: false,
+            metrics: Arc::default(),
         })
         .unwrap();
 
@@ -521,6 +668,11 @@ mod tests {
             event_pub: events.publisher(),
             p2p: Arc::new(mock),
             store: store.clone(),
+            blockstore: Arc::new(InMemoryBlockstore::new()),
+            sampling_window: DEFAULT_SAMPLING_WINDOW,
+            max_samples_needed: DEFAULT_MAX_SAMPLES_NEEDED,
+            full_storage: false,
+            metrics: Arc::default(),
         })
         .unwrap();
 
@@ -535,6 +687,123 @@ mod tests {
         gen_and_sample_block(&mut handle, &mut gen, &store, &mut event_sub, 8, false).await;
     }
 
+    #[async_test]
+    async fn eds_fallback_accepts_block() {
+        let (mock, mut handle) = P2p::mocked();
+        let store = Arc::new(InMemoryStore::new());
+        let blockstore = Arc::new(InMemoryBlockstore::new());
+        let events = EventChannel::new();
+
+        let _daser = Daser::start(DaserArgs {
+            event_pub: events.publisher(),
+            p2p: Arc::new(mock),
+            store: store.clone(),
+            blockstore: blockstore.clone(),
+            sampling_window: DEFAULT_SAMPLING_WINDOW,
+            max_samples_needed: DEFAULT_MAX_SAMPLES_NEEDED,
+            full_storage: true,
+            metrics: Arc::default(),
+        })
+        .unwrap();
+
+        let mut gen = ExtendedHeaderGenerator::new();
+
+        handle.expect_no_cmd().await;
+        handle.announce_peer_connected();
+        handle.expect_no_cmd().await;
+
+        let eds = generate_eds(4);
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let header = gen.next_with_dah(dah.clone());
+        store.insert(header).await.unwrap();
+
+        // Shwap isn't served, all the rows and samples time out
+        for _ in 0..usize::from(eds.square_width() / 2) + DEFAULT_MAX_SAMPLES_NEEDED {
+            let (_, respond_to) = handle.expect_get_shwap_cid().await;
+            respond_to.send(Err(P2pError::BitswapQueryTimeout)).unwrap();
+        }
+
+        let (request, respond_to) = handle.expect_shrex_eds_request().await;
+        assert_eq!(request.hash, dah.hash().as_bytes());
+
+        let ods_width = eds.square_width() as usize / 2;
+        let ods = (0..ods_width)
+            .flat_map(|row| eds.row(row as u16).unwrap().into_iter().take(ods_width))
+            .collect();
+        respond_to.send(Ok((PeerId::random(), ods))).unwrap();
+
+        handle.expect_no_cmd().await;
+
+        let sampling_metadata = store.get_sampling_metadata(1).await.unwrap().unwrap();
+        assert_eq!(sampling_metadata.status, SamplingStatus::Accepted);
+
+        // Rows of the retrieved square are stored under the tracked CIDs
+        for row in 0..ods_width as u16 {
+            let cid = row_cid(row, 1).unwrap();
+            assert!(sampling_metadata.cids.contains(&cid));
+
+            let data = blockstore.get(&cid).await.unwrap().unwrap();
+            let row = Row::decode(&data[..]).unwrap();
+            row.verify(&dah).unwrap();
+        }
+    }
+
+    #[async_test]
+    async fn full_storage_retrieves_rows() {
+        let (mock, mut handle) = P2p::mocked();
+        let store = Arc::new(InMemoryStore::new());
+        let events = EventChannel::new();
+
+        let _daser = Daser::start(DaserArgs {
+            event_pub: events.publisher(),
+            p2p: Arc::new(mock),
+            store: store.clone(),
+            blockstore: Arc::new(InMemoryBlockstore::new()),
+            sampling_window: DEFAULT_SAMPLING_WINDOW,
+            max_samples_needed: DEFAULT_MAX_SAMPLES_NEEDED,
+            full_storage: true,
+            metrics: Arc::default(),
+        })
+        .unwrap();
+
+        let mut gen = ExtendedHeaderGenerator::new();
+
+        handle.expect_no_cmd().await;
+        handle.announce_peer_connected();
+        handle.expect_no_cmd().await;
+
+        let eds = generate_eds(4);
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let header = gen.next_with_dah(dah);
+        store.insert(header).await.unwrap();
+
+        // All the rows of the original data square are retrieved first
+        let mut row_cids = Vec::new();
+
+        for _ in 0..eds.square_width() / 2 {
+            let (cid, respond_to) = handle.expect_get_shwap_cid().await;
+            let row_id: RowId = cid.try_into().unwrap();
+            assert!(row_id.index() < eds.square_width() / 2);
+            let row = Row::new(row_id.index(), &eds, 1).unwrap();
+
+            respond_to.send(Ok(row.encode_vec().unwrap())).unwrap();
+            row_cids.push(cid);
+        }
+
+        let sample_cids = handle_get_shwap_cid(&mut handle, &store, 1, &eds, false).await;
+        handle.expect_no_cmd().await;
+
+        let sampling_metadata = store.get_sampling_metadata(1).await.unwrap().unwrap();
+        assert_eq!(sampling_metadata.status, SamplingStatus::Accepted);
+
+        // Rows are tracked for pruning together with the samples
+        let mut cids = sampling_metadata.cids;
+        cids.sort();
+        let mut expected_cids = [row_cids, sample_cids].concat();
+        expected_cids.sort();
+        assert_eq!(cids, expected_cids);
+    }
+
     #[async_test]
     async fn backward_dasing() {
         let (mock, mut handle) = P2p::mocked();
@@ -545,6 +814,11 @@ mod tests {
             event_pub: events.publisher(),
             p2p: Arc::new(mock),
             store: store.clone(),
+            blockstore: Arc::new(InMemoryBlockstore::new()),
+            sampling_window: DEFAULT_SAMPLING_WINDOW,
+            max_samples_needed: DEFAULT_MAX_SAMPLES_NEEDED,
+            full_storage: false,
+            metrics: Arc::default(),
         })
         .unwrap();
 
@@ -757,7 +1031,7 @@ mod tests {
             .into_iter()
             .map(|(height, eds, simulate_invalid_sampling)| {
                 let square_width = eds.square_width() as usize;
-                let needed_samples = (square_width * square_width).min(MAX_SAMPLES_NEEDED);
+                let needed_samples = (square_width * square_width).min(DEFAULT_MAX_SAMPLES_NEEDED);
 
                 (
                     height,
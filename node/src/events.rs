@@ -183,6 +183,15 @@ pub enum NodeEvent {
         trusted: bool,
     },
 
+    /// Peer was banned because of its low score.
+    PeerBanned {
+        #[serde(serialize_with = "serialize_as_string")]
+        /// The ID of the peer.
+        id: PeerId,
+        /// For how long the peer is banned.
+        duration: Duration,
+    },
+
     /// Sampling just started.
     SamplingStarted {
         /// The block height that will be sampled.
@@ -270,6 +279,17 @@ pub enum NodeEvent {
         took: Duration,
     },
 
+    /// Archival backfill stored another batch of historical headers.
+    ArchivalBackfillProgress {
+        /// Lowest height of the headers stored contiguously with the head.
+        lowest_height: u64,
+        /// Number of headers still missing down to the genesis.
+        remaining: u64,
+    },
+
+    /// Archival backfill stored all the headers down to the genesis.
+    ArchivalBackfillFinished,
+
     /// Header syncing fatal error.
     FatalSyncerError {
         /// A human readable error.
@@ -309,6 +329,7 @@ impl NodeEvent {
             NodeEvent::ConnectingToBootnodes
             | NodeEvent::PeerConnected { .. }
             | NodeEvent::PeerDisconnected { .. }
+            | NodeEvent::PeerBanned { .. }
             | NodeEvent::SamplingStarted { .. }
             | NodeEvent::ShareSamplingResult { .. }
             | NodeEvent::SamplingFinished { .. }
@@ -317,6 +338,8 @@ impl NodeEvent {
             | NodeEvent::FetchingHeadHeaderFinished { .. }
             | NodeEvent::FetchingHeadersStarted { .. }
             | NodeEvent::FetchingHeadersFinished { .. }
+            | NodeEvent::ArchivalBackfillProgress { .. }
+            | NodeEvent::ArchivalBackfillFinished
             | NodeEvent::PrunedHeaders { .. } => false,
         }
     }
@@ -342,6 +365,9 @@ impl fmt::Display for NodeEvent {
                     write!(f, "Peer disconnected: {id}")
                 }
             }
+            NodeEvent::PeerBanned { id, duration } => {
+                write!(f, "Peer banned for {duration:?}: {id}")
+            }
             NodeEvent::SamplingStarted {
                 height,
                 square_width,
@@ -419,6 +445,18 @@ impl fmt::Display for NodeEvent {
                     write!(f, "Fetching headers of blocks {from_height}-{to_height} failed. Took: {took:?}, Error: {error}")
                 }
             }
+            NodeEvent::ArchivalBackfillProgress {
+                lowest_height,
+                remaining,
+            } => {
+                write!(
+                    f,
+                    "Archival backfill reached block {lowest_height}, {remaining} headers remaining"
+                )
+            }
+            NodeEvent::ArchivalBackfillFinished => {
+                write!(f, "Archival backfill reached the genesis")
+            }
             NodeEvent::FatalSyncerError { error } => {
                 write!(f, "Syncer stopped because of a fatal error: {error}")
             }
@@ -6,6 +6,7 @@ pub mod blockstore;
 mod daser;
 pub mod events;
 mod executor;
+mod metrics;
 pub mod network;
 pub mod node;
 mod p2p;
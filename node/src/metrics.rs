@@ -0,0 +1,261 @@
+//! Metrics of the node, exported in Prometheus text format.
+
+use std::time::Duration;
+
+use prometheus_client::encoding::text::encode;
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+use prometheus_client::registry::Registry;
+
+/// Result of an operation, used as a metric label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OpResult {
+    Success,
+    Failure,
+    Timeout,
+    Invalid,
+}
+
+impl OpResult {
+    fn as_str(self) -> &'static str {
+        match self {
+            OpResult::Success => "success",
+            OpResult::Failure => "failure",
+            OpResult::Timeout => "timeout",
+            OpResult::Invalid => "invalid",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+struct ResultLabels {
+    result: &'static str,
+}
+
+/// Collection of all the metrics of the node.
+#[derive(Debug)]
+pub(crate) struct Metrics {
+    registry: Registry,
+    header_ex_requests: Family<ResultLabels, Counter>,
+    syncer_batch_duration: Histogram,
+    syncer_batches: Family<ResultLabels, Counter>,
+    sampled_blocks: Family<ResultLabels, Counter>,
+    sampled_shares: Family<ResultLabels, Counter>,
+    pruned_headers: Counter,
+    bitswap_queries: Family<ResultLabels, Counter>,
+    stored_headers: Gauge,
+    sampled_headers: Gauge,
+    connected_peers: Gauge,
+    connected_trusted_peers: Gauge,
+}
+
+impl Metrics {
+    /// Create a new set of metrics with all of them registered.
+    pub(crate) fn new() -> Metrics {
+        let mut registry = Registry::with_prefix("lumina");
+
+        let header_ex_requests = Family::default();
+        registry.register(
+            "header_ex_requests",
+            "Header-ex requests sent to peers, by result",
+            header_ex_requests.clone(),
+        );
+
+        let syncer_batch_duration = Histogram::new(exponential_buckets(0.1, 2.0, 10));
+        registry.register(
+            "syncer_batch_duration_seconds",
+            "Time it took to fetch a batch of headers",
+            syncer_batch_duration.clone(),
+        );
+
+        let syncer_batches = Family::default();
+        registry.register(
+            "syncer_batches",
+            "Batches of headers fetched by syncer, by result",
+            syncer_batches.clone(),
+        );
+
+        let sampled_blocks = Family::default();
+        registry.register(
+            "sampled_blocks",
+            "Blocks sampled by daser, by result",
+            sampled_blocks.clone(),
+        );
+
+        let sampled_shares = Family::default();
+        registry.register(
+            "sampled_shares",
+            "Shares sampled by daser, by result",
+            sampled_shares.clone(),
+        );
+
+        let pruned_headers = Counter::default();
+        registry.register(
+            "pruned_headers",
+            "Headers removed by pruner",
+            pruned_headers.clone(),
+        );
+
+        let bitswap_queries = Family::default();
+        registry.register(
+            "bitswap_queries",
+            "Shwap queries done over bitswap, by result",
+            bitswap_queries.clone(),
+        );
+
+        let stored_headers = Gauge::default();
+        registry.register(
+            "stored_headers",
+            "Headers in the store",
+            stored_headers.clone(),
+        );
+
+        let sampled_headers = Gauge::default();
+        registry.register(
+            "sampled_headers",
+            "Headers in the store that were successfully sampled",
+            sampled_headers.clone(),
+        );
+
+        let connected_peers = Gauge::default();
+        registry.register(
+            "connected_peers",
+            "Currently connected peers",
+            connected_peers.clone(),
+        );
+
+        let connected_trusted_peers = Gauge::default();
+        registry.register(
+            "connected_trusted_peers",
+            "Currently connected trusted peers",
+            connected_trusted_peers.clone(),
+        );
+
+        Metrics {
+            registry,
+            header_ex_requests,
+            syncer_batch_duration,
+            syncer_batches,
+            sampled_blocks,
+            sampled_shares,
+            pruned_headers,
+            bitswap_queries,
+            stored_headers,
+            sampled_headers,
+            connected_peers,
+            connected_trusted_peers,
+        }
+    }
+
+    /// Encode all the metrics in Prometheus text format.
+    pub(crate) fn encode(&self) -> String {
+        let mut buf = String::new();
+        encode(&mut buf, &self.registry).expect("writing to String can't fail");
+        buf
+    }
+
+    pub(crate) fn header_ex_request(&self, result: OpResult) {
+        self.header_ex_requests
+            .get_or_create(&ResultLabels {
+                result: result.as_str(),
+            })
+            .inc();
+    }
+
+    pub(crate) fn syncer_batch(&self, result: OpResult, took: Duration) {
+        self.syncer_batches
+            .get_or_create(&ResultLabels {
+                result: result.as_str(),
+            })
+            .inc();
+
+        if result == OpResult::Success {
+            self.syncer_batch_duration.observe(took.as_secs_f64());
+        }
+    }
+
+    pub(crate) fn sampled_block(&self, accepted: bool) {
+        let result = if accepted {
+            OpResult::Success
+        } else {
+            OpResult::Failure
+        };
+
+        self.sampled_blocks
+            .get_or_create(&ResultLabels {
+                result: result.as_str(),
+            })
+            .inc();
+    }
+
+    pub(crate) fn sampled_share(&self, result: OpResult) {
+        self.sampled_shares
+            .get_or_create(&ResultLabels {
+                result: result.as_str(),
+            })
+            .inc();
+    }
+
+    pub(crate) fn pruned_header(&self) {
+        self.pruned_headers.inc();
+    }
+
+    pub(crate) fn bitswap_query(&self, result: OpResult) {
+        self.bitswap_queries
+            .get_or_create(&ResultLabels {
+                result: result.as_str(),
+            })
+            .inc();
+    }
+
+    pub(crate) fn set_store_sizes(&self, stored_headers: u64, sampled_headers: u64) {
+        self.stored_headers.set(saturating_i64(stored_headers));
+        self.sampled_headers.set(saturating_i64(sampled_headers));
+    }
+
+    pub(crate) fn set_connected_peers(&self, peers: u64, trusted_peers: u64) {
+        self.connected_peers.set(saturating_i64(peers));
+        self.connected_trusted_peers
+            .set(saturating_i64(trusted_peers));
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics::new()
+    }
+}
+
+fn saturating_i64(val: u64) -> i64 {
+    i64::try_from(val).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_metrics() {
+        let metrics = Metrics::new();
+
+        metrics.header_ex_request(OpResult::Success);
+        metrics.header_ex_request(OpResult::Success);
+        metrics.sampled_share(OpResult::Timeout);
+        metrics.sampled_share(OpResult::Failure);
+        metrics.pruned_header();
+        metrics.set_connected_peers(3, 1);
+
+        let encoded = metrics.encode();
+
+        assert!(encoded.contains("lumina_header_ex_requests_total{result=\"success\"} 2"));
+        assert!(encoded.contains("lumina_sampled_shares_total{result=\"timeout\"} 1"));
+        assert!(encoded.contains("lumina_sampled_shares_total{result=\"failure\"} 1"));
+        assert!(encoded.contains("lumina_pruned_headers_total 1"));
+        assert!(encoded.contains("lumina_connected_peers 3"));
+        assert!(encoded.contains("lumina_connected_trusted_peers 1"));
+        assert!(encoded.ends_with("# EOF\n"));
+    }
+}
@@ -1,11 +1,15 @@
 //! Primitives and constants related to the networks supported by Celestia nodes.
 
 use std::str::FromStr;
+use std::time::Duration;
 
+use celestia_types::hash::Hash;
 use libp2p::Multiaddr;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::syncer::TrustedCheckpoint;
+
 /// Supported Celestia networks.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Network {
@@ -30,6 +34,7 @@ impl FromStr for Network {
 
     fn from_str(network_id: &str) -> Result<Self, Self::Err> {
         match network_id {
+            "celestia" => Ok(Network::Mainnet),
             "arabica-11" => Ok(Network::Arabica),
             "mocha-4" => Ok(Network::Mocha),
             "private" => Ok(Network::Private),
@@ -81,10 +86,217 @@ pub fn canonical_network_bootnodes(network: Network) -> impl Iterator<Item = Mul
         .map(|s| s.parse().expect("Invalid bootstrap address"))
 }
 
+/// Invalid [`NetworkConfig`] provided.
+#[derive(Debug, Error)]
+pub enum NetworkConfigError {
+    /// Config couldn't be deserialized.
+    #[error("invalid network config: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Config has an empty network id.
+    #[error("network id cannot be empty")]
+    EmptyId,
+
+    /// Config has a trusted height, but no trusted hash.
+    #[error("trusted height requires trusted hash")]
+    TrustedHeightWithoutHash,
+}
+
+/// Definition of a network to connect to.
+///
+/// Canonical Celestia networks are created with [`NetworkConfig::canonical`], while
+/// custom ones, e.g. private devnets, can be loaded from JSON with [`NetworkConfig::from_json`]:
+///
+/// ```json
+/// {
+///   "id": "devnet-1",
+///   "bootnodes": ["/dns4/bridge.devnet/tcp/2121/p2p/12D3KooWSqZaLcn5Guypo2mrHr297YPJnV8KMEMXNjs3qAS8msw8"],
+///   "trusted_hash": "6E8B8C2C1C2E0B2A3F3D2F6E2B1F0A5B3C4D5E6F7A8B9C0D1E2F3A4B5C6D7E8F",
+///   "syncing_window_secs": 86400
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Id of the network, e.g. `mocha-4`. It is a part of the protocol names.
+    pub id: String,
+    /// Bootnodes of the network, including peer ids.
+    #[serde(default)]
+    pub bootnodes: Vec<Multiaddr>,
+    /// Hash of the genesis or any other trusted header, used as the root of trust when syncing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trusted_hash: Option<Hash>,
+    /// Height of the trusted header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trusted_height: Option<u64>,
+    /// Default syncing window of the network.
+    #[serde(
+        default,
+        rename = "syncing_window_secs",
+        with = "duration_secs_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub syncing_window: Option<Duration>,
+    /// Default sampling window of the network.
+    #[serde(
+        default,
+        rename = "sampling_window_secs",
+        with = "duration_secs_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sampling_window: Option<Duration>,
+    /// Default pruning window of the network.
+    #[serde(
+        default,
+        rename = "pruning_window_secs",
+        with = "duration_secs_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pruning_window: Option<Duration>,
+}
+
+impl NetworkConfig {
+    /// Get the config of the canonical Celestia network, with the official bootnodes.
+    pub fn canonical(network: Network) -> NetworkConfig {
+        NetworkConfig {
+            id: network_id(network).to_owned(),
+            bootnodes: canonical_network_bootnodes(network).collect(),
+            trusted_hash: None,
+            trusted_height: None,
+            syncing_window: None,
+            sampling_window: None,
+            pruning_window: None,
+        }
+    }
+
+    /// Deserialize and validate the config from JSON.
+    pub fn from_json(json: &str) -> Result<NetworkConfig, NetworkConfigError> {
+        let config: NetworkConfig = serde_json::from_str(json)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that the config is consistent.
+    pub fn validate(&self) -> Result<(), NetworkConfigError> {
+        if self.id.is_empty() {
+            return Err(NetworkConfigError::EmptyId);
+        }
+
+        if self.trusted_height.is_some() && self.trusted_hash.is_none() {
+            return Err(NetworkConfigError::TrustedHeightWithoutHash);
+        }
+
+        Ok(())
+    }
+
+    /// Get the trusted checkpoint of the network, if it has any.
+    pub fn trusted_checkpoint(&self) -> Option<TrustedCheckpoint> {
+        let hash = self.trusted_hash?;
+
+        Some(match self.trusted_height {
+            Some(height) => TrustedCheckpoint::with_height(height, hash),
+            None => TrustedCheckpoint::new(hash),
+        })
+    }
+}
+
+impl From<Network> for NetworkConfig {
+    fn from(network: Network) -> NetworkConfig {
+        NetworkConfig::canonical(network)
+    }
+}
+
+mod duration_secs_opt {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub(super) fn serialize<S>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        duration
+            .map(|duration| duration.as_secs())
+            .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_secs))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn network_from_str() {
+        assert_eq!("celestia".parse::<Network>().unwrap(), Network::Mainnet);
+        assert_eq!("arabica-11".parse::<Network>().unwrap(), Network::Arabica);
+        assert_eq!("mocha-4".parse::<Network>().unwrap(), Network::Mocha);
+        assert_eq!("private".parse::<Network>().unwrap(), Network::Private);
+        "mainnet".parse::<Network>().unwrap_err();
+    }
+
+    #[test]
+    fn network_config_from_json() {
+        let config = NetworkConfig::from_json(
+            r#"{
+                "id": "devnet-1",
+                "bootnodes": ["/ip4/10.0.0.1/tcp/2121/p2p/12D3KooWSqZaLcn5Guypo2mrHr297YPJnV8KMEMXNjs3qAS8msw8"],
+                "trusted_hash": "6E8B8C2C1C2E0B2A3F3D2F6E2B1F0A5B3C4D5E6F7A8B9C0D1E2F3A4B5C6D7E8F",
+                "trusted_height": 10,
+                "syncing_window_secs": 86400
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.id, "devnet-1");
+        assert_eq!(config.bootnodes.len(), 1);
+        assert_eq!(config.syncing_window, Some(Duration::from_secs(86400)));
+        assert_eq!(config.sampling_window, None);
+
+        let checkpoint = config.trusted_checkpoint().unwrap();
+        assert_eq!(checkpoint.height, Some(10));
+        assert_eq!(Some(checkpoint.hash), config.trusted_hash);
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(NetworkConfig::from_json(&json).unwrap(), config);
+    }
+
+    #[test]
+    fn invalid_network_config() {
+        assert!(matches!(
+            NetworkConfig::from_json(r#"{"id": ""}"#),
+            Err(NetworkConfigError::EmptyId)
+        ));
+        assert!(matches!(
+            NetworkConfig::from_json(r#"{"id": "devnet-1", "trusted_height": 10}"#),
+            Err(NetworkConfigError::TrustedHeightWithoutHash)
+        ));
+        assert!(matches!(
+            NetworkConfig::from_json(r#"{"id": "devnet-1", "unknown": 1}"#),
+            Err(NetworkConfigError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn canonical_network_config() {
+        let config = NetworkConfig::canonical(Network::Mocha);
+
+        assert_eq!(config.id, "mocha-4");
+        assert_eq!(
+            config.bootnodes,
+            canonical_network_bootnodes(Network::Mocha).collect::<Vec<_>>()
+        );
+        assert_eq!(config.trusted_checkpoint(), None);
+    }
+
     #[test]
     fn test_canonical_network_bootnodes() {
         // canonical_network_bootnodes works on const data, test it doesn't panic and the data is there
@@ -5,33 +5,51 @@
 
 use std::ops::RangeBounds;
 use std::sync::Arc;
+use std::time::Duration;
 
 use blockstore::Blockstore;
+use celestia_types::fraud_proof::BadEncodingFraudProof;
 use celestia_types::hash::Hash;
 use celestia_types::namespaced_data::NamespacedData;
 use celestia_types::nmt::Namespace;
 use celestia_types::row::Row;
 use celestia_types::sample::Sample;
-use celestia_types::ExtendedHeader;
+use celestia_types::state::{AccAddress, Balance, ProvenQueryResponse};
+use celestia_types::{Blob, Commitment, ExtendedHeader};
+use futures::future;
+use futures::stream::{self, Stream, StreamExt};
 use libp2p::identity::Keypair;
 use libp2p::swarm::NetworkInfo;
 use libp2p::{Multiaddr, PeerId};
-use tokio::sync::watch;
+use tokio::select;
+use tokio::sync::{broadcast, mpsc, watch};
 use tokio_util::sync::CancellationToken;
 use tracing::warn;
 
-use crate::daser::{Daser, DaserArgs};
-use crate::events::{EventChannel, EventSubscriber, NodeEvent};
-use crate::executor::{spawn_cancellable, JoinHandle};
+use crate::daser::{in_sampling_window, Daser, DaserArgs};
+use crate::events::{EventChannel, EventSubscriber, NodeEvent, RecvError};
+use crate::executor::{sleep, spawn_cancellable, timeout, JoinHandle};
+use crate::metrics::Metrics;
+use crate::network::NetworkConfig;
 use crate::p2p::{P2p, P2pArgs};
-use crate::pruner::{Pruner, PrunerArgs, DEFAULT_PRUNING_INTERVAL};
-use crate::store::{SamplingMetadata, Store, StoreError};
-use crate::syncer::{Syncer, SyncerArgs};
+use crate::pruner::{Pruner, PrunerArgs, DEFAULT_PRUNING_INTERVAL, PRUNING_WINDOW_MARGIN};
+use crate::store::{SamplingMetadata, SamplingStatus, Store, StoreError};
+use crate::syncer::{in_syncing_window, Syncer, SyncerArgs};
 
-pub use crate::daser::DaserError;
-pub use crate::p2p::{HeaderExError, P2pError};
-pub use crate::peer_tracker::PeerTrackerInfo;
-pub use crate::syncer::{SyncerError, SyncingInfo};
+pub use crate::daser::{DaserError, DEFAULT_MAX_SAMPLES_NEEDED, DEFAULT_SAMPLING_WINDOW};
+pub use crate::p2p::{
+    ConnectionLimits, HeaderExError, P2pError, ShrexError, DEFAULT_MAX_CONNECTIONS,
+    DEFAULT_MAX_CONNECTIONS_PER_PEER, DEFAULT_MAX_INBOUND_CONNECTIONS,
+    DEFAULT_MAX_OUTBOUND_CONNECTIONS,
+};
+pub use crate::peer_tracker::{PeerScore, PeerTrackerInfo, DEFAULT_PEER_BAN_DURATION};
+pub use crate::pruner::DEFAULT_PRUNING_WINDOW;
+pub use crate::syncer::{SyncerError, SyncingInfo, TrustedCheckpoint, DEFAULT_SYNCING_WINDOW};
+
+/// Delay before retrying a height after a blob subscription error.
+const BLOB_SUBSCRIPTION_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Interval of re-checking the sampling status, in case the sampling event was missed.
+const SAMPLING_RECHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Alias of [`Result`] with [`NodeError`] error type
 ///
@@ -56,6 +74,40 @@ pub enum NodeError {
     /// An error propagated from the `Daser` component.
     #[error("Daser: {0}")]
     Daser(#[from] DaserError),
+
+    /// Provided [`NodeConfig`] is invalid.
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+
+    /// Proof of the queried state failed the verification.
+    #[error("Invalid state proof: {0}")]
+    InvalidStateProof(celestia_types::Error),
+
+    /// An error returned from the subscription streams.
+    #[error("Subscription: {0}")]
+    Subscription(#[from] SubscriptionError),
+}
+
+/// An error returned from the subscription streams of the [`Node`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum SubscriptionError {
+    /// Subscriber didn't keep up and the given number of items were skipped.
+    #[error("Subscriber lagged behind, {0} items skipped")]
+    Lagged(u64),
+
+    /// Header of the given height is outside of the synced chain and will never be synced,
+    /// e.g. because it was pruned. The subscription ends after this error.
+    #[error("Header of height {0} is not stored and won't be synced")]
+    HeightNotAvailable(u64),
+}
+
+/// Blobs of a namespace found in a single block, yielded by [`Node::subscribe_blobs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobsAtHeight {
+    /// Height of the block.
+    pub height: u64,
+    /// Blobs of the namespace, empty if the block doesn't have any.
+    pub blobs: Vec<Blob>,
 }
 
 /// Node conifguration.
@@ -64,22 +116,154 @@ where
     B: Blockstore,
     S: Store,
 {
-    /// An id of the network to connect to.
-    pub network_id: String,
+    /// The network to connect to.
+    pub network: NetworkConfig,
     /// The keypair to be used as [`Node`]s identity.
     pub p2p_local_keypair: Keypair,
     /// List of bootstrap nodes to connect to and trust.
+    ///
+    /// If empty, bootnodes of the `network` are used.
     pub p2p_bootnodes: Vec<Multiaddr>,
     /// List of the addresses where [`Node`] will listen for incoming connections.
     pub p2p_listen_on: Vec<Multiaddr>,
+    /// Duration for which peers are banned after misbehaving. Trusted peers are never banned.
+    ///
+    /// Default: [`DEFAULT_PEER_BAN_DURATION`].
+    pub p2p_peer_ban_duration: Duration,
+    /// Limits of the connections with peers. Bootnodes and trusted peers are exempt.
+    ///
+    /// Default: [`ConnectionLimits::default`].
+    pub p2p_connection_limits: ConnectionLimits,
     /// Maximum number of headers in batch while syncing.
     pub sync_batch_size: u64,
+    /// Optional checkpoint used as the root of trust when verifying the network head.
+    ///
+    /// If `None`, trusted checkpoint of the `network` is used, if it has any.
+    /// See [`TrustedCheckpoint`] for more details.
+    pub sync_trusted_checkpoint: Option<TrustedCheckpoint>,
+    /// Headers older than this window are not synced.
+    ///
+    /// If `None`, syncing window of the `network` is used or, if it has none,
+    /// [`DEFAULT_SYNCING_WINDOW`].
+    pub sync_window: Option<Duration>,
+    /// Ignore `sync_window` and backfill headers all the way down to the genesis.
+    ///
+    /// Headers are never pruned in this mode, `pruning_window` applies only to
+    /// the samples and sampling metadata.
+    pub sync_archival: bool,
+    /// Blocks older than this window are not sampled. Must not be bigger than the syncing window.
+    ///
+    /// If `None`, sampling window of the `network` is used or, if it has none, the smaller
+    /// of [`DEFAULT_SAMPLING_WINDOW`] and the syncing window.
+    pub sampling_window: Option<Duration>,
+    /// Maximum number of random samples taken from each block. Must be bigger than 0.
+    ///
+    /// Default: [`DEFAULT_MAX_SAMPLES_NEEDED`].
+    pub sampling_max_samples_needed: usize,
+    /// Retrieve all the rows of the original data square of the sampled blocks and keep
+    /// them for the pruning window.
+    ///
+    /// Stored rows are served to other nodes, together with the parity rows, samples
+    /// and namespaced data derived from them.
+    pub sampling_full_storage: bool,
+    /// Headers and samples older than this window are removed from the stores.
+    /// Must not be smaller than the syncing window.
+    ///
+    /// If `None`, pruning window of the `network` is used or, if it has none, a window
+    /// 1 hour behind the syncing window, which is [`DEFAULT_PRUNING_WINDOW`] for the
+    /// default syncing window.
+    pub pruning_window: Option<Duration>,
+    /// Never remove anything from the stores, `pruning_window` is ignored.
+    pub pruning_disabled: bool,
     /// The blockstore for bitswap.
     pub blockstore: B,
     /// The store for headers.
     pub store: S,
 }
 
+impl<B, S> NodeConfig<B, S>
+where
+    B: Blockstore,
+    S: Store,
+{
+    /// Check that sampling, syncing and pruning windows and the amount of
+    /// samples are consistent with each other.
+    pub fn validate(&self) -> Result<()> {
+        self.network
+            .validate()
+            .map_err(|e| NodeError::InvalidConfig(e.to_string()))?;
+
+        if self.sampling_max_samples_needed == 0 {
+            return Err(NodeError::InvalidConfig(
+                "sampling_max_samples_needed must be bigger than 0".into(),
+            ));
+        }
+
+        let windows = self.windows();
+
+        if windows.sampling > windows.syncing {
+            return Err(NodeError::InvalidConfig(format!(
+                "sampling_window ({:?}) is bigger than sync_window ({:?})",
+                windows.sampling, windows.syncing
+            )));
+        }
+
+        match windows.pruning {
+            // Headers are not pruned in archival mode, only the sampling data is.
+            Some(pruning_window) if self.sync_archival && pruning_window < windows.sampling => {
+                return Err(NodeError::InvalidConfig(format!(
+                    "pruning_window ({pruning_window:?}) is smaller than sampling_window ({:?})",
+                    windows.sampling
+                )));
+            }
+            Some(pruning_window) if !self.sync_archival && pruning_window < windows.syncing => {
+                return Err(NodeError::InvalidConfig(format!(
+                    "pruning_window ({pruning_window:?}) is smaller than sync_window ({:?})",
+                    windows.syncing
+                )));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Syncing, sampling and pruning windows with the windows of the network
+    /// and the defaults applied.
+    fn windows(&self) -> Windows {
+        let syncing = self
+            .sync_window
+            .or(self.network.syncing_window)
+            .unwrap_or(DEFAULT_SYNCING_WINDOW);
+        let sampling = self
+            .sampling_window
+            .or(self.network.sampling_window)
+            .unwrap_or_else(|| DEFAULT_SAMPLING_WINDOW.min(syncing));
+        let pruning = if self.pruning_disabled {
+            None
+        } else {
+            Some(
+                self.pruning_window
+                    .or(self.network.pruning_window)
+                    .unwrap_or_else(|| syncing.saturating_add(PRUNING_WINDOW_MARGIN)),
+            )
+        };
+
+        Windows {
+            syncing,
+            sampling,
+            pruning,
+        }
+    }
+}
+
+/// Windows of the [`Node`] after applying the defaults of [`NodeConfig`].
+struct Windows {
+    syncing: Duration,
+    sampling: Duration,
+    pruning: Option<Duration>,
+}
+
 /// Celestia node.
 pub struct Node<B, S>
 where
@@ -93,6 +277,10 @@ where
     syncer: Option<Arc<Syncer<S>>>,
     daser: Option<Arc<Daser>>,
     pruner: Option<Arc<Pruner>>,
+    /// `None` in archival mode, where everything is synced.
+    syncing_window: Option<Duration>,
+    sampling_window: Duration,
+    metrics: Arc<Metrics>,
     tasks_cancellation_token: CancellationToken,
     network_compromised_task: JoinHandle,
 }
@@ -113,20 +301,36 @@ where
     /// Returns `Node` alogn with `EventSubscriber`. Use this to avoid missing any
     /// events that will be generated on the construction of the node.
     pub async fn new_subscribed(config: NodeConfig<B, S>) -> Result<(Self, EventSubscriber)> {
+        config.validate()?;
+        let windows = config.windows();
+
         let event_channel = EventChannel::new();
         let event_sub = event_channel.subscribe();
         let store = Arc::new(config.store);
         let blockstore = Arc::new(config.blockstore);
+        let metrics = Arc::new(Metrics::new());
+
+        let bootnodes = if config.p2p_bootnodes.is_empty() {
+            config.network.bootnodes.clone()
+        } else {
+            config.p2p_bootnodes
+        };
+        let trusted_checkpoint = config
+            .sync_trusted_checkpoint
+            .or_else(|| config.network.trusted_checkpoint());
 
         let p2p = Arc::new(
             P2p::start(P2pArgs {
-                network_id: config.network_id,
+                network_id: config.network.id,
                 local_keypair: config.p2p_local_keypair,
-                bootnodes: config.p2p_bootnodes,
+                bootnodes,
                 listen_on: config.p2p_listen_on,
                 blockstore: blockstore.clone(),
                 store: store.clone(),
                 event_pub: event_channel.publisher(),
+                peer_ban_duration: config.p2p_peer_ban_duration,
+                connection_limits: config.p2p_connection_limits,
+                metrics: metrics.clone(),
             })
             .await?,
         );
@@ -136,12 +340,21 @@ where
             p2p: p2p.clone(),
             event_pub: event_channel.publisher(),
             batch_size: config.sync_batch_size,
+            trusted_checkpoint,
+            syncing_window: windows.syncing,
+            archival: config.sync_archival,
+            metrics: metrics.clone(),
         })?);
 
         let daser = Arc::new(Daser::start(DaserArgs {
             p2p: p2p.clone(),
             store: store.clone(),
+            blockstore: blockstore.clone(),
             event_pub: event_channel.publisher(),
+            sampling_window: windows.sampling,
+            max_samples_needed: config.sampling_max_samples_needed,
+            full_storage: config.sampling_full_storage,
+            metrics: metrics.clone(),
         })?);
 
         let pruner = Arc::new(Pruner::start(PrunerArgs {
@@ -149,6 +362,10 @@ where
             blockstore: blockstore.clone(),
             event_pub: event_channel.publisher(),
             pruning_interval: DEFAULT_PRUNING_INTERVAL,
+            pruning_window: windows.pruning,
+            // Removing headers from the tail would undo the backfilling
+            archival: config.sync_archival,
+            metrics: metrics.clone(),
         }));
 
         let tasks_cancellation_token = CancellationToken::new();
@@ -184,6 +401,9 @@ where
             syncer: Some(syncer),
             daser: Some(daser),
             pruner: Some(pruner),
+            syncing_window: (!config.sync_archival).then_some(windows.syncing),
+            sampling_window: windows.sampling,
+            metrics,
             tasks_cancellation_token,
             network_compromised_task,
         };
@@ -264,6 +484,20 @@ where
         self.p2p().peer_tracker_info_watcher()
     }
 
+    /// Get the node's metrics encoded in Prometheus text format.
+    pub async fn metrics(&self) -> Result<String> {
+        let stored_headers = self.store().get_stored_header_ranges().await?;
+        let sampled_headers = self.store().get_accepted_sampling_ranges().await?;
+        self.metrics
+            .set_store_sizes(stored_headers.len(), sampled_headers.len());
+
+        let peers = self.p2p().peer_tracker_info();
+        self.metrics
+            .set_connected_peers(peers.num_connected_peers, peers.num_connected_trusted_peers);
+
+        Ok(self.metrics.encode())
+    }
+
     /// Wait until the node is connected to at least 1 peer.
     pub async fn wait_connected(&self) -> Result<()> {
         Ok(self.p2p().wait_connected().await?)
@@ -289,6 +523,14 @@ where
         Ok(self.p2p().connected_peers().await?)
     }
 
+    /// Get the scores of the connected and banned peers.
+    ///
+    /// Scores are based on the validity and latency of peers' responses and messages.
+    /// Peers with the highest scores are preferred when requesting headers.
+    pub async fn peer_scores(&self) -> Result<Vec<PeerScore>> {
+        Ok(self.p2p().peer_scores().await?)
+    }
+
     /// Trust or untrust the peer with a given ID.
     pub async fn set_peer_trust(&self, peer_id: PeerId, is_trusted: bool) -> Result<()> {
         Ok(self.p2p().set_peer_trust(peer_id, is_trusted).await?)
@@ -366,6 +608,150 @@ where
             .await?)
     }
 
+    /// Request all verified [`Blob`]s of the given [`Namespace`] from the block
+    /// at `block_height`.
+    ///
+    /// The header for `block_height` must already be synchronized into the store.
+    ///
+    /// # Errors
+    ///
+    /// On failure to receive verified shares of the namespace within a certain time, the
+    /// `NodeError::P2p(P2pError::BitswapQueryTimeout)` error will be returned.
+    pub async fn request_blobs(
+        &self,
+        namespace: Namespace,
+        block_height: u64,
+    ) -> Result<Vec<Blob>> {
+        let header = self.get_header_by_height(block_height).await?;
+        Ok(self.p2p().get_all_blobs(namespace, &header).await?)
+    }
+
+    /// Request a verified [`Blob`] with the given [`Namespace`] and [`Commitment`] from
+    /// the block at `block_height`.
+    ///
+    /// Returns `None` if the block has no such blob.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Node::request_blobs`].
+    pub async fn request_blob(
+        &self,
+        namespace: Namespace,
+        block_height: u64,
+        commitment: Commitment,
+    ) -> Result<Option<Blob>> {
+        let blobs = self.request_blobs(namespace, block_height).await?;
+
+        Ok(blobs.into_iter().find(|blob| blob.commitment == commitment))
+    }
+
+    /// Publish a [`BadEncodingFraudProof`] to the network.
+    ///
+    /// The proof can be created with [`BadEncodingFraudProof::create`] after
+    /// incorrectly encoded row or column is detected. After publishing, the node
+    /// treats the network as compromised and stops its services.
+    pub async fn publish_bad_encoding_fraud_proof(
+        &self,
+        befp: BadEncodingFraudProof,
+    ) -> Result<()> {
+        Ok(self.p2p().publish_bad_encoding_fraud_proof(befp).await?)
+    }
+
+    /// Subscribe to new verified headers appended on top of the synced chain.
+    ///
+    /// Headers are delivered in ascending order, starting with the current head of the
+    /// store, or with the initial head once it is synced if the store is empty. If the
+    /// subscriber doesn't keep up, [`SubscriptionError::Lagged`] is yielded with the number
+    /// of skipped headers and the stream continues with newer ones. The stream ends when
+    /// the node is stopped.
+    pub fn subscribe_headers(
+        &self,
+    ) -> impl Stream<Item = Result<ExtendedHeader, SubscriptionError>> + Send + 'static {
+        let new_heads = self.syncer().subscribe_new_heads();
+        let store = self.store.clone().expect("Store not initialized");
+
+        let current_head =
+            stream::once(async move { store.get_head().await.ok() }).filter_map(future::ready);
+        let new_heads = stream::unfold(new_heads, |mut new_heads| async move {
+            match new_heads.recv().await {
+                Ok(header) => Some((Ok(header), new_heads)),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    Some((Err(SubscriptionError::Lagged(skipped)), new_heads))
+                }
+                Err(broadcast::error::RecvError::Closed) => None,
+            }
+        });
+
+        // Skip the new heads which were already in the store when subscribing
+        let mut last_height = 0;
+
+        current_head.map(Ok).chain(new_heads).filter(move |header| {
+            let is_new = match header {
+                Ok(header) if header.height().value() <= last_height => false,
+                Ok(header) => {
+                    last_height = header.height().value();
+                    true
+                }
+                Err(_) => true,
+            };
+
+            future::ready(is_new)
+        })
+    }
+
+    /// Subscribe to blobs of the given namespace from the synced blocks.
+    ///
+    /// For each synced block, starting from `from_height` or from the next new head
+    /// if `None`, the namespace data is requested over shwap, verified and yielded
+    /// as [`BlobsAtHeight`]. Blocks without blobs of the namespace are yielded with an
+    /// empty list, so the last yielded height can be used to resume the subscription,
+    /// e.g. after restart.
+    ///
+    /// If `wait_for_sampling` is set, blocks are yielded only after being successfully
+    /// sampled. Blocks outside of the sampling window are never sampled, so they are
+    /// yielded right away.
+    ///
+    /// Heights are never skipped. On error, it is yielded and the same height is
+    /// retried. If the height is below the synced chain and won't be synced anymore,
+    /// e.g. because it was pruned, [`SubscriptionError::HeightNotAvailable`] is yielded
+    /// and the stream ends. The stream also ends when the node is stopped.
+    pub fn subscribe_blobs(
+        &self,
+        namespace: Namespace,
+        from_height: Option<u64>,
+        wait_for_sampling: bool,
+    ) -> impl Stream<Item = Result<BlobsAtHeight>> + Send + 'static {
+        let (tx, rx) = mpsc::channel(1);
+
+        let subscription = BlobSubscription {
+            store: self.store.clone().expect("Store not initialized"),
+            p2p: self.p2p.clone().expect("P2p not initialized"),
+            namespace,
+            next_height: from_height,
+            new_heads: self.syncer().subscribe_new_heads(),
+            events: wait_for_sampling.then(|| self.event_channel.subscribe()),
+            syncing_window: self.syncing_window,
+            sampling_window: self.sampling_window,
+        };
+
+        // Task is detached, it stops when the stream is dropped or the node is stopped.
+        spawn_cancellable(
+            self.tasks_cancellation_token.child_token(),
+            subscription.run(tx),
+        );
+
+        stream::unfold(rx, |mut rx| async move {
+            let item = rx.recv().await?;
+            Some((item, rx))
+        })
+    }
+
+    /// Wait until the header of the given height is synced and return it.
+    pub async fn wait_for_height(&self, height: u64) -> Result<ExtendedHeader> {
+        self.store().wait_height(height).await?;
+        self.get_header_by_height(height).await
+    }
+
     /// Get current header syncing info.
     pub async fn syncer_info(&self) -> Result<SyncingInfo> {
         Ok(self.syncer().info().await?)
@@ -418,6 +804,198 @@ where
             Err(e) => Err(e.into()),
         }
     }
+
+    /// Verify the balance queried from a consensus node against the synced headers.
+    ///
+    /// The queried state is committed in the `app_hash` of the header following the
+    /// query height, so this waits until that header is synced.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NodeError::InvalidStateProof`] if the response is not for the given
+    /// balance or its proof doesn't match the `app_hash`.
+    pub async fn verify_balance(
+        &self,
+        address: &AccAddress,
+        denom: &str,
+        response: &ProvenQueryResponse,
+    ) -> Result<Balance> {
+        let header = self.wait_for_height(response.height + 1).await?;
+
+        response
+            .verify_balance(&header, address, denom)
+            .map_err(NodeError::InvalidStateProof)
+    }
+}
+
+/// State of the task behind [`Node::subscribe_blobs`].
+struct BlobSubscription<S>
+where
+    S: Store + 'static,
+{
+    store: Arc<S>,
+    p2p: Arc<P2p>,
+    namespace: Namespace,
+    next_height: Option<u64>,
+    new_heads: broadcast::Receiver<ExtendedHeader>,
+    events: Option<EventSubscriber>,
+    syncing_window: Option<Duration>,
+    sampling_window: Duration,
+}
+
+impl<S> BlobSubscription<S>
+where
+    S: Store,
+{
+    async fn run(mut self, tx: mpsc::Sender<Result<BlobsAtHeight>>) {
+        if self.next_height.is_none() {
+            self.next_height = self.store.head_height().await.ok().map(|head| head + 1);
+        }
+
+        loop {
+            let res = select! {
+                // Subscriber was dropped
+                _ = tx.closed() => return,
+                res = self.next() => res,
+            };
+
+            let Some(res) = res else {
+                // Node was stopped
+                return;
+            };
+
+            let is_err = res.is_err();
+            let is_terminal = matches!(res, Err(NodeError::Subscription(_)));
+
+            if tx.send(res).await.is_err() || is_terminal {
+                return;
+            }
+
+            if is_err {
+                select! {
+                    _ = tx.closed() => return,
+                    _ = sleep(BLOB_SUBSCRIPTION_RETRY_DELAY) => {}
+                }
+            }
+        }
+    }
+
+    async fn next(&mut self) -> Option<Result<BlobsAtHeight>> {
+        let header = match self.next_header().await? {
+            Ok(header) => header,
+            Err(e) => return Some(Err(e)),
+        };
+        let height = header.height().value();
+
+        if self.events.is_some() && in_sampling_window(header.time(), self.sampling_window) {
+            if let Err(e) = self.wait_for_sampling(height).await? {
+                return Some(Err(e));
+            }
+        }
+
+        let blobs = match self.p2p.get_all_blobs(self.namespace, &header).await {
+            Ok(blobs) => blobs,
+            Err(e) => return Some(Err(e.into())),
+        };
+
+        self.next_height = Some(height + 1);
+
+        Some(Ok(BlobsAtHeight { height, blobs }))
+    }
+
+    /// Returns the header of the next height, once it is synced.
+    async fn next_header(&mut self) -> Option<Result<ExtendedHeader>> {
+        loop {
+            if let Some(next_height) = self.next_height {
+                match self.store.head_height().await {
+                    Ok(head) if next_height <= head => {
+                        match self.store.get_by_height(next_height).await {
+                            Ok(header) => return Some(Ok(header)),
+                            // Height is not backfilled yet, check again on the next head
+                            Err(StoreError::NotFound) => {
+                                match self.is_never_synced(next_height).await {
+                                    Ok(false) => {}
+                                    Ok(true) => {
+                                        let e = SubscriptionError::HeightNotAvailable(next_height);
+                                        return Some(Err(e.into()));
+                                    }
+                                    Err(e) => return Some(Err(e.into())),
+                                }
+                            }
+                            Err(e) => return Some(Err(e.into())),
+                        }
+                    }
+                    Ok(_) | Err(StoreError::NotFound) => {}
+                    Err(e) => return Some(Err(e.into())),
+                }
+            }
+
+            match self.new_heads.recv().await {
+                Ok(header) => {
+                    // Store was empty, start with the first new head
+                    self.next_height.get_or_insert(header.height().value());
+                }
+                // Missed heads are taken from the store
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Returns true if the missing header of the given height is below the synced
+    /// chain, which already reaches outside of the syncing window, e.g. after pruning.
+    async fn is_never_synced(&self, height: u64) -> Result<bool, StoreError> {
+        let Some(syncing_window) = self.syncing_window else {
+            // Everything is synced in archival mode
+            return Ok(false);
+        };
+
+        let stored_ranges = self.store.get_stored_header_ranges().await?;
+
+        match stored_ranges.tail() {
+            Some(tail) if height < tail => {
+                let tail_header = self.store.get_by_height(tail).await?;
+                Ok(!in_syncing_window(&tail_header, syncing_window))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Returns once the block of the given height is successfully sampled.
+    async fn wait_for_sampling(&mut self, height: u64) -> Option<Result<()>> {
+        let events = self.events.as_mut().expect("events not subscribed");
+
+        loop {
+            match self.store.get_sampling_metadata(height).await {
+                Ok(Some(metadata)) if metadata.status == SamplingStatus::Accepted => {
+                    return Some(Ok(()));
+                }
+                // Rejected blocks are sampled again, so keep waiting
+                Ok(_) => {}
+                Err(e) => return Some(Err(e.into())),
+            }
+
+            let sampling_finished = async {
+                loop {
+                    match events.recv().await {
+                        Ok(ev) => match ev.event {
+                            NodeEvent::SamplingFinished { height: h, .. } if h == height => {
+                                return Ok(());
+                            }
+                            _ => {}
+                        },
+                        Err(e) => return Err(e),
+                    }
+                }
+            };
+
+            if let Ok(Err(RecvError::Closed)) =
+                timeout(SAMPLING_RECHECK_INTERVAL, sampling_finished).await
+            {
+                return None;
+            }
+        }
+    }
 }
 
 impl<B, S> Drop for Node<B, S>
@@ -446,3 +1024,295 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::spawn;
+    use crate::network::Network;
+    use crate::store::InMemoryStore;
+    use crate::test_utils::{async_test, test_node_config};
+    use celestia_tendermint::Time;
+    use celestia_tendermint_proto::Protobuf;
+    use celestia_types::namespaced_data::NamespacedDataId;
+    use celestia_types::test_utils::ExtendedHeaderGenerator;
+    use celestia_types::{DataAvailabilityHeader, ExtendedDataSquare};
+
+    #[async_test]
+    async fn blob_subscription() {
+        let ns = Namespace::new_v0(&[1]).unwrap();
+        let blob = Blob::new(ns, vec![1; 100]).unwrap();
+        let eds =
+            ExtendedDataSquare::from_ods(vec![blob.to_shares().unwrap()[0].data.to_vec()]).unwrap();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+
+        let mut gen = ExtendedHeaderGenerator::new();
+        let headers = gen.next_many(3);
+        let header_with_blob = gen.next_with_dah(dah.clone());
+
+        let store = Arc::new(InMemoryStore::new());
+        store.insert(headers[..2].to_vec()).await.unwrap();
+
+        let (p2p, mut handle) = P2p::mocked();
+        let (new_heads_tx, new_heads) = broadcast::channel(16);
+        let (tx, rx) = mpsc::channel(1);
+
+        let subscription = BlobSubscription {
+            store: store.clone(),
+            p2p: Arc::new(p2p),
+            namespace: ns,
+            next_height: Some(2),
+            new_heads,
+            events: None,
+            syncing_window: Some(DEFAULT_SYNCING_WINDOW),
+            sampling_window: DEFAULT_SAMPLING_WINDOW,
+        };
+        spawn(subscription.run(tx));
+        let mut blobs = stream::unfold(rx, |mut rx| async move {
+            let item = rx.recv().await?;
+            Some((item, rx))
+        })
+        .boxed();
+
+        // Resumed from the height already in the store
+        let item = blobs.next().await.unwrap().unwrap();
+        assert_eq!(
+            item,
+            BlobsAtHeight {
+                height: 2,
+                blobs: vec![]
+            }
+        );
+
+        // New head without any data of the namespace
+        store.insert(headers[2].clone()).await.unwrap();
+        new_heads_tx.send(headers[2].clone()).unwrap();
+        let item = blobs.next().await.unwrap().unwrap();
+        assert_eq!(
+            item,
+            BlobsAtHeight {
+                height: 3,
+                blobs: vec![]
+            }
+        );
+
+        // New head with the blob of the namespace
+        store.insert(header_with_blob.clone()).await.unwrap();
+        new_heads_tx.send(header_with_blob.clone()).unwrap();
+
+        let (cid, respond_to) = handle.expect_get_shwap_cid().await;
+        let id = NamespacedDataId::try_from(cid).unwrap();
+        let data = eds.get_namespaced_data(ns, &dah, 4).unwrap();
+        let data = data.into_iter().find(|data| data.id == id).unwrap();
+        respond_to.send(Ok(data.encode_vec().unwrap())).unwrap();
+
+        let item = blobs.next().await.unwrap().unwrap();
+        assert_eq!(item.height, 4);
+        assert_eq!(item.blobs.len(), 1);
+        assert_eq!(item.blobs[0].data, blob.data);
+
+        // Stream ends once new heads aren't delivered anymore
+        drop(new_heads_tx);
+        assert!(blobs.next().await.is_none());
+    }
+
+    #[async_test]
+    async fn blob_subscription_below_synced_chain() {
+        let month_and_day_ago = Duration::from_secs(31 * 24 * 60 * 60);
+        let mut gen = ExtendedHeaderGenerator::new();
+        gen.set_time(
+            (Time::now() - month_and_day_ago).expect("to not underflow"),
+            Duration::from_secs(1),
+        );
+        let headers = gen.next_many(10);
+
+        // Tail of the synced chain is outside of the syncing window, e.g. after pruning
+        let store = Arc::new(InMemoryStore::new());
+        store.insert(headers[4..].to_vec()).await.unwrap();
+
+        let (p2p, _handle) = P2p::mocked();
+        let (new_heads_tx, new_heads) = broadcast::channel(16);
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let subscription = BlobSubscription {
+            store: store.clone(),
+            p2p: Arc::new(p2p),
+            namespace: Namespace::new_v0(&[1]).unwrap(),
+            next_height: Some(2),
+            new_heads,
+            events: None,
+            syncing_window: Some(DEFAULT_SYNCING_WINDOW),
+            sampling_window: DEFAULT_SAMPLING_WINDOW,
+        };
+        spawn(subscription.run(tx));
+
+        let e = rx.recv().await.unwrap().unwrap_err();
+        assert!(matches!(
+            e,
+            NodeError::Subscription(SubscriptionError::HeightNotAvailable(2))
+        ));
+
+        // Subscription ends after the error
+        assert!(rx.recv().await.is_none());
+        drop(new_heads_tx);
+    }
+
+    #[async_test]
+    async fn blob_subscription_stops_when_dropped() {
+        let store = Arc::new(InMemoryStore::new());
+        let (p2p, _handle) = P2p::mocked();
+        let (_new_heads_tx, new_heads) = broadcast::channel(16);
+        let (tx, rx) = mpsc::channel(1);
+
+        let subscription = BlobSubscription {
+            store,
+            p2p: Arc::new(p2p),
+            namespace: Namespace::new_v0(&[1]).unwrap(),
+            next_height: None,
+            new_heads,
+            events: None,
+            syncing_window: Some(DEFAULT_SYNCING_WINDOW),
+            sampling_window: DEFAULT_SAMPLING_WINDOW,
+        };
+        let task = spawn(subscription.run(tx));
+
+        // Task is waiting for new heads, dropping the receiver stops it
+        drop(rx);
+        timeout(Duration::from_secs(1), task.join()).await.unwrap();
+    }
+
+    #[test]
+    fn validate_config_windows() {
+        test_node_config().validate().unwrap();
+
+        let config = NodeConfig {
+            sync_window: Some(Duration::from_secs(60)),
+            sampling_window: Some(Duration::from_secs(60)),
+            pruning_disabled: true,
+            ..test_node_config()
+        };
+        config.validate().unwrap();
+
+        let config = NodeConfig {
+            sync_window: Some(Duration::from_secs(60)),
+            sampling_window: Some(Duration::from_secs(61)),
+            ..test_node_config()
+        };
+        assert!(matches!(
+            config.validate().unwrap_err(),
+            NodeError::InvalidConfig(_)
+        ));
+
+        let config = NodeConfig {
+            sync_window: Some(Duration::from_secs(60)),
+            sampling_window: Some(Duration::from_secs(60)),
+            pruning_window: Some(Duration::from_secs(59)),
+            ..test_node_config()
+        };
+        assert!(matches!(
+            config.validate().unwrap_err(),
+            NodeError::InvalidConfig(_)
+        ));
+
+        // Headers are not pruned in archival mode, pruning window is
+        // checked only against the sampling window.
+        let config = NodeConfig {
+            sync_window: Some(Duration::from_secs(60)),
+            sampling_window: Some(Duration::from_secs(30)),
+            pruning_window: Some(Duration::from_secs(30)),
+            sync_archival: true,
+            ..test_node_config()
+        };
+        config.validate().unwrap();
+
+        let config = NodeConfig {
+            sampling_window: Some(Duration::from_secs(30)),
+            pruning_window: Some(Duration::from_secs(29)),
+            sync_archival: true,
+            ..test_node_config()
+        };
+        assert!(matches!(
+            config.validate().unwrap_err(),
+            NodeError::InvalidConfig(_)
+        ));
+
+        let config = NodeConfig {
+            sampling_max_samples_needed: 0,
+            ..test_node_config()
+        };
+        assert!(matches!(
+            config.validate().unwrap_err(),
+            NodeError::InvalidConfig(_)
+        ));
+    }
+
+    #[test]
+    fn default_config_windows() {
+        let windows = test_node_config().windows();
+        assert_eq!(windows.syncing, DEFAULT_SYNCING_WINDOW);
+        assert_eq!(windows.sampling, DEFAULT_SAMPLING_WINDOW);
+        assert_eq!(windows.pruning, Some(DEFAULT_PRUNING_WINDOW));
+
+        // sampling and pruning windows follow the custom syncing window
+        let config = NodeConfig {
+            sync_window: Some(Duration::from_secs(24 * 60 * 60)),
+            ..test_node_config()
+        };
+        config.validate().unwrap();
+        let windows = config.windows();
+        assert_eq!(windows.syncing, Duration::from_secs(24 * 60 * 60));
+        assert_eq!(windows.sampling, Duration::from_secs(24 * 60 * 60));
+        assert_eq!(windows.pruning, Some(Duration::from_secs(25 * 60 * 60)));
+
+        let config = NodeConfig {
+            sync_window: Some(Duration::from_secs(60 * 24 * 60 * 60)),
+            pruning_window: Some(Duration::from_secs(60 * 24 * 60 * 60)),
+            ..test_node_config()
+        };
+        config.validate().unwrap();
+        let windows = config.windows();
+        assert_eq!(windows.sampling, DEFAULT_SAMPLING_WINDOW);
+        assert_eq!(
+            windows.pruning,
+            Some(Duration::from_secs(60 * 24 * 60 * 60))
+        );
+
+        let config = NodeConfig {
+            pruning_window: Some(Duration::from_secs(60)),
+            pruning_disabled: true,
+            ..test_node_config()
+        };
+        config.validate().unwrap();
+        assert_eq!(config.windows().pruning, None);
+    }
+
+    #[test]
+    fn network_config_windows() {
+        let mut network = NetworkConfig::from(Network::Private);
+        network.syncing_window = Some(Duration::from_secs(2 * 24 * 60 * 60));
+        network.pruning_window = Some(Duration::from_secs(3 * 24 * 60 * 60));
+
+        // windows of the network are used unless provided explicitly
+        let config = NodeConfig {
+            network: network.clone(),
+            ..test_node_config()
+        };
+        config.validate().unwrap();
+        let windows = config.windows();
+        assert_eq!(windows.syncing, Duration::from_secs(2 * 24 * 60 * 60));
+        assert_eq!(windows.sampling, Duration::from_secs(2 * 24 * 60 * 60));
+        assert_eq!(windows.pruning, Some(Duration::from_secs(3 * 24 * 60 * 60)));
+
+        let config = NodeConfig {
+            network,
+            sync_window: Some(Duration::from_secs(24 * 60 * 60)),
+            sampling_window: Some(Duration::from_secs(60 * 60)),
+            ..test_node_config()
+        };
+        config.validate().unwrap();
+        let windows = config.windows();
+        assert_eq!(windows.syncing, Duration::from_secs(24 * 60 * 60));
+        assert_eq!(windows.sampling, Duration::from_secs(60 * 60));
+        assert_eq!(windows.pruning, Some(Duration::from_secs(3 * 24 * 60 * 60)));
+    }
+}
@@ -12,8 +12,10 @@
 //! - header-ex server
 //! - bitswap 1.2.0
 //! - shwap - celestia's data availability protocol on top of bitswap
+//! - shrex/nd and shrex/eds clients
 
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::future::poll_fn;
 use std::sync::Arc;
 use std::task::Poll;
@@ -21,14 +23,20 @@ use std::time::Duration;
 
 use blockstore::Blockstore;
 use celestia_proto::p2p::pb::{header_request, HeaderRequest};
+use celestia_proto::share::p2p::shrex::nd::{GetSharesByNamespaceRequest, NamespaceRowResponse};
+use celestia_proto::EdsRequest;
 use celestia_tendermint_proto::Protobuf;
-use celestia_types::namespaced_data::NamespacedData;
-use celestia_types::nmt::Namespace;
+use celestia_types::namespaced_data::{NamespacedData, NamespacedDataId};
+use celestia_types::nmt::{Namespace, NamespacedSha2Hasher};
 use celestia_types::row::Row;
 use celestia_types::sample::Sample;
 use celestia_types::{fraud_proof::BadEncodingFraudProof, hash::Hash};
-use celestia_types::{ExtendedHeader, FraudProof};
+use celestia_types::{
+    Blob, DataAvailabilityHeader, ExtendedDataSquare, ExtendedHeader, FraudProof, Share,
+};
 use cid::Cid;
+use futures::future::try_join_all;
+use futures::Future;
 use futures::StreamExt;
 use libp2p::core::transport::ListenerId;
 use libp2p::{
@@ -40,40 +48,53 @@ use libp2p::{
     kad,
     multiaddr::Protocol,
     ping,
+    request_response::{self, OutboundRequestId},
     swarm::{
         dial_opts::{DialOpts, PeerCondition},
         ConnectionId, NetworkBehaviour, NetworkInfo, Swarm, SwarmEvent,
     },
     Multiaddr, PeerId,
 };
+use rand::seq::SliceRandom;
 use smallvec::SmallVec;
 use tokio::select;
 use tokio::sync::{mpsc, oneshot, watch};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, instrument, trace, warn};
+use web_time::Instant;
 
 mod connection_control;
 mod header_ex;
 pub(crate) mod header_session;
+mod shrex;
 pub(crate) mod shwap;
 mod swarm;
 
 use crate::block_ranges::BlockRange;
 use crate::events::{EventPublisher, NodeEvent};
 use crate::executor::{self, spawn, Interval, JoinHandle};
+use crate::metrics::{Metrics, OpResult};
 use crate::p2p::header_ex::{HeaderExBehaviour, HeaderExConfig};
 use crate::p2p::header_session::HeaderSession;
-use crate::p2p::shwap::{namespaced_data_cid, row_cid, sample_cid, ShwapMultihasher};
+use crate::p2p::shrex::{ShrexEdsBehaviour, ShrexEdsEvent, ShrexNdBehaviour, ShrexNdEvent};
+use crate::p2p::shwap::{
+    namespaced_data_cid, row_cid, sample_cid, ShwapBlockstore, ShwapMultihasher,
+};
 use crate::p2p::swarm::new_swarm;
 use crate::peer_tracker::PeerTracker;
-use crate::peer_tracker::PeerTrackerInfo;
+use crate::peer_tracker::{PeerScore, PeerTrackerInfo};
 use crate::store::Store;
 use crate::utils::{
     celestia_protocol_id, fraudsub_ident_topic, gossipsub_ident_topic, MultiaddrExt,
     OneshotResultSender, OneshotResultSenderExt, OneshotSenderExt, Token,
 };
 
+pub use crate::p2p::connection_control::{
+    ConnectionLimits, DEFAULT_MAX_CONNECTIONS, DEFAULT_MAX_CONNECTIONS_PER_PEER,
+    DEFAULT_MAX_INBOUND_CONNECTIONS, DEFAULT_MAX_OUTBOUND_CONNECTIONS,
+};
 pub use crate::p2p::header_ex::HeaderExError;
+pub use crate::p2p::shrex::ShrexError;
 
 // Minimal number of peers that we want to maintain connection to.
 // If we have fewer peers than that, we will try to reconnect / discover
@@ -85,12 +106,27 @@ pub(crate) const MAX_MH_SIZE: usize = 64;
 
 pub(crate) const GET_SAMPLE_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Maximum time for retrieving a row stored in full storage mode.
+pub(crate) const GET_ROW_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum time for retrieving namespaced data of a row over shwap, before
+/// falling back to shrex.
+const GET_NAMESPACED_DATA_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Number of peers a shrex request is tried with, before giving up.
+pub(crate) const SHREX_ATTEMPTS: usize = 3;
+
 // all fraud proofs for height bigger than head height by this threshold
 // will be ignored
 const FRAUD_PROOF_HEAD_HEIGHT_THRESHOLD: u64 = 20;
 
 pub(crate) type Result<T, E = P2pError> = std::result::Result<T, E>;
 
+/// Shrex responses come together with the peer that sent them, so that
+/// the peer can be reported if the response turns out to be invalid.
+type ShrexNdResponder = OneshotResultSender<(PeerId, Vec<NamespaceRowResponse>), P2pError>;
+type ShrexEdsResponder = OneshotResultSender<(PeerId, Vec<Vec<u8>>), P2pError>;
+
 /// Representation of all the errors that can occur in `P2p` component.
 #[derive(Debug, thiserror::Error)]
 pub enum P2pError {
@@ -141,6 +177,18 @@ pub enum P2pError {
     /// Bitswap query timed out.
     #[error("Bitswap query timed out")]
     BitswapQueryTimeout,
+
+    /// Failed to publish a message on gossipsub.
+    #[error("Gossipsub publish: {0}")]
+    GossipsubPublish(#[from] gossipsub::PublishError),
+
+    /// Data received over shwap could not be interpreted.
+    #[error("Invalid shwap data: {0}")]
+    InvalidShwapData(celestia_types::Error),
+
+    /// An error propagated from the `shrex` clients.
+    #[error("Shrex: {0}")]
+    Shrex(#[from] ShrexError),
 }
 
 impl P2pError {
@@ -160,7 +208,10 @@ impl P2pError {
             | P2pError::Bitswap(_)
             | P2pError::ProtoDecodeFailed(_)
             | P2pError::Cid(_)
-            | P2pError::BitswapQueryTimeout => false,
+            | P2pError::BitswapQueryTimeout
+            | P2pError::GossipsubPublish(_)
+            | P2pError::InvalidShwapData(_)
+            | P2pError::Shrex(_) => false,
         }
     }
 }
@@ -179,6 +230,7 @@ pub(crate) struct P2p {
     join_handle: JoinHandle,
     peer_tracker_info_watcher: watch::Receiver<PeerTrackerInfo>,
     local_peer_id: PeerId,
+    metrics: Arc<Metrics>,
 }
 
 /// Arguments used to configure the [`P2p`].
@@ -201,6 +253,12 @@ where
     pub store: Arc<S>,
     /// Event publisher.
     pub event_pub: EventPublisher,
+    /// Duration for which misbehaving peers are banned.
+    pub peer_ban_duration: Duration,
+    /// Limits of the connections with non-trusted peers.
+    pub connection_limits: ConnectionLimits,
+    /// Metrics of the node.
+    pub metrics: Arc<Metrics>,
 }
 
 #[derive(Debug)]
@@ -218,6 +276,9 @@ pub(crate) enum P2pCmd {
     ConnectedPeers {
         respond_to: oneshot::Sender<Vec<PeerId>>,
     },
+    PeerScores {
+        respond_to: oneshot::Sender<Vec<PeerScore>>,
+    },
     InitHeaderSub {
         head: Box<ExtendedHeader>,
         /// Any valid headers received by header-sub will be send to this channel.
@@ -229,6 +290,8 @@ pub(crate) enum P2pCmd {
     },
     GetShwapCid {
         cid: Cid,
+        /// Time after which the requester gives up on the query.
+        deadline: Option<Instant>,
         respond_to: OneshotResultSender<Vec<u8>, P2pError>,
     },
     GetNetworkCompromisedToken {
@@ -237,6 +300,21 @@ pub(crate) enum P2pCmd {
     GetNetworkHead {
         respond_to: oneshot::Sender<Option<ExtendedHeader>>,
     },
+    PublishBadEncodingFraudProof {
+        befp: Box<BadEncodingFraudProof>,
+        respond_to: OneshotResultSender<(), P2pError>,
+    },
+    ShrexNdRequest {
+        request: GetSharesByNamespaceRequest,
+        respond_to: ShrexNdResponder,
+    },
+    ShrexEdsRequest {
+        request: EdsRequest,
+        respond_to: ShrexEdsResponder,
+    },
+    ShrexInvalidResponse {
+        peer: PeerId,
+    },
 }
 
 impl P2p {
@@ -250,11 +328,15 @@ impl P2p {
 
         let local_peer_id = PeerId::from(args.local_keypair.public());
 
-        let peer_tracker = Arc::new(PeerTracker::new(args.event_pub.clone()));
+        let peer_tracker = Arc::new(PeerTracker::new(
+            args.event_pub.clone(),
+            args.peer_ban_duration,
+        ));
         let peer_tracker_info_watcher = peer_tracker.info_watcher();
 
         let cancellation_token = CancellationToken::new();
         let (cmd_tx, cmd_rx) = mpsc::channel(16);
+        let metrics = args.metrics.clone();
 
         let mut worker =
             Worker::new(args, cancellation_token.child_token(), cmd_rx, peer_tracker).await?;
@@ -269,6 +351,7 @@ impl P2p {
             join_handle,
             peer_tracker_info_watcher,
             local_peer_id,
+            metrics,
         })
     }
 
@@ -288,6 +371,7 @@ impl P2p {
             join_handle,
             peer_tracker_info_watcher: peer_tracker_rx,
             local_peer_id: PeerId::random(),
+            metrics: Arc::default(),
         };
 
         let handle = crate::test_utils::MockP2pHandle {
@@ -480,18 +564,27 @@ impl P2p {
 
         self.send_command(P2pCmd::GetShwapCid {
             cid,
+            deadline: timeout.map(|dur| Instant::now() + dur),
             respond_to: tx,
         })
         .await?;
 
-        let data = match timeout {
+        let res = match timeout {
             Some(dur) => executor::timeout(dur, rx)
                 .await
-                .map_err(|_| P2pError::BitswapQueryTimeout)???,
-            None => rx.await??,
+                .map_err(|_| P2pError::BitswapQueryTimeout)
+                .and_then(|res| res?),
+            None => rx.await.map_err(P2pError::from).and_then(|res| res),
+        };
+
+        let result = match res {
+            Ok(_) => OpResult::Success,
+            Err(P2pError::BitswapQueryTimeout) => OpResult::Timeout,
+            Err(_) => OpResult::Failure,
         };
+        self.metrics.bitswap_query(result);
 
-        Ok(data)
+        res
     }
 
     /// Request a [`Row`] on bitswap protocol.
@@ -502,6 +595,19 @@ impl P2p {
         Ok(Row::decode(&data[..])?)
     }
 
+    /// Request a [`Row`] on bitswap protocol, giving up after 30 seconds.
+    ///
+    /// On timeout [`P2pError::BitswapQueryTimeout`] is returned.
+    pub(crate) async fn get_row_with_timeout(
+        &self,
+        row_index: u16,
+        block_height: u64,
+    ) -> Result<Row> {
+        let cid = row_cid(row_index, block_height)?;
+        let data = self.get_shwap_cid(cid, Some(GET_ROW_TIMEOUT)).await?;
+        Ok(Row::decode(&data[..])?)
+    }
+
     /// Request a [`Sample`] on bitswap protocol.
     ///
     /// This method awaits for a verified `Sample` until timeout of 10 second
@@ -531,6 +637,211 @@ impl P2p {
         Ok(NamespacedData::decode(&data[..])?)
     }
 
+    /// Request all [`Blob`]s of the given [`Namespace`] from the block described by `header`.
+    ///
+    /// Only the rows of the original data square that may contain the namespace are
+    /// requested, concurrently, as [`NamespacedData`] and blobs are then reconstructed
+    /// from their shares. The index of each returned [`Blob`] is set to the index of its
+    /// first share in the extended data square.
+    pub async fn get_all_blobs(
+        &self,
+        namespace: Namespace,
+        header: &ExtendedHeader,
+    ) -> Result<Vec<Blob>> {
+        let height = header.height().value();
+        let eds_width = header.dah.square_width();
+        let ods_width = eds_width / 2;
+
+        let rows = (0..ods_width).filter(|&row| {
+            header
+                .dah
+                .row_root(row)
+                .is_some_and(|root| root.contains::<NamespacedSha2Hasher>(*namespace))
+        });
+
+        let shwap_data = try_join_all(rows.map(|row| async move {
+            let cid = namespaced_data_cid(namespace, row, height)?;
+            let data = self
+                .get_shwap_cid(cid, Some(GET_NAMESPACED_DATA_TIMEOUT))
+                .await?;
+            Ok::<_, P2pError>(NamespacedData::decode(&data[..])?)
+        }))
+        .await;
+
+        let namespaced_data = match shwap_data {
+            Ok(data) => data,
+            // Shwap isn't enabled on all the networks
+            Err(P2pError::BitswapQueryTimeout) => {
+                debug!("Shwap timed out, retrieving namespace {namespace:?} with shrex/nd");
+                self.get_shares_by_namespace(namespace, header).await?
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut shares = Vec::new();
+
+        for data in namespaced_data {
+            let row = u64::from(data.id.row_index());
+            let first_index = row * u64::from(eds_width) + u64::from(data.proof.start_idx());
+
+            for (i, share) in data.shares.iter().enumerate() {
+                let share = Share::from_raw(share).map_err(P2pError::InvalidShwapData)?;
+                shares.push((first_index + i as u64, share));
+            }
+        }
+
+        Blob::reconstruct_all_indexed(shares.iter().map(|(idx, share)| (*idx, share)))
+            .map_err(P2pError::InvalidShwapData)
+    }
+
+    /// Request all the shares of the [`Namespace`] from the block described by `header`
+    /// on `shrex/nd` protocol.
+    ///
+    /// The server sends every row whose root may contain the namespace, which are verified
+    /// against the [`DataAvailabilityHeader`]. Rows without any shares of the namespace
+    /// come with the proof of its absence.
+    pub async fn get_shares_by_namespace(
+        &self,
+        namespace: Namespace,
+        header: &ExtendedHeader,
+    ) -> Result<Vec<NamespacedData>> {
+        let height = header.height().value();
+        let rows: Vec<_> = (0..header.dah.square_width())
+            .filter(|&row| {
+                header
+                    .dah
+                    .row_root(row)
+                    .is_some_and(|root| root.contains::<NamespacedSha2Hasher>(*namespace))
+            })
+            .collect();
+
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let request = GetSharesByNamespaceRequest {
+            root_hash: header.dah.hash().as_bytes().to_vec(),
+            namespace: namespace.as_bytes().to_vec(),
+        };
+
+        self.shrex_with_retries(|| async {
+            let (tx, rx) = oneshot::channel();
+
+            self.send_command(P2pCmd::ShrexNdRequest {
+                request: request.clone(),
+                respond_to: tx,
+            })
+            .await?;
+
+            let (peer, response) = rx.await??;
+
+            if response.len() != rows.len() {
+                return self.reject_shrex_response(peer).await;
+            }
+
+            let data = rows
+                .iter()
+                .zip(response)
+                .map(|(&row, row_response)| {
+                    let proof = row_response
+                        .proof
+                        .ok_or(ShrexError::InvalidResponse)?
+                        .try_into()
+                        .map_err(|_| ShrexError::InvalidResponse)?;
+
+                    let data = NamespacedData {
+                        id: NamespacedDataId::new(namespace, row, height).map_err(P2pError::Cid)?,
+                        proof,
+                        shares: row_response.shares,
+                    };
+
+                    data.verify(&header.dah)
+                        .map_err(|_| ShrexError::InvalidResponse)?;
+
+                    Ok(data)
+                })
+                .collect::<Result<Vec<_>>>();
+
+            match data {
+                Err(P2pError::Shrex(ShrexError::InvalidResponse)) => {
+                    self.reject_shrex_response(peer).await
+                }
+                res => res,
+            }
+        })
+        .await
+    }
+
+    /// Request the [`ExtendedDataSquare`] of the block described by `header` on
+    /// `shrex/eds` protocol.
+    ///
+    /// The original data square is received and extended, and the resulting square
+    /// is verified against the [`DataAvailabilityHeader`].
+    pub async fn get_eds(&self, header: &ExtendedHeader) -> Result<ExtendedDataSquare> {
+        let ods_width = usize::from(header.dah.square_width() / 2);
+        let request = EdsRequest {
+            hash: header.dah.hash().as_bytes().to_vec(),
+        };
+
+        self.shrex_with_retries(|| async {
+            let (tx, rx) = oneshot::channel();
+
+            self.send_command(P2pCmd::ShrexEdsRequest {
+                request: request.clone(),
+                respond_to: tx,
+            })
+            .await?;
+
+            let (peer, mut shares) = rx.await??;
+
+            // Only the original data square is needed, server may send more.
+            if shares.len() < ods_width * ods_width {
+                return self.reject_shrex_response(peer).await;
+            }
+            shares.truncate(ods_width * ods_width);
+
+            let Ok(eds) = ExtendedDataSquare::from_ods(shares) else {
+                return self.reject_shrex_response(peer).await;
+            };
+
+            if DataAvailabilityHeader::from_eds(&eds) != header.dah {
+                return self.reject_shrex_response(peer).await;
+            }
+
+            Ok(eds)
+        })
+        .await
+    }
+
+    /// Reports the peer for sending a shrex response that failed verification
+    /// and returns [`ShrexError::InvalidResponse`].
+    async fn reject_shrex_response<T>(&self, peer: PeerId) -> Result<T> {
+        self.send_command(P2pCmd::ShrexInvalidResponse { peer })
+            .await?;
+
+        Err(ShrexError::InvalidResponse.into())
+    }
+
+    /// Runs the shrex request until it succeeds or the attempts run out. Each
+    /// attempt is sent to a randomly chosen peer.
+    async fn shrex_with_retries<T, F, Fut>(&self, request: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            match request().await {
+                Err(P2pError::Shrex(e)) if attempt < SHREX_ATTEMPTS => {
+                    debug!("Shrex request failed (attempt {attempt}): {e}");
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
     /// Get the addresses where [`P2p`] listens on for incoming connections.
     pub async fn listeners(&self) -> Result<Vec<Multiaddr>> {
         let (tx, rx) = oneshot::channel();
@@ -551,6 +862,16 @@ impl P2p {
         Ok(rx.await?)
     }
 
+    /// Get the scores of the connected and banned peers.
+    pub async fn peer_scores(&self) -> Result<Vec<PeerScore>> {
+        let (tx, rx) = oneshot::channel();
+
+        self.send_command(P2pCmd::PeerScores { respond_to: tx })
+            .await?;
+
+        Ok(rx.await?)
+    }
+
     /// Alter the trust status for a given peer.
     pub async fn set_peer_trust(&self, peer_id: PeerId, is_trusted: bool) -> Result<()> {
         self.send_command(P2pCmd::SetPeerTrust {
@@ -560,6 +881,26 @@ impl P2p {
         .await
     }
 
+    /// Publish a [`BadEncodingFraudProof`] on the `fraud-sub` topic.
+    ///
+    /// The proof is expected to be validated already, e.g. by creating it with
+    /// [`BadEncodingFraudProof::create`]. Publishing a proof also marks the network
+    /// as compromised for the local node.
+    pub async fn publish_bad_encoding_fraud_proof(
+        &self,
+        befp: BadEncodingFraudProof,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+
+        self.send_command(P2pCmd::PublishBadEncodingFraudProof {
+            befp: Box::new(befp),
+            respond_to: tx,
+        })
+        .await?;
+
+        rx.await?
+    }
+
     /// Get the cancellation token which will be cancelled when the network gets compromised.
     ///
     /// After this token is cancelled, the network should be treated as insincere
@@ -599,10 +940,12 @@ where
 {
     connection_control: connection_control::Behaviour,
     autonat: autonat::Behaviour,
-    bitswap: beetswap::Behaviour<MAX_MH_SIZE, B>,
+    bitswap: beetswap::Behaviour<MAX_MH_SIZE, ShwapBlockstore<B>>,
     ping: ping::Behaviour,
     identify: identify::Behaviour,
     header_ex: HeaderExBehaviour<S>,
+    shrex_nd: ShrexNdBehaviour,
+    shrex_eds: ShrexEdsBehaviour,
     gossipsub: gossipsub::Behaviour,
     kademlia: kad::Behaviour<kad::store::MemoryStore>,
 }
@@ -620,7 +963,9 @@ where
     cmd_rx: mpsc::Receiver<P2pCmd>,
     peer_tracker: Arc<PeerTracker>,
     header_sub_state: Option<HeaderSubState>,
-    bitswap_queries: HashMap<beetswap::QueryId, OneshotResultSender<Vec<u8>, P2pError>>,
+    bitswap_queries: HashMap<beetswap::QueryId, BitswapQuery>,
+    shrex_nd_queries: HashMap<OutboundRequestId, ShrexNdResponder>,
+    shrex_eds_queries: HashMap<OutboundRequestId, ShrexEdsResponder>,
     network_compromised_token: Token,
     store: Arc<S>,
     event_pub: EventPublisher,
@@ -632,6 +977,13 @@ struct HeaderSubState {
     channel: mpsc::Sender<ExtendedHeader>,
 }
 
+struct BitswapQuery {
+    respond_to: OneshotResultSender<Vec<u8>, P2pError>,
+    deadline: Option<Instant>,
+    /// Peers connected when the query was made.
+    peers: Vec<PeerId>,
+}
+
 impl<B, S> Worker<B, S>
 where
     B: Blockstore,
@@ -645,7 +997,8 @@ where
     ) -> Result<Self, P2pError> {
         let local_peer_id = PeerId::from(args.local_keypair.public());
 
-        let connection_control = connection_control::Behaviour::new();
+        let connection_control =
+            connection_control::Behaviour::new(peer_tracker.clone(), args.connection_limits);
         let autonat = autonat::Behaviour::new(local_peer_id, autonat::Config::default());
         let ping = ping::Behaviour::new(ping::Config::default());
 
@@ -671,7 +1024,10 @@ where
             network_id: &args.network_id,
             peer_tracker: peer_tracker.clone(),
             header_store: args.store.clone(),
+            metrics: args.metrics.clone(),
         });
+        let shrex_nd = shrex::new_nd_behaviour(&args.network_id);
+        let shrex_eds = shrex::new_eds_behaviour(&args.network_id);
 
         let behaviour = Behaviour {
             connection_control,
@@ -681,6 +1037,8 @@ where
             identify,
             gossipsub,
             header_ex,
+            shrex_nd,
+            shrex_eds,
             kademlia,
         };
 
@@ -720,6 +1078,8 @@ where
             peer_tracker,
             header_sub_state: None,
             bitswap_queries: HashMap::new(),
+            shrex_nd_queries: HashMap::new(),
+            shrex_eds_queries: HashMap::new(),
             network_compromised_token: Token::new(),
             store: args.store,
             event_pub: args.event_pub,
@@ -731,6 +1091,7 @@ where
         let mut report_interval = Interval::new(Duration::from_secs(60)).await;
         let mut kademlia_interval = Interval::new(Duration::from_secs(30)).await;
         let mut peer_tracker_info_watcher = self.peer_tracker.info_watcher();
+        let peer_tracker = self.peer_tracker.clone();
 
         self.dial_bootnodes();
 
@@ -745,6 +1106,9 @@ where
                         self.dial_bootnodes();
                     }
                 }
+                _ = peer_tracker.banned() => {
+                    self.disconnect_banned_peers();
+                }
                 _ = report_interval.tick() => {
                     self.report();
                 }
@@ -791,18 +1155,37 @@ where
         }
     }
 
+    fn disconnect_banned_peers(&mut self) {
+        for peer_id in self.peer_tracker.connected_banned_peers() {
+            debug!("Disconnecting banned peer {peer_id}");
+            let _ = self.swarm.disconnect_peer_id(peer_id);
+        }
+    }
+
     fn prune_canceled_bitswap_queries(&mut self) {
         let mut cancelled = SmallVec::<[_; 16]>::new();
 
-        for (query_id, chan) in &self.bitswap_queries {
-            if chan.is_closed() {
+        for (query_id, query) in &self.bitswap_queries {
+            if query.respond_to.is_closed() {
                 cancelled.push(*query_id);
             }
         }
 
+        let now = Instant::now();
+
         for query_id in cancelled {
-            self.bitswap_queries.remove(&query_id);
+            let Some(query) = self.bitswap_queries.remove(&query_id) else {
+                continue;
+            };
             self.swarm.behaviour_mut().bitswap.cancel(query_id);
+
+            // Requester gave up on the query because none of the peers
+            // delivered the block in time.
+            if query.deadline.is_some_and(|deadline| deadline <= now) {
+                for peer in query.peers {
+                    self.peer_tracker.record_bitswap_timeout(peer);
+                }
+            }
         }
     }
 
@@ -858,6 +1241,8 @@ where
                 BehaviourEvent::Kademlia(ev) => self.on_kademlia_event(ev).await?,
                 BehaviourEvent::Bitswap(ev) => self.on_bitswap_event(ev).await,
                 BehaviourEvent::Ping(ev) => self.on_ping_event(ev).await,
+                BehaviourEvent::ShrexNd(ev) => self.on_shrex_nd_event(ev),
+                BehaviourEvent::ShrexEds(ev) => self.on_shrex_eds_event(ev),
                 BehaviourEvent::Autonat(_)
                 | BehaviourEvent::ConnectionControl(_)
                 | BehaviourEvent::HeaderEx(_) => {}
@@ -916,6 +1301,9 @@ where
             P2pCmd::ConnectedPeers { respond_to } => {
                 respond_to.maybe_send(self.peer_tracker.connected_peers());
             }
+            P2pCmd::PeerScores { respond_to } => {
+                respond_to.maybe_send(self.peer_tracker.scores());
+            }
             P2pCmd::InitHeaderSub { head, channel } => {
                 self.on_init_header_sub(*head, channel);
             }
@@ -927,8 +1315,12 @@ where
                     self.peer_tracker.set_trusted(peer_id, is_trusted);
                 }
             }
-            P2pCmd::GetShwapCid { cid, respond_to } => {
-                self.on_get_shwap_cid(cid, respond_to);
+            P2pCmd::GetShwapCid {
+                cid,
+                deadline,
+                respond_to,
+            } => {
+                self.on_get_shwap_cid(cid, deadline, respond_to);
             }
             P2pCmd::GetNetworkCompromisedToken { respond_to } => {
                 respond_to.maybe_send(self.network_compromised_token.clone())
@@ -940,6 +1332,44 @@ where
                     .map(|state| state.known_head.clone());
                 respond_to.maybe_send(head);
             }
+            P2pCmd::PublishBadEncodingFraudProof { befp, respond_to } => {
+                self.on_publish_bad_encoding_fraud_proof(*befp, respond_to);
+            }
+            P2pCmd::ShrexNdRequest {
+                request,
+                respond_to,
+            } => {
+                let Some(peer) = self.shrex_peer() else {
+                    respond_to.maybe_send_err(P2pError::NoConnectedPeers);
+                    return Ok(());
+                };
+
+                let request_id = self
+                    .swarm
+                    .behaviour_mut()
+                    .shrex_nd
+                    .send_request(&peer, request);
+                self.shrex_nd_queries.insert(request_id, respond_to);
+            }
+            P2pCmd::ShrexEdsRequest {
+                request,
+                respond_to,
+            } => {
+                let Some(peer) = self.shrex_peer() else {
+                    respond_to.maybe_send_err(P2pError::NoConnectedPeers);
+                    return Ok(());
+                };
+
+                let request_id = self
+                    .swarm
+                    .behaviour_mut()
+                    .shrex_eds
+                    .send_request(&peer, request);
+                self.shrex_eds_queries.insert(request_id, respond_to);
+            }
+            P2pCmd::ShrexInvalidResponse { peer } => {
+                self.peer_tracker.record_invalid_shrex_response(peer);
+            }
         }
 
         Ok(())
@@ -980,7 +1410,7 @@ where
             gossipsub::Event::Message {
                 message,
                 message_id,
-                ..
+                propagation_source,
             } => {
                 let Some(peer) = message.source else {
                     // Validation mode is `strict` so this will never happen
@@ -997,7 +1427,9 @@ where
                     gossipsub::MessageAcceptance::Ignore
                 };
 
-                if !matches!(acceptance, gossipsub::MessageAcceptance::Reject) {
+                if matches!(acceptance, gossipsub::MessageAcceptance::Reject) {
+                    self.peer_tracker.record_invalid_gossip(propagation_source);
+                } else {
                     // We may have discovered a new peer
                     self.peer_maybe_discovered(peer);
                 }
@@ -1027,41 +1459,137 @@ where
     }
 
     #[instrument(level = "trace", skip_all)]
-    fn on_get_shwap_cid(&mut self, cid: Cid, respond_to: OneshotResultSender<Vec<u8>, P2pError>) {
+    fn on_get_shwap_cid(
+        &mut self,
+        cid: Cid,
+        deadline: Option<Instant>,
+        respond_to: OneshotResultSender<Vec<u8>, P2pError>,
+    ) {
         trace!("Requesting CID {cid} from bitswap");
         let query_id = self.swarm.behaviour_mut().bitswap.get(&cid);
-        self.bitswap_queries.insert(query_id, respond_to);
+        // Bitswap asks all the connected peers for the block, so they are
+        // the ones to blame if it times out.
+        let peers = if deadline.is_some() {
+            self.peer_tracker.connected_peers()
+        } else {
+            Vec::new()
+        };
+
+        self.bitswap_queries.insert(
+            query_id,
+            BitswapQuery {
+                respond_to,
+                deadline,
+                peers,
+            },
+        );
     }
 
     #[instrument(level = "trace", skip(self))]
     async fn on_bitswap_event(&mut self, ev: beetswap::Event) {
         match ev {
             beetswap::Event::GetQueryResponse { query_id, data } => {
-                if let Some(respond_to) = self.bitswap_queries.remove(&query_id) {
-                    respond_to.maybe_send_ok(data);
+                if let Some(query) = self.bitswap_queries.remove(&query_id) {
+                    query.respond_to.maybe_send_ok(data);
                 }
             }
             beetswap::Event::GetQueryError { query_id, error } => {
-                if let Some(respond_to) = self.bitswap_queries.remove(&query_id) {
+                if let Some(query) = self.bitswap_queries.remove(&query_id) {
                     let error: P2pError = error.into();
-                    respond_to.maybe_send_err(error);
+                    query.respond_to.maybe_send_err(error);
                 }
             }
         }
     }
 
+    /// Chooses a peer for a shrex request. Only full and bridge nodes serve
+    /// shrex, so trusted peers, such as bootnodes, are preferred.
+    fn shrex_peer(&self) -> Option<PeerId> {
+        self.peer_tracker
+            .trusted_n_peers(usize::MAX)
+            .choose(&mut rand::thread_rng())
+            .copied()
+            .or_else(|| self.peer_tracker.best_peer())
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    fn on_shrex_nd_event(&mut self, ev: ShrexNdEvent) {
+        match ev {
+            request_response::Event::Message {
+                peer,
+                message:
+                    request_response::Message::Response {
+                        request_id,
+                        response,
+                    },
+                ..
+            } => {
+                if matches!(response, Err(ShrexError::InvalidResponse)) {
+                    self.peer_tracker.record_invalid_shrex_response(peer);
+                }
+
+                if let Some(respond_to) = self.shrex_nd_queries.remove(&request_id) {
+                    respond_to.maybe_send(response.map(|res| (peer, res)).map_err(Into::into));
+                }
+            }
+            request_response::Event::OutboundFailure {
+                request_id, error, ..
+            } => {
+                if let Some(respond_to) = self.shrex_nd_queries.remove(&request_id) {
+                    respond_to.maybe_send_err(ShrexError::OutboundFailure(error));
+                }
+            }
+            _ => trace!("Unhandled shrex/nd event"),
+        }
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    fn on_shrex_eds_event(&mut self, ev: ShrexEdsEvent) {
+        match ev {
+            request_response::Event::Message {
+                peer,
+                message:
+                    request_response::Message::Response {
+                        request_id,
+                        response,
+                    },
+                ..
+            } => {
+                if matches!(response, Err(ShrexError::InvalidResponse)) {
+                    self.peer_tracker.record_invalid_shrex_response(peer);
+                }
+
+                if let Some(respond_to) = self.shrex_eds_queries.remove(&request_id) {
+                    respond_to.maybe_send(response.map(|res| (peer, res)).map_err(Into::into));
+                }
+            }
+            request_response::Event::OutboundFailure {
+                request_id, error, ..
+            } => {
+                if let Some(respond_to) = self.shrex_eds_queries.remove(&request_id) {
+                    respond_to.maybe_send_err(ShrexError::OutboundFailure(error));
+                }
+            }
+            _ => trace!("Unhandled shrex/eds event"),
+        }
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn on_ping_event(&mut self, ev: ping::Event) {
         match ev.result {
-            Ok(dur) => debug!(
-                "Ping success: peer: {}, connection_id: {}, time: {:?}",
-                ev.peer, ev.connection, dur
-            ),
+            Ok(dur) => {
+                debug!(
+                    "Ping success: peer: {}, connection_id: {}, time: {:?}",
+                    ev.peer, ev.connection, dur
+                );
+                self.peer_tracker.record_ping_rtt(ev.peer, dur);
+            }
             Err(e) => {
                 debug!(
                     "Ping failure: peer: {}, connection_id: {}, error: {}",
                     &ev.peer, &ev.connection, e
                 );
+                self.peer_tracker.record_ping_failure(ev.peer);
                 self.swarm.close_connection(ev.connection);
             }
         }
@@ -1151,6 +1679,37 @@ where
         gossipsub::MessageAcceptance::Accept
     }
 
+    #[instrument(skip_all)]
+    fn on_publish_bad_encoding_fraud_proof(
+        &mut self,
+        befp: BadEncodingFraudProof,
+        respond_to: OneshotResultSender<(), P2pError>,
+    ) {
+        // until unwrap_infallible is stabilised, make sure Result is Infallible manually
+        let data: Result<_, Infallible> = befp.encode_vec();
+        let data = data.unwrap();
+
+        let res = self
+            .swarm
+            .behaviour_mut()
+            .gossipsub
+            .publish(self.bad_encoding_fraud_sub_topic.clone(), data);
+
+        if let Err(e) = res {
+            respond_to.maybe_send_err(e);
+            return;
+        }
+
+        warn!(
+            "Published bad encoding fraud proof for block {}",
+            befp.height()
+        );
+        // trigger cancellation for all services
+        self.network_compromised_token.trigger();
+
+        respond_to.maybe_send_ok(());
+    }
+
     #[instrument(skip_all)]
     async fn on_bad_encoding_fraud_sub_message(
         &mut self,
@@ -1202,12 +1761,10 @@ where
 }
 
 /// Awaits at least one channel from the `bitswap_queries` to close.
-async fn poll_closed(
-    bitswap_queries: &mut HashMap<beetswap::QueryId, OneshotResultSender<Vec<u8>, P2pError>>,
-) {
+async fn poll_closed(bitswap_queries: &mut HashMap<beetswap::QueryId, BitswapQuery>) {
     poll_fn(|cx| {
-        for chan in bitswap_queries.values_mut() {
-            match chan.poll_closed(cx) {
+        for query in bitswap_queries.values_mut() {
+            match query.respond_to.poll_closed(cx) {
                 Poll::Pending => continue,
                 Poll::Ready(_) => return Poll::Ready(()),
             }
@@ -1296,16 +1853,149 @@ fn init_bitswap<B, S>(
     blockstore: Arc<B>,
     store: Arc<S>,
     network_id: &str,
-) -> Result<beetswap::Behaviour<MAX_MH_SIZE, B>>
+) -> Result<beetswap::Behaviour<MAX_MH_SIZE, ShwapBlockstore<B>>>
 where
     B: Blockstore + 'static,
     S: Store + 'static,
 {
     let protocol_prefix = format!("/celestia/{}", network_id);
 
+    let blockstore = Arc::new(ShwapBlockstore::new(blockstore));
+
     Ok(beetswap::Behaviour::builder(blockstore)
         .protocol_prefix(&protocol_prefix)?
         .register_multihasher(ShwapMultihasher::new(store))
         .client_set_send_dont_have(false)
         .build())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::async_test;
+    use celestia_types::namespaced_data::NamespacedDataId;
+    use celestia_types::test_utils::ExtendedHeaderGenerator;
+    use celestia_types::{DataAvailabilityHeader, ExtendedDataSquare};
+
+    #[async_test]
+    async fn get_all_blobs() {
+        let ns_before = Namespace::new_v0(&[1]).unwrap();
+        let ns = Namespace::new_v0(&[2]).unwrap();
+        let ns_after = Namespace::new_v0(&[3]).unwrap();
+
+        // 3 shares, 2 shares crossing the row boundary, 1 share, 10 shares
+        let blobs = [
+            Blob::new(ns_before, vec![1; 1200]).unwrap(),
+            Blob::new(ns, vec![2; 900]).unwrap(),
+            Blob::new(ns, vec![3; 100]).unwrap(),
+            Blob::new(ns_after, vec![4; 4800]).unwrap(),
+        ];
+        let ods = blobs
+            .iter()
+            .flat_map(|blob| blob.to_shares().unwrap())
+            .map(|share| share.data.to_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(ods.len(), 16);
+
+        let eds = ExtendedDataSquare::from_ods(ods).unwrap();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let header = ExtendedHeaderGenerator::new().next_with_dah(dah.clone());
+        let height = header.height().value();
+
+        let (p2p, mut handle) = P2p::mocked();
+
+        let namespaced_data = eds.get_namespaced_data(ns, &dah, height).unwrap();
+        assert_eq!(namespaced_data.len(), 2);
+
+        let respond = async {
+            for _ in 0..namespaced_data.len() {
+                let (cid, respond_to) = handle.expect_get_shwap_cid().await;
+                let id = NamespacedDataId::try_from(cid).unwrap();
+                let data = namespaced_data.iter().find(|data| data.id == id).unwrap();
+                respond_to.send(Ok(data.encode_vec().unwrap())).unwrap();
+            }
+        };
+
+        let (received, _) = tokio::join!(p2p.get_all_blobs(ns, &header), respond);
+        let received = received.unwrap();
+        handle.expect_no_cmd().await;
+
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].data, blobs[1].data);
+        assert_eq!(received[0].index, Some(3));
+        assert_eq!(received[1].data, blobs[2].data);
+        // second row of EDS starts at index 8
+        assert_eq!(received[1].index, Some(9));
+    }
+
+    #[async_test]
+    async fn get_all_blobs_shrex_fallback() {
+        let ns = Namespace::new_v0(&[2]).unwrap();
+        let ns_after = Namespace::new_v0(&[3]).unwrap();
+
+        // 2 shares, 1 share, 1 share
+        let blobs = [
+            Blob::new(ns, vec![1; 900]).unwrap(),
+            Blob::new(ns, vec![2; 100]).unwrap(),
+            Blob::new(ns_after, vec![3; 100]).unwrap(),
+        ];
+        let ods = blobs
+            .iter()
+            .flat_map(|blob| blob.to_shares().unwrap())
+            .map(|share| share.data.to_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(ods.len(), 4);
+
+        let eds = ExtendedDataSquare::from_ods(ods).unwrap();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let header = ExtendedHeaderGenerator::new().next_with_dah(dah.clone());
+        let height = header.height().value();
+
+        let (p2p, mut handle) = P2p::mocked();
+
+        let namespaced_data = eds.get_namespaced_data(ns, &dah, height).unwrap();
+        assert_eq!(namespaced_data.len(), 2);
+
+        let respond = async {
+            // Shwap isn't served
+            for _ in 0..namespaced_data.len() {
+                let (_, respond_to) = handle.expect_get_shwap_cid().await;
+                let _ = respond_to.send(Err(P2pError::BitswapQueryTimeout));
+            }
+
+            let rows: Vec<_> = namespaced_data
+                .iter()
+                .map(|data| NamespaceRowResponse {
+                    shares: data.shares.clone(),
+                    proof: Some(data.proof.clone().into()),
+                })
+                .collect();
+
+            // Response which fails the verification is rejected and its sender reported
+            let (request, respond_to) = handle.expect_shrex_nd_request().await;
+            assert_eq!(request.root_hash, dah.hash().as_bytes());
+            assert_eq!(request.namespace, ns.as_bytes());
+
+            let malicious = PeerId::random();
+            let mut invalid_rows = rows.clone();
+            invalid_rows[0].shares.pop();
+            respond_to.send(Ok((malicious, invalid_rows))).unwrap();
+
+            assert_eq!(handle.expect_shrex_invalid_response().await, malicious);
+
+            // Request is retried
+            let (_, respond_to) = handle.expect_shrex_nd_request().await;
+            respond_to.send(Ok((PeerId::random(), rows))).unwrap();
+        };
+
+        let (received, _) = tokio::join!(p2p.get_all_blobs(ns, &header), respond);
+        let received = received.unwrap();
+        handle.expect_no_cmd().await;
+
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].data, blobs[0].data);
+        assert_eq!(received[0].index, Some(0));
+        assert_eq!(received[1].data, blobs[1].data);
+        assert_eq!(received[1].index, Some(4));
+    }
+}
@@ -1,32 +1,258 @@
-use std::task::{Context, Poll};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
 
 use libp2p::{
     core::{transport::PortUse, Endpoint},
     swarm::{
-        dummy, ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, THandler,
-        THandlerInEvent, THandlerOutEvent, ToSwarm,
+        dummy, CloseConnection, ConnectionClosed, ConnectionDenied, ConnectionId, DialFailure,
+        FromSwarm, ListenFailure, NetworkBehaviour, THandler, THandlerInEvent, THandlerOutEvent,
+        ToSwarm,
     },
     Multiaddr, PeerId,
 };
 use void::Void;
+use web_time::{Duration, Instant};
+
+use crate::peer_tracker::PeerTracker;
+
+/// Default maximum number of inbound connections.
+pub const DEFAULT_MAX_INBOUND_CONNECTIONS: usize = 128;
+/// Default maximum number of outbound connections.
+pub const DEFAULT_MAX_OUTBOUND_CONNECTIONS: usize = 64;
+/// Default maximum number of connections.
+pub const DEFAULT_MAX_CONNECTIONS: usize = 160;
+/// Default maximum number of connections with a single peer.
+pub const DEFAULT_MAX_CONNECTIONS_PER_PEER: usize = 2;
+
+/// How much the score of a new peer needs to exceed the score of the peer
+/// whose connection is closed to make room for it.
+const EVICTION_SCORE_MARGIN: f64 = 5.0;
+/// Connections younger than this are never closed to make room for new ones.
+const MIN_EVICTED_CONNECTION_AGE: Duration = Duration::from_secs(60);
+
+/// Limits of the connections with the peers.
+///
+/// Connections with trusted peers, including bootnodes, are exempt from the
+/// limits and are not counted towards them. `None` means no limit.
+///
+/// When a new connection would exceed a limit, the connection with the lowest
+/// scored peer is closed to make room for it. Only peers that earned a positive
+/// reputation and whose score is clearly better than the lowest one can do
+/// that, and only connections that have been open for a while are closed.
+/// Otherwise the new connection is denied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionLimits {
+    /// Maximum number of inbound connections.
+    pub max_inbound: Option<usize>,
+    /// Maximum number of outbound connections.
+    pub max_outbound: Option<usize>,
+    /// Maximum number of inbound and outbound connections.
+    pub max_total: Option<usize>,
+    /// Maximum number of connections with a single peer.
+    pub max_per_peer: Option<usize>,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        ConnectionLimits {
+            max_inbound: Some(DEFAULT_MAX_INBOUND_CONNECTIONS),
+            max_outbound: Some(DEFAULT_MAX_OUTBOUND_CONNECTIONS),
+            max_total: Some(DEFAULT_MAX_CONNECTIONS),
+            max_per_peer: Some(DEFAULT_MAX_CONNECTIONS_PER_PEER),
+        }
+    }
+}
+
+impl ConnectionLimits {
+    /// Limits that allow any number of connections.
+    pub fn unlimited() -> Self {
+        ConnectionLimits {
+            max_inbound: None,
+            max_outbound: None,
+            max_total: None,
+            max_per_peer: None,
+        }
+    }
+}
 
-// TODO: Wrap ConnectionLimits in it and exclude limits from trusted peers
 pub(crate) struct Behaviour {
     stopping: bool,
+    peer_tracker: Arc<PeerTracker>,
+    limits: ConnectionLimits,
+    min_evicted_age: Duration,
+    connections: HashMap<ConnectionId, Connection>,
+    next_seq: u64,
+    to_close: VecDeque<(PeerId, ConnectionId)>,
+    waker: Option<Waker>,
+}
+
+struct Connection {
+    peer: PeerId,
+    endpoint: Endpoint,
+    /// Increasing sequence number, used to find the newest connections.
+    seq: u64,
+    established_at: Instant,
+    closing: bool,
 }
 
 #[derive(Debug, thiserror::Error)]
 #[error("Swarm is stopping")]
 struct Stopping;
 
+#[derive(Debug, thiserror::Error)]
+#[error("Peer {0} is banned")]
+struct Banned(PeerId);
+
+#[derive(Debug, thiserror::Error)]
+#[error("Connection limit exceeded: {0}")]
+struct LimitExceeded(&'static str);
+
 impl Behaviour {
-    pub(crate) fn new() -> Behaviour {
-        Behaviour { stopping: false }
+    pub(crate) fn new(peer_tracker: Arc<PeerTracker>, limits: ConnectionLimits) -> Behaviour {
+        Behaviour {
+            stopping: false,
+            peer_tracker,
+            limits,
+            min_evicted_age: MIN_EVICTED_CONNECTION_AGE,
+            connections: HashMap::new(),
+            next_seq: 0,
+            to_close: VecDeque::new(),
+            waker: None,
+        }
     }
 
     pub(crate) fn set_stopping(&mut self, value: bool) {
         self.stopping = value;
     }
+
+    fn check_banned(&self, peer: &PeerId) -> Result<(), ConnectionDenied> {
+        if self.peer_tracker.is_banned(peer) {
+            Err(ConnectionDenied::new(Banned(*peer)))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Connections that are counted towards the limits.
+    fn limited_connections(&self) -> impl Iterator<Item = (&ConnectionId, &Connection)> {
+        self.connections
+            .iter()
+            .filter(|(_, conn)| !conn.closing && !self.peer_tracker.is_trusted(&conn.peer))
+    }
+
+    fn on_established_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        endpoint: Endpoint,
+    ) -> Result<(), ConnectionDenied> {
+        if self.stopping {
+            return Err(ConnectionDenied::new(Stopping));
+        }
+
+        self.check_banned(&peer)?;
+
+        if !self.peer_tracker.is_trusted(&peer) {
+            self.check_limits(peer, endpoint)?;
+        }
+
+        self.connections.insert(
+            connection_id,
+            Connection {
+                peer,
+                endpoint,
+                seq: self.next_seq,
+                established_at: Instant::now(),
+                closing: false,
+            },
+        );
+        self.next_seq += 1;
+
+        Ok(())
+    }
+
+    /// Checks if a new connection fits into the limits, making room for it
+    /// by closing a lower scored connection if needed.
+    fn check_limits(&mut self, peer: PeerId, endpoint: Endpoint) -> Result<(), ConnectionDenied> {
+        let (mut per_peer, mut inbound, mut outbound, mut total) = (0, 0, 0, 0);
+
+        for (_, conn) in self.limited_connections() {
+            if conn.peer == peer {
+                per_peer += 1;
+            }
+
+            match conn.endpoint {
+                Endpoint::Listener => inbound += 1,
+                Endpoint::Dialer => outbound += 1,
+            }
+
+            total += 1;
+        }
+
+        if exceeds(per_peer, self.limits.max_per_peer) {
+            return Err(ConnectionDenied::new(LimitExceeded("per peer")));
+        }
+
+        let direction_exceeded = match endpoint {
+            Endpoint::Listener => exceeds(inbound, self.limits.max_inbound),
+            Endpoint::Dialer => exceeds(outbound, self.limits.max_outbound),
+        };
+
+        if !direction_exceeded && !exceeds(total, self.limits.max_total) {
+            return Ok(());
+        }
+
+        let reason = match endpoint {
+            _ if !direction_exceeded => "total",
+            Endpoint::Listener => "inbound",
+            Endpoint::Dialer => "outbound",
+        };
+
+        // Unknown peers, or peers that didn't earn any reputation yet, are not
+        // allowed to push out existing connections. Otherwise anyone could churn
+        // our connections just by opening new ones.
+        if self.peer_tracker.reputation(&peer) <= 0.0 {
+            return Err(ConnectionDenied::new(LimitExceeded(reason)));
+        }
+
+        // If limit of the direction is reached, only closing a connection of the
+        // same direction makes room. Otherwise closing any connection does.
+        let victim = self
+            .limited_connections()
+            .filter(|(_, conn)| !direction_exceeded || conn.endpoint == endpoint)
+            .filter(|(_, conn)| conn.established_at.elapsed() >= self.min_evicted_age)
+            .map(|(id, conn)| (*id, conn, self.peer_tracker.score(&conn.peer)))
+            // Lowest score first and if scores are equal, newest connection first
+            .min_by(|(_, a, a_score), (_, b, b_score)| {
+                a_score.total_cmp(b_score).then(b.seq.cmp(&a.seq))
+            })
+            .map(|(id, conn, score)| (id, conn.peer, score));
+
+        match victim {
+            Some((id, victim_peer, victim_score))
+                if self.peer_tracker.score(&peer) > victim_score + EVICTION_SCORE_MARGIN =>
+            {
+                if let Some(conn) = self.connections.get_mut(&id) {
+                    conn.closing = true;
+                }
+
+                self.to_close.push_back((victim_peer, id));
+
+                if let Some(waker) = self.waker.take() {
+                    waker.wake();
+                }
+
+                Ok(())
+            }
+            _ => Err(ConnectionDenied::new(LimitExceeded(reason))),
+        }
+    }
+}
+
+/// Returns true if adding one more connection exceeds the limit.
+fn exceeds(current: usize, limit: Option<usize>) -> bool {
+    limit.is_some_and(|limit| current >= limit)
 }
 
 impl NetworkBehaviour for Behaviour {
@@ -48,22 +274,19 @@ impl NetworkBehaviour for Behaviour {
 
     fn handle_established_inbound_connection(
         &mut self,
-        _connection_id: ConnectionId,
-        _peer: PeerId,
+        connection_id: ConnectionId,
+        peer: PeerId,
         _local_addr: &Multiaddr,
         _remote_addr: &Multiaddr,
     ) -> Result<THandler<Self>, ConnectionDenied> {
-        if self.stopping {
-            return Err(ConnectionDenied::new(Stopping));
-        }
-
+        self.on_established_connection(connection_id, peer, Endpoint::Listener)?;
         Ok(dummy::ConnectionHandler)
     }
 
     fn handle_pending_outbound_connection(
         &mut self,
         _connection_id: ConnectionId,
-        _maybe_peer: Option<PeerId>,
+        maybe_peer: Option<PeerId>,
         _addresses: &[Multiaddr],
         _effective_role: Endpoint,
     ) -> Result<Vec<Multiaddr>, ConnectionDenied> {
@@ -71,21 +294,22 @@ impl NetworkBehaviour for Behaviour {
             return Err(ConnectionDenied::new(Stopping));
         }
 
+        if let Some(peer) = maybe_peer {
+            self.check_banned(&peer)?;
+        }
+
         Ok(Vec::new())
     }
 
     fn handle_established_outbound_connection(
         &mut self,
-        _connection_id: ConnectionId,
-        _peer: PeerId,
+        connection_id: ConnectionId,
+        peer: PeerId,
         _addr: &Multiaddr,
         _role_override: Endpoint,
         _port_use: PortUse,
     ) -> Result<THandler<Self>, ConnectionDenied> {
-        if self.stopping {
-            return Err(ConnectionDenied::new(Stopping));
-        }
-
+        self.on_established_connection(connection_id, peer, Endpoint::Dialer)?;
         Ok(dummy::ConnectionHandler)
     }
 
@@ -97,12 +321,198 @@ impl NetworkBehaviour for Behaviour {
     ) {
     }
 
-    fn on_swarm_event(&mut self, _event: FromSwarm) {}
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        // Connection can be denied by other behaviours after we accepted it.
+        match event {
+            FromSwarm::ConnectionClosed(ConnectionClosed { connection_id, .. })
+            | FromSwarm::DialFailure(DialFailure { connection_id, .. })
+            | FromSwarm::ListenFailure(ListenFailure { connection_id, .. }) => {
+                self.connections.remove(&connection_id);
+            }
+            _ => {}
+        }
+    }
 
     fn poll(
         &mut self,
-        _cx: &mut Context<'_>,
+        cx: &mut Context<'_>,
     ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some((peer_id, connection_id)) = self.to_close.pop_front() {
+            return Poll::Ready(ToSwarm::CloseConnection {
+                peer_id,
+                connection: CloseConnection::One(connection_id),
+            });
+        }
+
+        self.waker = Some(cx.waker().to_owned());
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventChannel;
+    use crate::peer_tracker::DEFAULT_PEER_BAN_DURATION;
+    use futures::task::noop_waker_ref;
+
+    fn behaviour(limits: ConnectionLimits) -> Behaviour {
+        let event_channel = EventChannel::new();
+        let peer_tracker = Arc::new(PeerTracker::new(
+            event_channel.publisher(),
+            DEFAULT_PEER_BAN_DURATION,
+        ));
+        let mut behaviour = Behaviour::new(peer_tracker, limits);
+        // Allow closing connections right after they are established.
+        behaviour.min_evicted_age = Duration::ZERO;
+        behaviour
+    }
+
+    fn reward(peer_tracker: &PeerTracker, peer: PeerId, responses: usize) {
+        for _ in 0..responses {
+            peer_tracker.record_header_ex_response(peer, Some(Duration::ZERO), true);
+        }
+    }
+
+    fn connect(
+        behaviour: &mut Behaviour,
+        id: usize,
+        peer: PeerId,
+        endpoint: Endpoint,
+    ) -> Result<(), ConnectionDenied> {
+        behaviour.on_established_connection(ConnectionId::new_unchecked(id), peer, endpoint)
+    }
+
+    fn poll_closed(behaviour: &mut Behaviour) -> Option<(PeerId, ConnectionId)> {
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        match behaviour.poll(&mut cx) {
+            Poll::Ready(ToSwarm::CloseConnection {
+                peer_id,
+                connection: CloseConnection::One(id),
+            }) => Some((peer_id, id)),
+            Poll::Ready(_) => panic!("unexpected event"),
+            Poll::Pending => None,
+        }
+    }
+
+    #[test]
+    fn per_peer_limit() {
+        let mut behaviour = behaviour(ConnectionLimits {
+            max_per_peer: Some(2),
+            ..ConnectionLimits::unlimited()
+        });
+        let peer = PeerId::random();
+
+        connect(&mut behaviour, 1, peer, Endpoint::Listener).unwrap();
+        connect(&mut behaviour, 2, peer, Endpoint::Dialer).unwrap();
+        connect(&mut behaviour, 3, peer, Endpoint::Listener).unwrap_err();
+        connect(&mut behaviour, 4, PeerId::random(), Endpoint::Listener).unwrap();
+    }
+
+    #[test]
+    fn trusted_peers_exempt() {
+        let mut behaviour = behaviour(ConnectionLimits {
+            max_inbound: Some(1),
+            max_per_peer: Some(1),
+            ..ConnectionLimits::unlimited()
+        });
+        let trusted = PeerId::random();
+        behaviour.peer_tracker.set_trusted(trusted, true);
+
+        connect(&mut behaviour, 1, trusted, Endpoint::Listener).unwrap();
+        connect(&mut behaviour, 2, trusted, Endpoint::Listener).unwrap();
+        connect(&mut behaviour, 3, PeerId::random(), Endpoint::Listener).unwrap();
+        connect(&mut behaviour, 4, PeerId::random(), Endpoint::Listener).unwrap_err();
+        connect(&mut behaviour, 5, trusted, Endpoint::Listener).unwrap();
+
+        assert!(poll_closed(&mut behaviour).is_none());
+    }
+
+    #[test]
+    fn prune_lowest_scored_connection() {
+        let mut behaviour = behaviour(ConnectionLimits {
+            max_inbound: Some(2),
+            max_total: Some(3),
+            ..ConnectionLimits::unlimited()
+        });
+        let bad = PeerId::random();
+        let good = PeerId::random();
+        let new = PeerId::random();
+        let outbound = PeerId::random();
+
+        let peer_tracker = behaviour.peer_tracker.clone();
+        peer_tracker.record_header_ex_failure(bad);
+        reward(&peer_tracker, good, 1);
+        reward(&peer_tracker, new, 1);
+
+        connect(&mut behaviour, 1, bad, Endpoint::Listener).unwrap();
+        connect(&mut behaviour, 2, good, Endpoint::Listener).unwrap();
+        connect(&mut behaviour, 3, outbound, Endpoint::Dialer).unwrap();
+
+        // Inbound limit is reached, connection with lower scored peer is closed.
+        connect(&mut behaviour, 4, new, Endpoint::Listener).unwrap();
+        assert_eq!(
+            poll_closed(&mut behaviour),
+            Some((bad, ConnectionId::new_unchecked(1)))
+        );
+        assert!(poll_closed(&mut behaviour).is_none());
+
+        // Peer without a clearly better score than existing ones is denied.
+        connect(&mut behaviour, 5, PeerId::random(), Endpoint::Listener).unwrap_err();
+        let slightly_better = PeerId::random();
+        reward(&peer_tracker, slightly_better, 3);
+        connect(&mut behaviour, 6, slightly_better, Endpoint::Listener).unwrap_err();
+
+        // Total limit is reached, connection with the newest of equally scored peers is closed.
+        reward(&peer_tracker, good, 1);
+        let newest = PeerId::random();
+        connect(&mut behaviour, 7, newest, Endpoint::Dialer).unwrap_err();
+        reward(&peer_tracker, newest, 7);
+        connect(&mut behaviour, 8, newest, Endpoint::Dialer).unwrap();
+        assert_eq!(
+            poll_closed(&mut behaviour),
+            Some((outbound, ConnectionId::new_unchecked(3)))
+        );
+    }
+
+    #[test]
+    fn unknown_peer_does_not_evict() {
+        let mut behaviour = behaviour(ConnectionLimits {
+            max_inbound: Some(3),
+            ..ConnectionLimits::unlimited()
+        });
+        let peer_tracker = behaviour.peer_tracker.clone();
+
+        // Honest peers without any rewards have slightly negative score because of latency.
+        for id in 0..3 {
+            let peer = PeerId::random();
+            peer_tracker.record_ping_rtt(peer, Duration::from_millis(50));
+            assert!(peer_tracker.score(&peer) < 0.0);
+            connect(&mut behaviour, id, peer, Endpoint::Listener).unwrap();
+        }
+
+        for id in 3..10 {
+            connect(&mut behaviour, id, PeerId::random(), Endpoint::Listener).unwrap_err();
+        }
+
+        assert!(poll_closed(&mut behaviour).is_none());
+    }
+
+    #[test]
+    fn young_connections_not_evicted() {
+        let mut behaviour = behaviour(ConnectionLimits {
+            max_inbound: Some(1),
+            ..ConnectionLimits::unlimited()
+        });
+        behaviour.min_evicted_age = MIN_EVICTED_CONNECTION_AGE;
+        let peer_tracker = behaviour.peer_tracker.clone();
+        let good = PeerId::random();
+        reward(&peer_tracker, good, 10);
+
+        connect(&mut behaviour, 1, PeerId::random(), Endpoint::Listener).unwrap();
+        connect(&mut behaviour, 2, good, Endpoint::Listener).unwrap_err();
+
+        assert!(poll_closed(&mut behaviour).is_none());
+    }
+}
@@ -26,6 +26,7 @@ mod server;
 pub(crate) mod utils;
 
 use crate::executor::timeout;
+use crate::metrics::Metrics;
 use crate::p2p::header_ex::client::HeaderExClientHandler;
 use crate::p2p::header_ex::server::HeaderExServerHandler;
 use crate::p2p::P2pError;
@@ -64,6 +65,7 @@ pub(crate) struct HeaderExConfig<'a, S> {
     pub network_id: &'a str,
     pub peer_tracker: Arc<PeerTracker>,
     pub header_store: Arc<S>,
+    pub metrics: Arc<Metrics>,
 }
 
 /// Representation of all the errors that can occur in `HeaderEx` component.
@@ -109,7 +111,7 @@ where
                 )],
                 request_response::Config::default(),
             ),
-            client_handler: HeaderExClientHandler::new(config.peer_tracker),
+            client_handler: HeaderExClientHandler::new(config.peer_tracker, config.metrics),
             server_handler: HeaderExServerHandler::new(config.header_store),
         }
     }
@@ -512,7 +514,7 @@ where
     Ok(buf)
 }
 
-fn parse_delimiter(mut buf: &[u8]) -> Option<(usize, &[u8])> {
+pub(super) fn parse_delimiter(mut buf: &[u8]) -> Option<(usize, &[u8])> {
     if buf.is_empty() {
         return None;
     }
@@ -15,8 +15,10 @@ use libp2p::PeerId;
 use tokio::sync::oneshot;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, instrument, trace};
+use web_time::Instant;
 
 use crate::executor::yield_now;
+use crate::metrics::{Metrics, OpResult};
 use crate::p2p::header_ex::utils::{HeaderRequestExt, HeaderResponseExt};
 use crate::p2p::header_ex::{HeaderExError, ReqRespBehaviour};
 use crate::p2p::P2pError;
@@ -31,6 +33,7 @@ where
 {
     reqs: HashMap<S::RequestId, State>,
     peer_tracker: Arc<PeerTracker>,
+    metrics: Arc<Metrics>,
     cancellation_token: CancellationToken,
     tasks: FuturesUnordered<BoxFuture<'static, ()>>,
 }
@@ -38,6 +41,7 @@ where
 struct State {
     request: HeaderRequest,
     respond_to: OneshotSender,
+    sent_at: Instant,
 }
 
 /// Oneshot sender that responds with `RequestCancelled` if not used.
@@ -91,10 +95,11 @@ impl<S> HeaderExClientHandler<S>
 where
     S: RequestSender,
 {
-    pub(super) fn new(peer_tracker: Arc<PeerTracker>) -> Self {
+    pub(super) fn new(peer_tracker: Arc<PeerTracker>, metrics: Arc<Metrics>) -> Self {
         HeaderExClientHandler {
             reqs: HashMap::new(),
             peer_tracker,
+            metrics,
             cancellation_token: CancellationToken::new(),
             tasks: FuturesUnordered::new(),
         }
@@ -147,6 +152,7 @@ where
         let state = State {
             request,
             respond_to: OneshotSender::new(respond_to),
+            sent_at: Instant::now(),
         };
 
         self.reqs.insert(req_id, state);
@@ -178,6 +184,7 @@ where
             let state = State {
                 request: request.clone(),
                 respond_to: OneshotSender::new(tx),
+                sent_at: Instant::now(),
             };
 
             self.reqs.insert(req_id, state);
@@ -262,12 +269,28 @@ where
             return;
         };
 
+        // Range requests take longer the more headers are requested, so
+        // only single header requests tell the peer's latency.
+        let latency = (state.request.amount == 1).then(|| state.sent_at.elapsed());
+        let peer_tracker = self.peer_tracker.clone();
+        let metrics = self.metrics.clone();
+
         self.tasks.push(
             async move {
-                let res = decode_and_verify_responses(&state.request, &responses)
-                    .await
-                    .map_err(P2pError::from);
-                state.respond_to.maybe_send(res);
+                let res = decode_and_verify_responses(&state.request, &responses).await;
+
+                // Peer responding with "not found" is a valid response.
+                let valid = matches!(res, Ok(_) | Err(HeaderExError::HeaderNotFound));
+                peer_tracker.record_header_ex_response(peer, latency, valid);
+
+                let result = if valid {
+                    OpResult::Success
+                } else {
+                    OpResult::Invalid
+                };
+                metrics.header_ex_request(result);
+
+                state.respond_to.maybe_send(res.map_err(P2pError::from));
             }
             .boxed(),
         );
@@ -282,6 +305,13 @@ where
     ) {
         debug!("Outbound failure");
 
+        self.peer_tracker.record_header_ex_failure(peer);
+        let result = match error {
+            OutboundFailure::Timeout => OpResult::Timeout,
+            _ => OpResult::Failure,
+        };
+        self.metrics.header_ex_request(result);
+
         if let Some(mut state) = self.reqs.remove(&request_id) {
             state
                 .respond_to
@@ -375,6 +405,7 @@ mod tests {
     use crate::events::EventChannel;
     use crate::executor::sleep;
     use crate::p2p::header_ex::utils::ExtendedHeaderExt;
+    use crate::peer_tracker::DEFAULT_PEER_BAN_DURATION;
     use crate::test_utils::async_test;
     use celestia_proto::p2p::pb::StatusCode;
     use celestia_types::consts::HASH_SIZE;
@@ -393,7 +424,8 @@ mod tests {
     async fn request_height() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler =
+            HeaderExClientHandler::<MockReq>::new(peer_tracker.clone(), Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -408,13 +440,21 @@ mod tests {
         let result = poll_client_and_receiver(&mut handler, rx).await.unwrap();
         assert_eq!(result.len(), 1);
         assert_eq!(result[0], expected_header);
+
+        // Latency of the responding peer is recorded
+        let latencies = peer_tracker
+            .scores()
+            .into_iter()
+            .filter_map(|score| score.header_ex_latency)
+            .count();
+        assert_eq!(latencies, 1);
     }
 
     #[async_test]
     async fn request_hash() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -439,7 +479,8 @@ mod tests {
     async fn request_range() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler =
+            HeaderExClientHandler::<MockReq>::new(peer_tracker.clone(), Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -457,13 +498,19 @@ mod tests {
         let result = poll_client_and_receiver(&mut handler, rx).await.unwrap();
         assert_eq!(result.len(), 3);
         assert_eq!(result, expected_headers);
+
+        // Latency of range requests isn't recorded
+        assert!(peer_tracker
+            .scores()
+            .iter()
+            .all(|score| score.header_ex_latency.is_none()));
     }
 
     #[async_test]
     async fn request_range_responds_with_unsorted_headers() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -492,7 +539,7 @@ mod tests {
     async fn request_range_responds_with_invalid_headaer_in_the_middle() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -520,7 +567,7 @@ mod tests {
     async fn request_range_responds_with_not_found() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -543,7 +590,7 @@ mod tests {
     async fn respond_with_another_height() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -564,7 +611,7 @@ mod tests {
     async fn respond_with_bad_range() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -595,7 +642,7 @@ mod tests {
     async fn respond_with_bad_hash() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -620,7 +667,7 @@ mod tests {
     async fn request_unavailable_heigh() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -643,7 +690,7 @@ mod tests {
     async fn respond_with_invalid_status_code() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -666,7 +713,7 @@ mod tests {
     async fn respond_with_unknown_status_code() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -689,7 +736,7 @@ mod tests {
     async fn request_range_responds_with_smaller_one() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -707,7 +754,7 @@ mod tests {
     async fn request_range_responds_with_bigger_one() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -732,7 +779,7 @@ mod tests {
     async fn respond_with_invalid_header() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -755,7 +802,7 @@ mod tests {
     async fn respond_with_allowed_bad_header() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -784,7 +831,7 @@ mod tests {
     async fn request_height_then_stop() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -807,7 +854,7 @@ mod tests {
     async fn invalid_requests() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         // Zero amount
         let (tx, rx) = oneshot::channel();
@@ -884,7 +931,7 @@ mod tests {
     async fn head_best() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -922,7 +969,7 @@ mod tests {
     async fn head_highest_peers() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -965,7 +1012,7 @@ mod tests {
     async fn head_highest_height() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -991,7 +1038,7 @@ mod tests {
     async fn head_request_responds_with_multiple_headers() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -1025,7 +1072,7 @@ mod tests {
     async fn head_request_responds_with_invalid_headers() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -1052,7 +1099,7 @@ mod tests {
     async fn head_request_responds_only_with_invalid_headers() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -1074,7 +1121,7 @@ mod tests {
     async fn head_request_responds_with_only_failures() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -1093,7 +1140,7 @@ mod tests {
     async fn head_request_with_one_peer() {
         let peer_tracker = peer_tracker_with_n_peers(1);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -1114,7 +1161,7 @@ mod tests {
     async fn head_request_with_no_peers() {
         let peer_tracker = peer_tracker_with_n_peers(0);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, rx) = oneshot::channel();
 
@@ -1130,7 +1177,7 @@ mod tests {
     async fn head_request_then_stop() {
         let peer_tracker = peer_tracker_with_n_peers(15);
         let mut mock_req = MockReq::new();
-        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker);
+        let mut handler = HeaderExClientHandler::<MockReq>::new(peer_tracker, Arc::default());
 
         let (tx, mut rx) = oneshot::channel();
 
@@ -1238,7 +1285,10 @@ mod tests {
 
     fn peer_tracker_with_n_peers(amount: usize) -> Arc<PeerTracker> {
         let event_channel = EventChannel::new();
-        let peers = Arc::new(PeerTracker::new(event_channel.publisher()));
+        let peers = Arc::new(PeerTracker::new(
+            event_channel.publisher(),
+            DEFAULT_PEER_BAN_DURATION,
+        ));
 
         for i in 0..amount {
             let peer = PeerId::random();
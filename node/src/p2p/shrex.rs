@@ -0,0 +1,467 @@
+//! Clients of the `shrex` protocols of celestia-node.
+//!
+//! - `shrex/nd` retrieves all the shares of a namespace from a block, row by row,
+//!   together with their inclusion proofs.
+//! - `shrex/eds` retrieves the original data square of a block, which is streamed
+//!   as a CARv1 file.
+//!
+//! Both protocols are served only by full and bridge nodes, and the data received
+//! has to be verified against the [`DataAvailabilityHeader`] by the caller.
+//!
+//! [`DataAvailabilityHeader`]: celestia_types::DataAvailabilityHeader
+
+use std::io;
+
+use async_trait::async_trait;
+use celestia_proto::share::p2p::shrex::nd::{
+    GetSharesByNamespaceRequest, GetSharesByNamespaceStatusResponse, NamespaceRowResponse,
+    StatusCode,
+};
+use celestia_proto::{EdsRequest, EdsResponse, Status};
+use celestia_types::consts::appconsts::SHARE_SIZE;
+use celestia_types::nmt::{NMT_ID_SIZE, NS_SIZE};
+use cid::CidGeneric;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use libp2p::request_response::{self, Codec, OutboundFailure, ProtocolSupport};
+use libp2p::StreamProtocol;
+use prost::Message;
+use web_time::Duration;
+
+use crate::executor::timeout;
+use crate::p2p::header_ex::parse_delimiter;
+use crate::utils::protocol_id;
+
+/// Size limit of a request in bytes
+const REQUEST_SIZE_LIMIT: usize = 1024;
+/// Time limit on writing a request
+const REQUEST_TIME_LIMIT: Duration = Duration::from_secs(1);
+/// Size limit of a `shrex/nd` response in bytes
+const ND_RESPONSE_SIZE_LIMIT: usize = 16 * 1024 * 1024;
+/// Time limit on reading a `shrex/nd` response
+const ND_RESPONSE_TIME_LIMIT: Duration = Duration::from_secs(20);
+/// Size limit of a `shrex/eds` response in bytes
+const EDS_RESPONSE_SIZE_LIMIT: usize = 64 * 1024 * 1024;
+/// Time limit on reading a `shrex/eds` response
+const EDS_RESPONSE_TIME_LIMIT: Duration = Duration::from_secs(60);
+
+pub(crate) type ShrexNdBehaviour = request_response::Behaviour<ShrexNdCodec>;
+pub(crate) type ShrexNdEvent =
+    request_response::Event<GetSharesByNamespaceRequest, ShrexNdResponse>;
+pub(crate) type ShrexEdsBehaviour = request_response::Behaviour<ShrexEdsCodec>;
+pub(crate) type ShrexEdsEvent = request_response::Event<EdsRequest, ShrexEdsResponse>;
+
+/// Rows of the namespace, as sent by the server.
+pub(crate) type ShrexNdResponse = Result<Vec<NamespaceRowResponse>, ShrexError>;
+/// Shares of the data square, in the order sent by the server.
+pub(crate) type ShrexEdsResponse = Result<Vec<Vec<u8>>, ShrexError>;
+
+/// Representation of all the errors that can occur when using `shrex` protocols.
+#[derive(Debug, thiserror::Error)]
+pub enum ShrexError {
+    /// Data not found.
+    #[error("Data not found")]
+    NotFound,
+
+    /// The request was rejected as invalid.
+    #[error("Invalid request")]
+    InvalidRequest,
+
+    /// The peer failed to serve the request.
+    #[error("Internal error of the peer")]
+    Internal,
+
+    /// The response is invalid.
+    #[error("Invalid response")]
+    InvalidResponse,
+
+    /// Error when handling connection to the server.
+    #[error("Outbound failure: {0}")]
+    OutboundFailure(OutboundFailure),
+}
+
+pub(crate) fn new_nd_behaviour(network_id: &str) -> ShrexNdBehaviour {
+    ShrexNdBehaviour::new(
+        [(
+            protocol_id(network_id, "/shrex/nd/v0.0.3"),
+            ProtocolSupport::Outbound,
+        )],
+        request_response::Config::default().with_request_timeout(ND_RESPONSE_TIME_LIMIT),
+    )
+}
+
+pub(crate) fn new_eds_behaviour(network_id: &str) -> ShrexEdsBehaviour {
+    ShrexEdsBehaviour::new(
+        [(
+            protocol_id(network_id, "/shrex/eds/v0.0.1"),
+            ProtocolSupport::Outbound,
+        )],
+        request_response::Config::default().with_request_timeout(EDS_RESPONSE_TIME_LIMIT),
+    )
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ShrexNdCodec;
+
+#[async_trait]
+impl Codec for ShrexNdCodec {
+    type Protocol = StreamProtocol;
+    type Request = GetSharesByNamespaceRequest;
+    type Response = ShrexNdResponse;
+
+    async fn read_request<T>(&mut self, _: &Self::Protocol, _: &mut T) -> io::Result<Self::Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        Err(server_unsupported())
+    }
+
+    async fn read_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+    ) -> io::Result<Self::Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let data = read_to_end(io, ND_RESPONSE_SIZE_LIMIT, ND_RESPONSE_TIME_LIMIT).await?;
+
+        let Some((status, mut data)) = parse_message::<GetSharesByNamespaceStatusResponse>(&data)
+        else {
+            return Ok(Err(ShrexError::InvalidResponse));
+        };
+
+        match status.status() {
+            StatusCode::Ok => {}
+            StatusCode::NotFound => return Ok(Err(ShrexError::NotFound)),
+            StatusCode::Invalid => return Ok(Err(ShrexError::InvalidRequest)),
+            StatusCode::Internal => return Ok(Err(ShrexError::Internal)),
+        }
+
+        let mut rows = Vec::new();
+
+        // Rows are sent until the stream is closed
+        while !data.is_empty() {
+            let Some((row, rest)) = parse_message::<NamespaceRowResponse>(data) else {
+                return Ok(Err(ShrexError::InvalidResponse));
+            };
+
+            rows.push(row);
+            data = rest;
+        }
+
+        Ok(Ok(rows))
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        req: Self::Request,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_request(io, req).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        _: &mut T,
+        _: Self::Response,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        Err(server_unsupported())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ShrexEdsCodec;
+
+#[async_trait]
+impl Codec for ShrexEdsCodec {
+    type Protocol = StreamProtocol;
+    type Request = EdsRequest;
+    type Response = ShrexEdsResponse;
+
+    async fn read_request<T>(&mut self, _: &Self::Protocol, _: &mut T) -> io::Result<Self::Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        Err(server_unsupported())
+    }
+
+    async fn read_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+    ) -> io::Result<Self::Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let data = read_to_end(io, EDS_RESPONSE_SIZE_LIMIT, EDS_RESPONSE_TIME_LIMIT).await?;
+
+        let Some((status, data)) = parse_message::<EdsResponse>(&data) else {
+            return Ok(Err(ShrexError::InvalidResponse));
+        };
+
+        match status.status() {
+            Status::Ok => {}
+            Status::NotFound => return Ok(Err(ShrexError::NotFound)),
+            Status::Invalid => return Ok(Err(ShrexError::InvalidRequest)),
+            Status::Internal => return Ok(Err(ShrexError::Internal)),
+        }
+
+        Ok(parse_car_shares(data).ok_or(ShrexError::InvalidResponse))
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        req: Self::Request,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_request(io, req).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        _: &mut T,
+        _: Self::Response,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        Err(server_unsupported())
+    }
+}
+
+fn server_unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "shrex server is not supported")
+}
+
+async fn write_request<T, M>(io: &mut T, req: M) -> io::Result<()>
+where
+    T: AsyncWrite + Unpin + Send,
+    M: Message,
+{
+    let mut buf = Vec::with_capacity(REQUEST_SIZE_LIMIT);
+
+    let _ = req.encode_length_delimited(&mut buf);
+
+    timeout(REQUEST_TIME_LIMIT, io.write_all(&buf))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "writing request timed out"))??;
+
+    Ok(())
+}
+
+/// Reads until the end of the stream, failing if it doesn't end within the limits.
+async fn read_to_end<T>(io: &mut T, size_limit: usize, time_limit: Duration) -> io::Result<Vec<u8>>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    let mut buf = Vec::new();
+    // Read one byte over the limit to detect if the response exceeds it.
+    let mut reader = io.take(size_limit as u64 + 1);
+
+    timeout(time_limit, reader.read_to_end(&mut buf))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "reading response timed out"))??;
+
+    if buf.len() > size_limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "response exceeds the size limit",
+        ));
+    }
+
+    Ok(buf)
+}
+
+fn parse_message<M>(buf: &[u8]) -> Option<(M, &[u8])>
+where
+    M: Message + Default,
+{
+    let (len, rest) = parse_delimiter(buf)?;
+
+    if rest.len() < len {
+        return None;
+    }
+
+    let msg = M::decode(&rest[..len]).ok()?;
+
+    Some((msg, &rest[len..]))
+}
+
+/// Parses shares out of the blocks of a CARv1 file.
+///
+/// Each block holds a share prefixed with its namespace, as stored in the NMT leaves.
+fn parse_car_shares(buf: &[u8]) -> Option<Vec<Vec<u8>>> {
+    // Header carries the roots of the square, which are known from the DAH already.
+    let (header_len, mut data) = parse_delimiter(buf)?;
+    data = data.get(header_len..)?;
+
+    let mut shares = Vec::new();
+
+    while !data.is_empty() {
+        let (section_len, rest) = parse_delimiter(data)?;
+        let mut section = rest.get(..section_len)?;
+        data = &rest[section_len..];
+
+        // Reading CID advances the section to the block data.
+        CidGeneric::<NMT_ID_SIZE>::read_bytes(&mut section).ok()?;
+
+        if section.len() != NS_SIZE + SHARE_SIZE {
+            return None;
+        }
+
+        shares.push(section[NS_SIZE..].to_vec());
+    }
+
+    Some(shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::async_test;
+    use celestia_types::nmt::{NMT_CODEC, NMT_MULTIHASH_CODE};
+    use futures::io::Cursor;
+    use libp2p::multihash::Multihash;
+    use prost::encode_length_delimiter;
+
+    fn car_file(shares: &[Vec<u8>]) -> Vec<u8> {
+        let mut car = Vec::new();
+
+        let header = b"roots and version";
+        encode_length_delimiter(header.len(), &mut car).unwrap();
+        car.extend_from_slice(header);
+
+        for share in shares {
+            let mh = Multihash::<NMT_ID_SIZE>::wrap(NMT_MULTIHASH_CODE, &[0; NMT_ID_SIZE]).unwrap();
+            let cid = CidGeneric::<NMT_ID_SIZE>::new_v1(NMT_CODEC, mh).to_bytes();
+
+            encode_length_delimiter(cid.len() + NS_SIZE + share.len(), &mut car).unwrap();
+            car.extend_from_slice(&cid);
+            car.extend_from_slice(&share[..NS_SIZE]);
+            car.extend_from_slice(share);
+        }
+
+        car
+    }
+
+    #[async_test]
+    async fn read_nd_response() {
+        let rows = vec![
+            NamespaceRowResponse {
+                shares: vec![vec![1; SHARE_SIZE], vec![2; SHARE_SIZE]],
+                proof: None,
+            },
+            NamespaceRowResponse {
+                shares: vec![vec![3; SHARE_SIZE]],
+                proof: None,
+            },
+        ];
+
+        let mut data = GetSharesByNamespaceStatusResponse {
+            status: StatusCode::Ok.into(),
+        }
+        .encode_length_delimited_to_vec();
+
+        for row in &rows {
+            data.extend(row.encode_length_delimited_to_vec());
+        }
+
+        let protocol = StreamProtocol::new("/foo/shrex/nd/v0.0.3");
+        let received = ShrexNdCodec
+            .read_response(&protocol, &mut Cursor::new(data))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(received, rows);
+    }
+
+    #[async_test]
+    async fn read_nd_response_not_found() {
+        let data = GetSharesByNamespaceStatusResponse {
+            status: StatusCode::NotFound.into(),
+        }
+        .encode_length_delimited_to_vec();
+
+        let protocol = StreamProtocol::new("/foo/shrex/nd/v0.0.3");
+        let received = ShrexNdCodec
+            .read_response(&protocol, &mut Cursor::new(data))
+            .await
+            .unwrap();
+
+        assert!(matches!(received, Err(ShrexError::NotFound)));
+    }
+
+    #[async_test]
+    async fn read_nd_response_truncated() {
+        let mut data = GetSharesByNamespaceStatusResponse {
+            status: StatusCode::Ok.into(),
+        }
+        .encode_length_delimited_to_vec();
+
+        let row = NamespaceRowResponse {
+            shares: vec![vec![1; SHARE_SIZE]],
+            proof: None,
+        }
+        .encode_length_delimited_to_vec();
+        data.extend_from_slice(&row[..row.len() - 1]);
+
+        let protocol = StreamProtocol::new("/foo/shrex/nd/v0.0.3");
+        let received = ShrexNdCodec
+            .read_response(&protocol, &mut Cursor::new(data))
+            .await
+            .unwrap();
+
+        assert!(matches!(received, Err(ShrexError::InvalidResponse)));
+    }
+
+    #[async_test]
+    async fn read_eds_response() {
+        let shares: Vec<_> = (0..4u8).map(|i| vec![i; SHARE_SIZE]).collect();
+
+        let mut data = EdsResponse {
+            status: Status::Ok.into(),
+        }
+        .encode_length_delimited_to_vec();
+        data.extend(car_file(&shares));
+
+        let protocol = StreamProtocol::new("/foo/shrex/eds/v0.0.1");
+        let received = ShrexEdsCodec
+            .read_response(&protocol, &mut Cursor::new(data))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(received, shares);
+    }
+
+    #[async_test]
+    async fn read_eds_response_invalid_block() {
+        let mut data = EdsResponse {
+            status: Status::Ok.into(),
+        }
+        .encode_length_delimited_to_vec();
+        data.extend(car_file(&[vec![0; SHARE_SIZE - 1]]));
+
+        let protocol = StreamProtocol::new("/foo/shrex/eds/v0.0.1");
+        let received = ShrexEdsCodec
+            .read_response(&protocol, &mut Cursor::new(data))
+            .await
+            .unwrap();
+
+        assert!(matches!(received, Err(ShrexError::InvalidResponse)));
+    }
+}
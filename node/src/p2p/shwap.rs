@@ -1,8 +1,11 @@
+use std::convert::Infallible;
 use std::sync::Arc;
 
 use beetswap::multihasher::{Multihasher, MultihasherError};
 use blockstore::block::CidError;
+use blockstore::Blockstore;
 use celestia_tendermint_proto::Protobuf;
+use celestia_types::consts::appconsts::SHARE_SIZE;
 use celestia_types::namespaced_data::{
     NamespacedData, NamespacedDataId, NAMESPACED_DATA_ID_MULTIHASH_CODE,
 };
@@ -11,6 +14,7 @@ use celestia_types::row::{Row, RowId, ROW_ID_MULTIHASH_CODE};
 use celestia_types::sample::{Sample, SampleId, SAMPLE_ID_MULTIHASH_CODE};
 use cid::{Cid, CidGeneric};
 use libp2p::multihash::Multihash;
+use tracing::debug;
 
 use crate::p2p::{P2pError, Result, MAX_MH_SIZE};
 use crate::store::Store;
@@ -107,6 +111,214 @@ where
     }
 }
 
+/// Blockstore which derives Shwap types from the stored rows.
+///
+/// Only the rows of the original data square are stored. Requests for [`Sample`]s
+/// and [`NamespacedData`] that are not in the underlying blockstore are answered
+/// from the [`Row`] they belong to. Parity rows are reconstructed from the stored
+/// ones, if all of them are available, encoding only the requested one.
+pub(super) struct ShwapBlockstore<B>
+where
+    B: Blockstore,
+{
+    blockstore: Arc<B>,
+}
+
+impl<B> ShwapBlockstore<B>
+where
+    B: Blockstore,
+{
+    pub(super) fn new(blockstore: Arc<B>) -> Self {
+        ShwapBlockstore { blockstore }
+    }
+
+    async fn get_stored_row(
+        &self,
+        row_index: u16,
+        block_height: u64,
+    ) -> blockstore::Result<Option<Row>> {
+        let Ok(cid) = row_cid(row_index, block_height) else {
+            return Ok(None);
+        };
+
+        let Some(data) = self.blockstore.get(&cid).await? else {
+            return Ok(None);
+        };
+
+        match Row::decode(&data[..]) {
+            Ok(row) => Ok(Some(row)),
+            Err(e) => {
+                debug!("Stored row {cid} could not be decoded: {e}");
+                Ok(None)
+            }
+        }
+    }
+
+    async fn get_row(&self, row_index: u16, block_height: u64) -> blockstore::Result<Option<Row>> {
+        if let Some(row) = self.get_stored_row(row_index, block_height).await? {
+            return Ok(Some(row));
+        }
+
+        // Width of the square is known only from the stored rows.
+        let Some(first_row) = self.get_stored_row(0, block_height).await? else {
+            return Ok(None);
+        };
+
+        let square_width = first_row.shares.len();
+        let ods_width = square_width / 2;
+
+        // Missing row of the original data square can't be reconstructed.
+        if usize::from(row_index) < ods_width || usize::from(row_index) >= square_width {
+            return Ok(None);
+        }
+
+        let mut ods_rows = Vec::with_capacity(ods_width);
+        ods_rows.push(first_row.shares);
+
+        for index in 1..ods_width {
+            // `ods_width` is a half of `u16` square width
+            let index = index as u16;
+
+            let Some(row) = self.get_stored_row(index, block_height).await? else {
+                return Ok(None);
+            };
+
+            if row.shares.len() != square_width {
+                return Ok(None);
+            }
+
+            ods_rows.push(row.shares);
+        }
+
+        let Ok(id) = RowId::new(row_index, block_height) else {
+            return Ok(None);
+        };
+
+        match encode_parity_row(&ods_rows, usize::from(row_index)) {
+            Ok(shares) => Ok(Some(Row { id, shares })),
+            Err(e) => {
+                debug!("Reconstructing row {row_index} of block {block_height} failed: {e}");
+                Ok(None)
+            }
+        }
+    }
+
+    async fn derive<const S: usize>(
+        &self,
+        cid: &CidGeneric<S>,
+    ) -> blockstore::Result<Option<Vec<u8>>> {
+        let derived = match cid.hash().code() {
+            ROW_ID_MULTIHASH_CODE => {
+                let Ok(id) = RowId::try_from(*cid) else {
+                    return Ok(None);
+                };
+
+                let Some(row) = self.get_row(id.index(), id.block_height()).await? else {
+                    return Ok(None);
+                };
+
+                let data: Result<_, Infallible> = row.encode_vec();
+                Ok(data.unwrap())
+            }
+            SAMPLE_ID_MULTIHASH_CODE => {
+                let Ok(id) = SampleId::try_from(*cid) else {
+                    return Ok(None);
+                };
+
+                let Some(row) = self.get_row(id.row_index(), id.block_height()).await? else {
+                    return Ok(None);
+                };
+
+                row.sample(id.column_index()).map(|sample| {
+                    // until unwrap_infallible is stabilised, make sure Result is Infallible manually
+                    let data: Result<_, Infallible> = sample.encode_vec();
+                    data.unwrap()
+                })
+            }
+            NAMESPACED_DATA_ID_MULTIHASH_CODE => {
+                let Ok(id) = NamespacedDataId::try_from(*cid) else {
+                    return Ok(None);
+                };
+
+                let Some(row) = self.get_row(id.row_index(), id.block_height()).await? else {
+                    return Ok(None);
+                };
+
+                row.namespaced_data(id.namespace()).map(|ns_data| {
+                    let data: Result<_, Infallible> = ns_data.encode_vec();
+                    data.unwrap()
+                })
+            }
+            _ => return Ok(None),
+        };
+
+        match derived {
+            Ok(data) => Ok(Some(data)),
+            Err(e) => {
+                debug!("Deriving {cid} from stored row failed: {e}");
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Encodes a parity row of the extended data square from the upper half of it.
+///
+/// Only the first half of the row is encoded from the columns of the original data
+/// square, the rest is the parity of that half. This avoids extending the whole square.
+fn encode_parity_row(
+    upper_rows: &[Vec<Vec<u8>>],
+    row_index: usize,
+) -> Result<Vec<Vec<u8>>, leopard_codec::LeopardError> {
+    let ods_width = upper_rows.len();
+    let square_width = ods_width * 2;
+    let mut row = Vec::with_capacity(square_width);
+
+    for col in 0..ods_width {
+        let mut column: Vec<_> = upper_rows.iter().map(|row| row[col].clone()).collect();
+        column.resize(square_width, vec![0; SHARE_SIZE]);
+        leopard_codec::encode(&mut column, ods_width)?;
+        row.push(column.swap_remove(row_index));
+    }
+
+    row.resize(square_width, vec![0; SHARE_SIZE]);
+    leopard_codec::encode(&mut row, ods_width)?;
+
+    Ok(row)
+}
+
+impl<B> Blockstore for ShwapBlockstore<B>
+where
+    B: Blockstore,
+{
+    async fn get<const S: usize>(
+        &self,
+        cid: &CidGeneric<S>,
+    ) -> blockstore::Result<Option<Vec<u8>>> {
+        match self.blockstore.get(cid).await? {
+            Some(data) => Ok(Some(data)),
+            None => self.derive(cid).await,
+        }
+    }
+
+    async fn put_keyed<const S: usize>(
+        &self,
+        cid: &CidGeneric<S>,
+        data: &[u8],
+    ) -> blockstore::Result<()> {
+        self.blockstore.put_keyed(cid, data).await
+    }
+
+    async fn remove<const S: usize>(&self, cid: &CidGeneric<S>) -> blockstore::Result<()> {
+        self.blockstore.remove(cid).await
+    }
+
+    async fn close(self) -> blockstore::Result<()> {
+        // Underlying blockstore is shared with the node, which closes it.
+        Ok(())
+    }
+}
+
 pub(crate) fn row_cid(row_index: u16, block_height: u64) -> Result<Cid> {
     let row_id = RowId::new(row_index, block_height).map_err(P2pError::Cid)?;
     convert_cid(&row_id.into())
@@ -136,8 +348,10 @@ pub(crate) fn convert_cid<const S: usize>(cid: &CidGeneric<S>) -> Result<Cid> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blockstore::InMemoryBlockstore;
     use crate::store::InMemoryStore;
     use crate::test_utils::async_test;
+    use celestia_types::nmt::NS_SIZE;
     use celestia_types::test_utils::{generate_eds, ExtendedHeaderGenerator};
     use celestia_types::{AxisType, DataAvailabilityHeader};
 
@@ -165,4 +379,92 @@ mod tests {
 
         assert_eq!(hash, *cid.hash());
     }
+
+    #[async_test]
+    async fn derive_from_stored_row() {
+        let blockstore = Arc::new(InMemoryBlockstore::new());
+        let shwap_blockstore = ShwapBlockstore::new(blockstore.clone());
+
+        let eds = generate_eds(4);
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let namespace = Namespace::from_raw(&eds.share(0, 0).unwrap()[..NS_SIZE]).unwrap();
+
+        let sample_id = sample_cid(1, 3, 1).unwrap();
+        let data_id = namespaced_data_cid(namespace, 1, 1).unwrap();
+
+        assert!(shwap_blockstore.get(&sample_id).await.unwrap().is_none());
+        assert!(shwap_blockstore.get(&data_id).await.unwrap().is_none());
+
+        let row = Row::new(1, &eds, 1).unwrap();
+        blockstore
+            .put_keyed(&row_cid(1, 1).unwrap(), &row.encode_vec().unwrap())
+            .await
+            .unwrap();
+
+        let sample = shwap_blockstore.get(&sample_id).await.unwrap().unwrap();
+        let sample = Sample::decode(&sample[..]).unwrap();
+        assert_eq!(sample.share, eds.share(1, 3).unwrap());
+        sample.verify(&dah).unwrap();
+
+        let data = shwap_blockstore.get(&data_id).await.unwrap().unwrap();
+        let data = NamespacedData::decode(&data[..]).unwrap();
+        assert_eq!(data.shares, eds.row(1).unwrap()[..2]);
+        data.verify(&dah).unwrap();
+
+        // Parity rows can't be reconstructed until all the original rows are stored
+        let parity_sample_id = sample_cid(2, 3, 1).unwrap();
+        assert!(shwap_blockstore
+            .get(&parity_sample_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let row = Row::new(0, &eds, 1).unwrap();
+        blockstore
+            .put_keyed(&row_cid(0, 1).unwrap(), &row.encode_vec().unwrap())
+            .await
+            .unwrap();
+
+        let sample = shwap_blockstore
+            .get(&parity_sample_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let sample = Sample::decode(&sample[..]).unwrap();
+        assert_eq!(sample.share, eds.share(2, 3).unwrap());
+        sample.verify(&dah).unwrap();
+
+        let row = shwap_blockstore
+            .get(&row_cid(3, 1).unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        let row = Row::decode(&row[..]).unwrap();
+        assert_eq!(row.shares, eds.row(3).unwrap());
+        row.verify(&dah).unwrap();
+
+        // Parity rows don't hold any namespaced data
+        let parity_data_id = namespaced_data_cid(namespace, 2, 1).unwrap();
+        assert!(shwap_blockstore
+            .get(&parity_data_id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn encode_parity_rows() {
+        let eds = generate_eds(8);
+        let square_width = usize::from(eds.square_width());
+        let upper_rows: Vec<_> = (0..square_width / 2)
+            .map(|row| eds.row(row as u16).unwrap())
+            .collect();
+
+        for row in square_width / 2..square_width {
+            assert_eq!(
+                encode_parity_row(&upper_rows, row).unwrap(),
+                eds.row(row as u16).unwrap()
+            );
+        }
+    }
 }
@@ -1,6 +1,7 @@
 //! Primitives related to tracking the state of peers in the network.
 
 use std::borrow::Borrow;
+use std::time::Duration;
 
 use dashmap::mapref::entry::Entry;
 use dashmap::mapref::one::RefMut;
@@ -9,16 +10,45 @@ use libp2p::{swarm::ConnectionId, Multiaddr, PeerId};
 use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
-use tokio::sync::watch;
+use tokio::sync::{watch, Notify};
+use web_time::Instant;
 
 use crate::events::{EventPublisher, NodeEvent};
 
+/// Default duration for which misbehaving peers are banned.
+pub const DEFAULT_PEER_BAN_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// Bounds of the peer's reputation.
+const MIN_REPUTATION: f64 = -100.0;
+const MAX_REPUTATION: f64 = 100.0;
+/// Peers with reputation at or below this threshold are banned.
+const BAN_REPUTATION_THRESHOLD: f64 = -50.0;
+
+const HEADER_EX_VALID_REWARD: f64 = 1.0;
+const HEADER_EX_INVALID_PENALTY: f64 = 20.0;
+const HEADER_EX_FAILURE_PENALTY: f64 = 5.0;
+const INVALID_GOSSIP_PENALTY: f64 = 25.0;
+const SHREX_INVALID_PENALTY: f64 = 20.0;
+const PING_FAILURE_PENALTY: f64 = 5.0;
+/// Beetswap doesn't report which peer failed to serve a block, so all the peers
+/// connected during a timed out query share the blame and the penalty is kept low.
+const BITSWAP_TIMEOUT_PENALTY: f64 = 1.0;
+
+/// Score subtracted for each second of the peer's latency.
+const LATENCY_PENALTY_PER_SEC: f64 = 10.0;
+/// Weight of the newest latency measurement in the moving average.
+const LATENCY_SMOOTHING: f64 = 0.2;
+/// Best peer is randomly chosen from this many highest scored peers.
+const BEST_PEER_CANDIDATES: usize = 3;
+
 /// Keeps track various information about peers.
 #[derive(Debug)]
 pub struct PeerTracker {
     peers: DashMap<PeerId, PeerInfo>,
     info_tx: watch::Sender<PeerTrackerInfo>,
     event_pub: EventPublisher,
+    ban_duration: Duration,
+    bans_notify: Notify,
 }
 
 /// Statistics of the connected peers
@@ -30,12 +60,35 @@ pub struct PeerTrackerInfo {
     pub num_connected_trusted_peers: u64,
 }
 
+/// Score of a peer, based on its past behaviour.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerScore {
+    /// The ID of the peer.
+    pub peer_id: PeerId,
+    /// Score used for peer selection, reputation lowered by the peer's latency.
+    pub score: f64,
+    /// Reputation collected from the validity of peer's responses and messages.
+    pub reputation: f64,
+    /// Moving average of header-ex response times of single header requests.
+    pub header_ex_latency: Option<Duration>,
+    /// Moving average of ping round trip times.
+    pub ping_rtt: Option<Duration>,
+    /// Whether peer is trusted. Trusted peers are never banned.
+    pub trusted: bool,
+    /// Whether peer is currently banned.
+    pub banned: bool,
+}
+
 #[derive(Debug)]
 struct PeerInfo {
     state: PeerState,
     addrs: SmallVec<[Multiaddr; 4]>,
     connections: SmallVec<[ConnectionId; 1]>,
     trusted: bool,
+    reputation: f64,
+    header_ex_latency: Option<Duration>,
+    ping_rtt: Option<Duration>,
+    banned_until: Option<Instant>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -46,18 +99,63 @@ enum PeerState {
 }
 
 impl PeerInfo {
+    fn new() -> PeerInfo {
+        PeerInfo {
+            state: PeerState::Discovered,
+            addrs: SmallVec::new(),
+            connections: SmallVec::new(),
+            trusted: false,
+            reputation: 0.0,
+            header_ex_latency: None,
+            ping_rtt: None,
+            banned_until: None,
+        }
+    }
+
     fn is_connected(&self) -> bool {
         matches!(self.state, PeerState::Connected)
     }
+
+    fn is_banned(&self) -> bool {
+        self.banned_until
+            .is_some_and(|banned_until| banned_until > Instant::now())
+    }
+
+    /// Connected peers that are not banned can be used for requests.
+    fn is_usable(&self) -> bool {
+        self.is_connected() && !self.is_banned()
+    }
+
+    fn score(&self) -> f64 {
+        let latency =
+            self.header_ex_latency.unwrap_or_default() + self.ping_rtt.unwrap_or_default();
+        self.reputation - latency.as_secs_f64() * LATENCY_PENALTY_PER_SEC
+    }
+
+    fn to_peer_score(&self, peer_id: PeerId) -> PeerScore {
+        PeerScore {
+            peer_id,
+            score: self.score(),
+            reputation: self.reputation,
+            header_ex_latency: self.header_ex_latency,
+            ping_rtt: self.ping_rtt,
+            trusted: self.trusted,
+            banned: self.is_banned(),
+        }
+    }
 }
 
 impl PeerTracker {
     /// Constructs an empty PeerTracker.
-    pub fn new(event_pub: EventPublisher) -> Self {
+    ///
+    /// Peers that misbehave are banned for `ban_duration`.
+    pub fn new(event_pub: EventPublisher, ban_duration: Duration) -> Self {
         PeerTracker {
             peers: DashMap::new(),
             info_tx: watch::channel(PeerTrackerInfo::default()).0,
             event_pub,
+            ban_duration,
+            bans_notify: Notify::new(),
         }
     }
 
@@ -77,12 +175,7 @@ impl PeerTracker {
     pub fn set_maybe_discovered(&self, peer: PeerId) -> bool {
         match self.peers.entry(peer) {
             Entry::Vacant(entry) => {
-                entry.insert(PeerInfo {
-                    state: PeerState::Discovered,
-                    addrs: SmallVec::new(),
-                    connections: SmallVec::new(),
-                    trusted: false,
-                });
+                entry.insert(PeerInfo::new());
                 true
             }
             Entry::Occupied(_) => false,
@@ -93,12 +186,7 @@ impl PeerTracker {
     ///
     /// If peer is not found it is added as `PeerState::Discovered`.
     fn get(&self, peer: PeerId) -> RefMut<PeerId, PeerInfo> {
-        self.peers.entry(peer).or_insert_with(|| PeerInfo {
-            state: PeerState::Discovered,
-            addrs: SmallVec::new(),
-            connections: SmallVec::new(),
-            trusted: false,
-        })
+        self.peers.entry(peer).or_insert_with(PeerInfo::new)
     }
 
     /// Add an address for a peer.
@@ -134,6 +222,11 @@ impl PeerTracker {
 
         peer_info.trusted = is_trusted;
 
+        if is_trusted {
+            // Trusted peers are never banned.
+            peer_info.banned_until = None;
+        }
+
         // If peer was already connected, then `num_connected_trusted_peers`
         // needs to be adjusted based on the new information.
         if peer_info.is_connected() {
@@ -242,46 +335,183 @@ impl PeerTracker {
     }
 
     /// Returns one of the best peers.
+    ///
+    /// Peer is chosen randomly from the few highest scored ones, so that
+    /// requests are spread between them.
     pub fn best_peer(&self) -> Option<PeerId> {
-        const MAX_PEER_SAMPLE: usize = 128;
+        let mut peers = self.best_n_peers(BEST_PEER_CANDIDATES);
+        peers.shuffle(&mut rand::thread_rng());
+        peers.first().copied()
+    }
 
-        // TODO: Implement peer score and return the best.
+    /// Returns up to N amount of best peers, ordered by their score.
+    pub fn best_n_peers(&self, limit: usize) -> Vec<PeerId> {
         let mut peers = self
             .peers
             .iter()
-            .filter(|pair| pair.value().is_connected())
-            .take(MAX_PEER_SAMPLE)
-            .map(|pair| pair.key().to_owned())
-            .collect::<SmallVec<[_; MAX_PEER_SAMPLE]>>();
+            .filter(|pair| pair.value().is_usable())
+            .map(|pair| (pair.key().to_owned(), pair.value().score()))
+            // collect instead of returning an iter to not block the dashmap
+            .collect::<Vec<_>>();
 
-        peers.shuffle(&mut rand::thread_rng());
+        peers.sort_unstable_by(|(_, a), (_, b)| b.total_cmp(a));
 
-        peers.first().copied()
+        peers
+            .into_iter()
+            .take(limit)
+            .map(|(peer, _)| peer)
+            .collect()
     }
 
-    /// Returns up to N amount of best peers.
-    #[allow(dead_code)]
-    pub fn best_n_peers(&self, limit: usize) -> Vec<PeerId> {
-        // TODO: Implement peer score and return the best N peers.
+    /// Returns up to N amount of trusted peers.
+    pub fn trusted_n_peers(&self, limit: usize) -> Vec<PeerId> {
         self.peers
             .iter()
-            .filter(|pair| pair.value().is_connected())
+            .filter(|pair| pair.value().is_usable() && pair.value().trusted)
             .take(limit)
             .map(|pair| pair.key().to_owned())
             // collect instead of returning an iter to not block the dashmap
             .collect()
     }
 
-    /// Returns up to N amount of trusted peers.
-    pub fn trusted_n_peers(&self, limit: usize) -> Vec<PeerId> {
+    /// Returns true if peer is currently banned.
+    pub fn is_banned(&self, peer: &PeerId) -> bool {
+        self.peers
+            .get(peer)
+            .is_some_and(|peer_info| peer_info.is_banned())
+    }
+
+    /// Returns true if peer is trusted.
+    pub fn is_trusted(&self, peer: &PeerId) -> bool {
+        self.peers
+            .get(peer)
+            .is_some_and(|peer_info| peer_info.trusted)
+    }
+
+    /// Returns the score of the peer. Unknown peers have a neutral score.
+    pub fn score(&self, peer: &PeerId) -> f64 {
+        self.peers
+            .get(peer)
+            .map_or(0.0, |peer_info| peer_info.score())
+    }
+
+    /// Returns the reputation of the peer. Unknown peers have a neutral reputation.
+    pub fn reputation(&self, peer: &PeerId) -> f64 {
+        self.peers
+            .get(peer)
+            .map_or(0.0, |peer_info| peer_info.reputation)
+    }
+
+    /// Returns connected peers that are banned and need to be disconnected.
+    pub fn connected_banned_peers(&self) -> Vec<PeerId> {
         self.peers
             .iter()
-            .filter(|pair| pair.value().is_connected() && pair.value().trusted)
-            .take(limit)
+            .filter(|pair| pair.value().is_connected() && pair.value().is_banned())
             .map(|pair| pair.key().to_owned())
-            // collect instead of returning an iter to not block the dashmap
             .collect()
     }
+
+    /// Waits until a peer gets banned.
+    pub async fn banned(&self) {
+        self.bans_notify.notified().await;
+    }
+
+    /// Returns the scores of the connected or banned peers.
+    pub fn scores(&self) -> Vec<PeerScore> {
+        self.peers
+            .iter()
+            .filter(|pair| pair.value().is_connected() || pair.value().is_banned())
+            .map(|pair| pair.value().to_peer_score(pair.key().to_owned()))
+            .collect()
+    }
+
+    /// Records a header-ex response of the peer.
+    ///
+    /// `latency` should be given only for single header requests, as the response
+    /// time of range requests depends mostly on the amount of requested headers.
+    pub fn record_header_ex_response(&self, peer: PeerId, latency: Option<Duration>, valid: bool) {
+        let mut peer_info = self.get(peer);
+
+        if let Some(latency) = latency {
+            update_latency(&mut peer_info.header_ex_latency, latency);
+        }
+
+        if valid {
+            self.adjust_reputation(peer, peer_info, HEADER_EX_VALID_REWARD);
+        } else {
+            self.adjust_reputation(peer, peer_info, -HEADER_EX_INVALID_PENALTY);
+        }
+    }
+
+    /// Records a failed header-ex request to the peer.
+    pub fn record_header_ex_failure(&self, peer: PeerId) {
+        let peer_info = self.get(peer);
+        self.adjust_reputation(peer, peer_info, -HEADER_EX_FAILURE_PENALTY);
+    }
+
+    /// Records a shrex response of the peer which failed the verification.
+    pub fn record_invalid_shrex_response(&self, peer: PeerId) {
+        let peer_info = self.get(peer);
+        self.adjust_reputation(peer, peer_info, -SHREX_INVALID_PENALTY);
+    }
+
+    /// Records a bitswap query which timed out while the peer was connected.
+    pub fn record_bitswap_timeout(&self, peer: PeerId) {
+        let peer_info = self.get(peer);
+        self.adjust_reputation(peer, peer_info, -BITSWAP_TIMEOUT_PENALTY);
+    }
+
+    /// Records an invalid gossipsub message propagated by the peer.
+    pub fn record_invalid_gossip(&self, peer: PeerId) {
+        let peer_info = self.get(peer);
+        self.adjust_reputation(peer, peer_info, -INVALID_GOSSIP_PENALTY);
+    }
+
+    /// Records a ping round trip time of the peer.
+    pub fn record_ping_rtt(&self, peer: PeerId, rtt: Duration) {
+        let mut peer_info = self.get(peer);
+        update_latency(&mut peer_info.ping_rtt, rtt);
+    }
+
+    /// Records a failed ping of the peer.
+    pub fn record_ping_failure(&self, peer: PeerId) {
+        let peer_info = self.get(peer);
+        self.adjust_reputation(peer, peer_info, -PING_FAILURE_PENALTY);
+    }
+
+    /// Adjusts the reputation of the peer and bans it if it drops too low.
+    fn adjust_reputation(&self, peer: PeerId, mut peer_info: RefMut<PeerId, PeerInfo>, by: f64) {
+        peer_info.reputation = (peer_info.reputation + by).clamp(MIN_REPUTATION, MAX_REPUTATION);
+
+        if peer_info.reputation > BAN_REPUTATION_THRESHOLD
+            || peer_info.trusted
+            || peer_info.is_banned()
+        {
+            return;
+        }
+
+        // Peer starts from a clean state after the ban is lifted.
+        peer_info.reputation = 0.0;
+        peer_info.banned_until = Some(Instant::now() + self.ban_duration);
+        // Release the dashmap lock before notifying.
+        drop(peer_info);
+
+        self.event_pub.send(NodeEvent::PeerBanned {
+            id: peer,
+            duration: self.ban_duration,
+        });
+        self.bans_notify.notify_one();
+    }
+}
+
+/// Updates the moving average of the latency with a new measurement.
+fn update_latency(average: &mut Option<Duration>, latency: Duration) {
+    *average = Some(match average {
+        Some(average) => {
+            average.mul_f64(1.0 - LATENCY_SMOOTHING) + latency.mul_f64(LATENCY_SMOOTHING)
+        }
+        None => latency,
+    });
 }
 
 fn increment_connected_peers(info_tx: &watch::Sender<PeerTrackerInfo>, trusted: bool) {
@@ -313,7 +543,7 @@ mod tests {
     #[test]
     fn trust_before_connect() {
         let event_channel = EventChannel::new();
-        let tracker = PeerTracker::new(event_channel.publisher());
+        let tracker = PeerTracker::new(event_channel.publisher(), DEFAULT_PEER_BAN_DURATION);
         let mut watcher = tracker.info_watcher();
         let peer = PeerId::random();
 
@@ -332,7 +562,7 @@ mod tests {
     #[test]
     fn trust_after_connect() {
         let event_channel = EventChannel::new();
-        let tracker = PeerTracker::new(event_channel.publisher());
+        let tracker = PeerTracker::new(event_channel.publisher(), DEFAULT_PEER_BAN_DURATION);
         let mut watcher = tracker.info_watcher();
         let peer = PeerId::random();
 
@@ -354,7 +584,7 @@ mod tests {
     #[test]
     fn untrust_after_connect() {
         let event_channel = EventChannel::new();
-        let tracker = PeerTracker::new(event_channel.publisher());
+        let tracker = PeerTracker::new(event_channel.publisher(), DEFAULT_PEER_BAN_DURATION);
         let mut watcher = tracker.info_watcher();
         let peer = PeerId::random();
 
@@ -375,4 +605,116 @@ mod tests {
         assert_eq!(info.num_connected_peers, 1);
         assert_eq!(info.num_connected_trusted_peers, 0);
     }
+
+    #[test]
+    fn best_peers_ordered_by_score() {
+        let event_channel = EventChannel::new();
+        let tracker = PeerTracker::new(event_channel.publisher(), DEFAULT_PEER_BAN_DURATION);
+        let good = PeerId::random();
+        let slow = PeerId::random();
+        let bad = PeerId::random();
+
+        for (i, peer) in [good, slow, bad].into_iter().enumerate() {
+            tracker.set_connected(peer, ConnectionId::new_unchecked(i), None);
+        }
+
+        let latency = Duration::from_millis(100);
+        tracker.record_header_ex_response(good, Some(latency), true);
+        tracker.record_header_ex_response(slow, Some(Duration::from_secs(2)), true);
+        tracker.record_header_ex_response(bad, Some(latency), false);
+        // Latency of range requests isn't taken into account
+        tracker.record_header_ex_response(good, None, true);
+
+        assert_eq!(tracker.best_n_peers(3), vec![good, slow, bad]);
+        assert_eq!(tracker.best_n_peers(1), vec![good]);
+
+        let scores = tracker.scores();
+        let good_score = scores.iter().find(|s| s.peer_id == good).unwrap();
+        assert_eq!(good_score.reputation, 2.0 * HEADER_EX_VALID_REWARD);
+        assert_eq!(good_score.header_ex_latency, Some(latency));
+        assert!(!good_score.banned);
+    }
+
+    #[test]
+    fn misbehaving_peer_banned() {
+        let event_channel = EventChannel::new();
+        let mut event_sub = event_channel.subscribe();
+        let tracker = PeerTracker::new(event_channel.publisher(), DEFAULT_PEER_BAN_DURATION);
+        let peer = PeerId::random();
+
+        tracker.set_connected(peer, ConnectionId::new_unchecked(1), None);
+        // Skip `PeerConnected` event
+        event_sub.try_recv().unwrap();
+
+        tracker.record_invalid_gossip(peer);
+        assert!(!tracker.is_banned(&peer));
+
+        tracker.record_invalid_gossip(peer);
+        assert!(tracker.is_banned(&peer));
+        assert_eq!(tracker.connected_banned_peers(), vec![peer]);
+        assert!(tracker.best_peer().is_none());
+
+        assert!(matches!(
+            event_sub.try_recv().unwrap().event,
+            NodeEvent::PeerBanned { id, duration } if id == peer && duration == DEFAULT_PEER_BAN_DURATION
+        ));
+
+        let scores = tracker.scores();
+        assert_eq!(scores.len(), 1);
+        assert!(scores[0].banned);
+    }
+
+    #[test]
+    fn bitswap_timeouts_lower_reputation() {
+        let event_channel = EventChannel::new();
+        let tracker = PeerTracker::new(event_channel.publisher(), DEFAULT_PEER_BAN_DURATION);
+        let peer = PeerId::random();
+
+        tracker.set_connected(peer, ConnectionId::new_unchecked(1), None);
+
+        tracker.record_bitswap_timeout(peer);
+        assert_eq!(tracker.reputation(&peer), -BITSWAP_TIMEOUT_PENALTY);
+        assert!(!tracker.is_banned(&peer));
+
+        let timeouts_to_ban = (-BAN_REPUTATION_THRESHOLD / BITSWAP_TIMEOUT_PENALTY) as usize;
+        for _ in 1..timeouts_to_ban {
+            tracker.record_bitswap_timeout(peer);
+        }
+        assert!(tracker.is_banned(&peer));
+        assert_eq!(tracker.connected_banned_peers(), vec![peer]);
+    }
+
+    #[test]
+    fn ban_expires() {
+        let event_channel = EventChannel::new();
+        let tracker = PeerTracker::new(event_channel.publisher(), Duration::ZERO);
+        let peer = PeerId::random();
+
+        tracker.set_connected(peer, ConnectionId::new_unchecked(1), None);
+
+        for _ in 0..3 {
+            tracker.record_invalid_gossip(peer);
+        }
+
+        assert!(!tracker.is_banned(&peer));
+        assert_eq!(tracker.best_peer(), Some(peer));
+    }
+
+    #[test]
+    fn trusted_peer_never_banned() {
+        let event_channel = EventChannel::new();
+        let tracker = PeerTracker::new(event_channel.publisher(), DEFAULT_PEER_BAN_DURATION);
+        let peer = PeerId::random();
+
+        tracker.set_trusted(peer, true);
+        tracker.set_connected(peer, ConnectionId::new_unchecked(1), None);
+
+        for _ in 0..10 {
+            tracker.record_header_ex_failure(peer);
+            tracker.record_invalid_gossip(peer);
+        }
+
+        assert!(!tracker.is_banned(&peer));
+        assert_eq!(tracker.trusted_n_peers(1), vec![peer]);
+    }
 }
@@ -10,12 +10,18 @@ use tracing::{debug, error, warn};
 
 use crate::events::{EventPublisher, NodeEvent};
 use crate::executor::{sleep, spawn, JoinHandle};
+use crate::metrics::Metrics;
 use crate::p2p::P2pError;
 use crate::store::{Store, StoreError};
-use crate::syncer::SYNCING_WINDOW;
-
-// pruning window is 1 hour behind syncing window
-const PRUNING_WINDOW: Duration = SYNCING_WINDOW.saturating_add(Duration::from_secs(60 * 60));
+use crate::syncer::DEFAULT_SYNCING_WINDOW;
+
+/// Default pruning window, headers and samples older than that are removed.
+///
+/// It is 1 hour behind the default syncing window.
+pub const DEFAULT_PRUNING_WINDOW: Duration =
+    DEFAULT_SYNCING_WINDOW.saturating_add(PRUNING_WINDOW_MARGIN);
+/// Distance between the syncing window and the default pruning window.
+pub(crate) const PRUNING_WINDOW_MARGIN: Duration = Duration::from_secs(60 * 60);
 pub const DEFAULT_PRUNING_INTERVAL: Duration = Duration::from_secs(12);
 
 type Result<T, E = PrunerError> = std::result::Result<T, E>;
@@ -60,6 +66,13 @@ where
     pub event_pub: EventPublisher,
     /// interval at which pruner will run
     pub pruning_interval: Duration,
+    /// Headers older than this window are removed, `None` disables pruning.
+    pub pruning_window: Option<Duration>,
+    /// Keep the headers and remove only the samples and sampling metadata of the
+    /// blocks older than the pruning window.
+    pub archival: bool,
+    /// Metrics of the node.
+    pub metrics: Arc<Metrics>,
 }
 
 impl Pruner {
@@ -117,6 +130,9 @@ where
     store: Arc<S>,
     blockstore: Arc<B>,
     pruning_interval: Duration,
+    pruning_window: Option<Duration>,
+    archival: bool,
+    metrics: Arc<Metrics>,
 }
 
 impl<S, B> Worker<S, B>
@@ -131,15 +147,29 @@ where
             store: args.store,
             blockstore: args.blockstore,
             pruning_interval: args.pruning_interval,
+            pruning_window: args.pruning_window,
+            archival: args.archival,
+            metrics: args.metrics,
         }
     }
 
     async fn run(&mut self) -> Result<()> {
+        let Some(pruning_window) = self.pruning_window else {
+            debug!("Pruning disabled");
+            self.cancellation_token.cancelled().await;
+            debug!("Pruner stopped");
+            return Ok(());
+        };
+
+        if self.archival {
+            return self.run_archival(pruning_window).await;
+        }
+
         let mut last_reported = None;
         let mut last_removed = None;
 
         loop {
-            let pruning_window_end = Time::now().checked_sub(PRUNING_WINDOW).unwrap_or_else(|| {
+            let pruning_window_end = Time::now().checked_sub(pruning_window).unwrap_or_else(|| {
                 warn!("underflow when computing pruning window start, defaulting to unix epoch");
                 Time::unix_epoch()
             });
@@ -165,6 +195,7 @@ where
                     return Err(PrunerError::WrongHeightRemoved);
                 }
 
+                self.metrics.pruned_header();
                 last_removed = Some(height);
             }
 
@@ -185,6 +216,103 @@ where
         Ok(())
     }
 
+    /// Pruning of the archival node, where headers stay in the store and only
+    /// the sampling data of the old blocks is removed.
+    async fn run_archival(&mut self, pruning_window: Duration) -> Result<()> {
+        // Highest height whose sampling data was removed.
+        let mut last_pruned = None;
+
+        loop {
+            let pruning_window_end = Time::now().checked_sub(pruning_window).unwrap_or_else(|| {
+                warn!("underflow when computing pruning window start, defaulting to unix epoch");
+                Time::unix_epoch()
+            });
+
+            if let Some(cutoff_height) = self.get_last_height_to_prune(&pruning_window_end).await? {
+                match last_pruned {
+                    // Sampling data is removed in order, so everything up to the
+                    // previous cutoff is gone already.
+                    Some(last_pruned) => {
+                        for height in last_pruned + 1..=cutoff_height {
+                            if self.cancellation_token.is_cancelled() {
+                                break;
+                            }
+                            self.remove_sampling_data(height).await?;
+                        }
+                    }
+                    // After the start we don't know where the previous run stopped,
+                    // so all the blocks with sampling metadata are checked.
+                    None => {
+                        let sampled = self.store.get_sampling_metadata_ranges().await?;
+
+                        for height in sampled {
+                            if height > cutoff_height || self.cancellation_token.is_cancelled() {
+                                break;
+                            }
+                            self.remove_sampling_data(height).await?;
+                        }
+                    }
+                }
+
+                if !self.cancellation_token.is_cancelled() {
+                    last_pruned = Some(cutoff_height);
+                }
+            }
+
+            select! {
+                _ = self.cancellation_token.cancelled() => break,
+                _ = sleep(self.pruning_interval) => ()
+            }
+        }
+
+        debug!("Pruner stopped");
+        Ok(())
+    }
+
+    /// Remove the samples and sampling metadata of the block, if there are any.
+    async fn remove_sampling_data(&self, height: u64) -> Result<()> {
+        let metadata = match self.store.get_sampling_metadata(height).await {
+            Ok(Some(metadata)) => metadata,
+            // Block was never sampled or its header is not synced yet.
+            Ok(None) | Err(StoreError::NotFound) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        for cid in metadata.cids {
+            self.blockstore.remove(&cid).await?;
+        }
+
+        self.store.remove_sampling_metadata(height).await?;
+
+        Ok(())
+    }
+
+    /// Get the height of the newest header outside of the pruning window, looking only
+    /// at the range of the store's head, or None if there's nothing to prune.
+    async fn get_last_height_to_prune(&self, cutoff: &Time) -> Result<Option<u64>> {
+        let stored_ranges = self.store.get_stored_header_ranges().await?;
+        let Some(head_range) = stored_ranges.into_inner().pop() else {
+            // empty store == nothing to prune
+            return Ok(None);
+        };
+
+        // Binary search for the last header that is not newer than the cutoff.
+        let (mut low, mut high) = (*head_range.start(), *head_range.end() + 1);
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let header = self.store.get_by_height(mid).await?;
+
+            if &header.time() > cutoff {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        Ok((low > *head_range.start()).then(|| low - 1))
+    }
+
     /// Get oldest header from the store to be pruned or None if there's nothing to prune
     async fn get_tail_header_to_prune(&self, cutoff: &Time) -> Result<Option<ExtendedHeader>> {
         let Some(current_tail_height) = self.store.get_stored_header_ranges().await?.tail() else {
@@ -232,6 +360,9 @@ mod test {
             blockstore,
             event_pub: events.publisher(),
             pruning_interval: Duration::from_secs(1),
+            pruning_window: Some(DEFAULT_PRUNING_WINDOW),
+            archival: false,
+            metrics: Arc::default(),
         });
 
         sleep(Duration::from_secs(1)).await;
@@ -259,6 +390,9 @@ mod test {
             blockstore,
             event_pub: events.publisher(),
             pruning_interval: Duration::from_secs(1),
+            pruning_window: Some(DEFAULT_PRUNING_WINDOW),
+            archival: false,
+            metrics: Arc::default(),
         });
 
         sleep(Duration::from_secs(1)).await;
@@ -277,6 +411,84 @@ mod test {
         );
     }
 
+    #[async_test]
+    async fn pruning_disabled() {
+        let events = EventChannel::new();
+        let store = Arc::new(InMemoryStore::new());
+        let mut gen = ExtendedHeaderGenerator::new();
+        let blockstore = Arc::new(InMemoryBlockstore::new());
+        let mut event_subscriber = events.subscribe();
+
+        let first_header_time =
+            (Time::now() - (DEFAULT_PRUNING_WINDOW + Duration::from_secs(24 * 60 * 60))).unwrap();
+        gen.set_time(first_header_time, Duration::from_secs(1));
+        store.insert(gen.next_many_verified(100)).await.unwrap();
+
+        let pruner = Pruner::start(PrunerArgs {
+            store: store.clone(),
+            blockstore,
+            event_pub: events.publisher(),
+            pruning_interval: Duration::from_secs(1),
+            pruning_window: None,
+            archival: false,
+            metrics: Arc::default(),
+        });
+
+        sleep(Duration::from_secs(2)).await;
+
+        pruner.stop();
+        pruner.join().await;
+
+        assert!(matches!(
+            event_subscriber.try_recv().unwrap_err(),
+            TryRecvError::Empty
+        ));
+        assert_eq!(
+            store.get_stored_header_ranges().await.unwrap(),
+            new_block_ranges([1..=100])
+        );
+    }
+
+    #[async_test]
+    async fn prune_with_custom_window() {
+        let events = EventChannel::new();
+        let store = Arc::new(InMemoryStore::new());
+        let mut gen = ExtendedHeaderGenerator::new();
+        let blockstore = Arc::new(InMemoryBlockstore::new());
+        let mut event_subscriber = events.subscribe();
+
+        // 30 headers older than 2 days
+        let two_days_ago = (Time::now() - Duration::from_secs(2 * 24 * 60 * 60)).unwrap();
+        gen.set_time(two_days_ago, Duration::from_secs(1));
+        store.insert(gen.next_many_verified(30)).await.unwrap();
+
+        // 20 headers at current time
+        gen.reset_time();
+        store.insert(gen.next_many_verified(20)).await.unwrap();
+
+        let pruner = Pruner::start(PrunerArgs {
+            store: store.clone(),
+            blockstore,
+            event_pub: events.publisher(),
+            pruning_interval: Duration::from_secs(1),
+            pruning_window: Some(Duration::from_secs(24 * 60 * 60)),
+            archival: false,
+            metrics: Arc::default(),
+        });
+
+        let pruner_event = event_subscriber.recv().await.unwrap().event;
+        assert!(matches!(
+            pruner_event,
+            NodeEvent::PrunedHeaders { to_height: 30 }
+        ));
+        assert_eq!(
+            store.get_stored_header_ranges().await.unwrap(),
+            new_block_ranges([31..=50])
+        );
+
+        pruner.stop();
+    }
+
     #[async_test]
     async fn prune_large_tail_with_cids() {
         let events = EventChannel::new();
@@ -286,8 +498,9 @@ mod test {
         let blockstore = Arc::new(InMemoryBlockstore::new());
         let mut event_subscriber = events.subscribe();
 
-        let first_header_time =
-            (Time::now() - (PRUNING_WINDOW + Duration::from_secs(30 * 24 * 60 * 60))).unwrap();
+        let first_header_time = (Time::now()
+            - (DEFAULT_PRUNING_WINDOW + Duration::from_secs(30 * 24 * 60 * 60)))
+        .unwrap();
         gen.set_time(first_header_time, Duration::from_secs(1));
 
         let blocks_with_sampling = (1..=1000)
@@ -318,6 +531,9 @@ mod test {
             blockstore: blockstore.clone(),
             event_pub: events.publisher(),
             pruning_interval: Duration::from_secs(1),
+            pruning_window: Some(DEFAULT_PRUNING_WINDOW),
+            archival: false,
+            metrics: Arc::default(),
         });
 
         sleep(Duration::from_secs(1)).await;
@@ -345,6 +561,86 @@ mod test {
         ));
     }
 
+    #[async_test]
+    async fn archival_prunes_only_sampling_data() {
+        let events = EventChannel::new();
+        let store = Arc::new(InMemoryStore::new());
+        let mut gen = ExtendedHeaderGenerator::new();
+        let blockstore = Arc::new(InMemoryBlockstore::new());
+        let mut event_subscriber = events.subscribe();
+
+        // 60 headers before pruning window edge
+        let first_header_time =
+            (Time::now() - (DEFAULT_PRUNING_WINDOW + Duration::from_millis(60_500))).unwrap();
+        gen.set_time(first_header_time, Duration::from_secs(1));
+        store.insert(gen.next_many_verified(100)).await.unwrap();
+
+        // Blocks from 11 to 30 were rejected and the ones up to 40 were never sampled
+        let blocks_with_sampling = (11..=30)
+            .chain(41..=100)
+            .map(|height| {
+                let block = TestBlock::from(height);
+                (height, block, block.cid().unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        for (height, block, cid) in &blocks_with_sampling {
+            let status = if *height <= 30 {
+                SamplingStatus::Rejected
+            } else {
+                SamplingStatus::Accepted
+            };
+
+            blockstore.put_keyed(cid, block.data()).await.unwrap();
+            store
+                .update_sampling_metadata(*height, status, vec![*cid])
+                .await
+                .unwrap()
+        }
+
+        let pruner = Pruner::start(PrunerArgs {
+            store: store.clone(),
+            blockstore: blockstore.clone(),
+            event_pub: events.publisher(),
+            pruning_interval: Duration::from_secs(1),
+            pruning_window: Some(DEFAULT_PRUNING_WINDOW),
+            archival: true,
+            metrics: Arc::default(),
+        });
+
+        sleep(Duration::from_millis(500)).await;
+
+        // Headers are kept
+        assert_eq!(
+            store.get_stored_header_ranges().await.unwrap(),
+            new_block_ranges([1..=100])
+        );
+
+        for (height, _, cid) in &blocks_with_sampling {
+            let metadata = store.get_sampling_metadata(*height).await.unwrap();
+
+            if *height <= 60 {
+                assert!(metadata.is_none());
+                assert!(!blockstore.has(cid).await.unwrap());
+            } else {
+                assert!(metadata.is_some());
+                assert!(blockstore.has(cid).await.unwrap());
+            }
+        }
+
+        assert_eq!(
+            store.get_accepted_sampling_ranges().await.unwrap(),
+            new_block_ranges([61..=100])
+        );
+
+        pruner.stop();
+
+        assert!(matches!(
+            event_subscriber.try_recv().unwrap_err(),
+            TryRecvError::Empty
+        ));
+    }
+
     #[async_test]
     async fn prune_tail() {
         const BLOCK_TIME: Duration = Duration::from_millis(10);
@@ -356,12 +652,14 @@ mod test {
         let mut event_subscriber = events.subscribe();
 
         // 50 headers before pruning window edge
-        let before_pruning_edge = (Time::now() - (PRUNING_WINDOW + BLOCK_TIME * 100)).unwrap();
+        let before_pruning_edge =
+            (Time::now() - (DEFAULT_PRUNING_WINDOW + BLOCK_TIME * 100)).unwrap();
         gen.set_time(before_pruning_edge, BLOCK_TIME);
         store.insert(gen.next_many_verified(50)).await.unwrap();
 
         // 10 headers within 1sec of pruning window edge
-        let after_pruning_edge = (Time::now() - (PRUNING_WINDOW - BLOCK_TIME * 10)).unwrap();
+        let after_pruning_edge =
+            (Time::now() - (DEFAULT_PRUNING_WINDOW - BLOCK_TIME * 10)).unwrap();
         gen.set_time(after_pruning_edge, BLOCK_TIME);
         store.insert(gen.next_many_verified(10)).await.unwrap();
 
@@ -374,6 +672,9 @@ mod test {
             blockstore,
             event_pub: events.publisher(),
             pruning_interval: Duration::from_secs(1),
+            pruning_window: Some(DEFAULT_PRUNING_WINDOW),
+            archival: false,
+            metrics: Arc::default(),
         });
 
         sleep(Duration::from_secs(1)).await;
@@ -15,6 +15,9 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 pub use crate::block_ranges::{BlockRange, BlockRanges, BlockRangesError};
+pub use crate::store::snapshot::{
+    export_snapshot, import_snapshot, SnapshotError, SNAPSHOT_VERSION,
+};
 pub use crate::store::utils::VerifiedExtendedHeaders;
 
 pub use in_memory_store::InMemoryStore;
@@ -28,6 +31,7 @@ mod in_memory_store;
 mod indexed_db_store;
 #[cfg(not(target_arch = "wasm32"))]
 mod redb_store;
+mod snapshot;
 
 pub(crate) mod utils;
 
@@ -155,9 +159,17 @@ pub trait Store: Send + Sync + Debug {
     /// Returns a list of accepted sampling ranges currently held in store.
     async fn get_accepted_sampling_ranges(&self) -> Result<BlockRanges>;
 
+    /// Returns a list of ranges of the blocks with sampling metadata, regardless of its status.
+    async fn get_sampling_metadata_ranges(&self) -> Result<BlockRanges>;
+
     /// Remove header with lowest height from the store.
     async fn remove_last(&self) -> Result<u64>;
 
+    /// Remove the sampling metadata of the height, keeping the header.
+    ///
+    /// `Err(StoreError::NotFound)` indicates that the header is not in the store.
+    async fn remove_sampling_metadata(&self, height: u64) -> Result<()>;
+
     /// Close store.
     async fn close(self) -> Result<()>;
 }
@@ -784,6 +796,52 @@ mod tests {
             .unwrap_err();
     }
 
+    #[rstest]
+    #[case::in_memory(new_in_memory_store())]
+    #[cfg_attr(not(target_arch = "wasm32"), case::redb(new_redb_store()))]
+    #[cfg_attr(target_arch = "wasm32", case::indexed_db(new_indexed_db_store()))]
+    #[self::test]
+    async fn test_sampling_metadata_removal<S: Store>(
+        #[case]
+        #[future(awt)]
+        s: S,
+    ) {
+        let mut store = s;
+        fill_store(&mut store, 4).await;
+
+        for height in 1..=3 {
+            store
+                .update_sampling_metadata(height, SamplingStatus::Accepted, vec![])
+                .await
+                .unwrap();
+        }
+        store
+            .update_sampling_metadata(4, SamplingStatus::Rejected, vec![])
+            .await
+            .unwrap();
+
+        store.remove_sampling_metadata(2).await.unwrap();
+        // Removing missing metadata is not an error
+        store.remove_sampling_metadata(2).await.unwrap();
+
+        assert!(store.get_sampling_metadata(2).await.unwrap().is_none());
+        assert!(store.get_sampling_metadata(3).await.unwrap().is_some());
+        assert_eq!(
+            store.get_accepted_sampling_ranges().await.unwrap(),
+            new_block_ranges([1..=1, 3..=3])
+        );
+        assert_eq!(
+            store.get_sampling_metadata_ranges().await.unwrap(),
+            new_block_ranges([1..=1, 3..=4])
+        );
+        assert!(store.has_at(2).await);
+
+        assert!(matches!(
+            store.remove_sampling_metadata(5).await.unwrap_err(),
+            StoreError::NotFound
+        ));
+    }
+
     #[rstest]
     #[case::in_memory(new_in_memory_store())]
     #[cfg_attr(not(target_arch = "wasm32"), case::redb(new_redb_store()))]
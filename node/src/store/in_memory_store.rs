@@ -126,6 +126,10 @@ impl InMemoryStore {
         self.inner.read().await.get_accepted_sampling_ranges()
     }
 
+    async fn get_sampling_metadata_ranges(&self) -> BlockRanges {
+        self.inner.read().await.get_sampling_metadata_ranges()
+    }
+
     /// Clone the store and all its contents. Async fn due to internal use of async mutex.
     pub async fn async_clone(&self) -> Self {
         InMemoryStore {
@@ -138,6 +142,10 @@ impl InMemoryStore {
         let mut inner = self.inner.write().await;
         inner.remove_last()
     }
+
+    async fn remove_sampling_metadata(&self, height: u64) -> Result<()> {
+        self.inner.write().await.remove_sampling_metadata(height)
+    }
 }
 
 impl InMemoryStoreInner {
@@ -149,6 +157,19 @@ impl InMemoryStoreInner {
         self.accepted_sampling_ranges.clone()
     }
 
+    fn get_sampling_metadata_ranges(&self) -> BlockRanges {
+        let mut heights: Vec<_> = self.sampling_data.keys().copied().collect();
+        heights.sort_unstable();
+
+        let mut ranges = BlockRanges::default();
+        for height in heights {
+            ranges
+                .insert_relaxed(height..=height)
+                .expect("invalid height");
+        }
+        ranges
+    }
+
     #[inline]
     fn get_head_height(&self) -> Result<u64> {
         self.header_ranges.head().ok_or(StoreError::NotFound)
@@ -307,6 +328,19 @@ impl InMemoryStoreInner {
         Ok(Some(metadata.clone()))
     }
 
+    fn remove_sampling_metadata(&mut self, height: u64) -> Result<()> {
+        if !self.contains_height(height) {
+            return Err(StoreError::NotFound);
+        }
+
+        self.sampling_data.remove(&height);
+        self.accepted_sampling_ranges
+            .remove_relaxed(height..=height)
+            .expect("invalid height");
+
+        Ok(())
+    }
+
     fn remove_last(&mut self) -> Result<u64> {
         let Some(height) = self.header_ranges.tail() else {
             return Err(StoreError::NotFound);
@@ -427,10 +461,18 @@ impl Store for InMemoryStore {
         Ok(self.get_accepted_sampling_ranges().await)
     }
 
+    async fn get_sampling_metadata_ranges(&self) -> Result<BlockRanges> {
+        Ok(self.get_sampling_metadata_ranges().await)
+    }
+
     async fn remove_last(&self) -> Result<u64> {
         self.remove_last().await
     }
 
+    async fn remove_sampling_metadata(&self, height: u64) -> Result<()> {
+        self.remove_sampling_metadata(height).await
+    }
+
     async fn close(self) -> Result<()> {
         Ok(())
     }
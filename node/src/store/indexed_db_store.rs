@@ -296,6 +296,25 @@ impl IndexedDbStore {
         get_ranges(&store, ACCEPTED_SAMPLING_RANGES_KEY).await
     }
 
+    async fn get_sampling_metadata_ranges(&self) -> Result<BlockRanges> {
+        let tx = self
+            .db
+            .transaction(&[SAMPLING_STORE_NAME], TransactionMode::ReadOnly)?;
+        let store = tx.store(SAMPLING_STORE_NAME)?;
+
+        let mut ranges = BlockRanges::default();
+
+        // Keys are returned in ascending order
+        for key in store.get_all_keys(None, None).await? {
+            let height: u64 = from_value(key)?;
+            ranges
+                .insert_relaxed(height..=height)
+                .expect("invalid height");
+        }
+
+        Ok(ranges)
+    }
+
     async fn remove_last(&self) -> Result<u64> {
         self.write_tx(
             &[HEADER_STORE_NAME, RANGES_STORE_NAME],
@@ -304,6 +323,15 @@ impl IndexedDbStore {
         )
         .await
     }
+
+    async fn remove_sampling_metadata(&self, height: u64) -> Result<()> {
+        self.write_tx(
+            &[SAMPLING_STORE_NAME, RANGES_STORE_NAME],
+            remove_sampling_metadata_tx_op,
+            height,
+        )
+        .await
+    }
 }
 
 trait TransactionOperationFn<'a, Arg>:
@@ -422,11 +450,21 @@ impl Store for IndexedDbStore {
         fut.await
     }
 
+    async fn get_sampling_metadata_ranges(&self) -> Result<BlockRanges> {
+        let fut = SendWrapper::new(self.get_sampling_metadata_ranges());
+        fut.await
+    }
+
     async fn remove_last(&self) -> Result<u64> {
         let fut = SendWrapper::new(self.remove_last());
         fut.await
     }
 
+    async fn remove_sampling_metadata(&self, height: u64) -> Result<()> {
+        let fut = SendWrapper::new(self.remove_sampling_metadata(height));
+        fut.await
+    }
+
     async fn close(self) -> Result<()> {
         self.db.take().close();
         Ok(())
@@ -703,6 +741,33 @@ async fn update_sampling_metadata_tx_op(
     Ok(())
 }
 
+async fn remove_sampling_metadata_tx_op(tx: &Transaction, height: u64) -> Result<()> {
+    let sampling_store = tx.store(SAMPLING_STORE_NAME)?;
+    let ranges_store = tx.store(RANGES_STORE_NAME)?;
+
+    let header_ranges = get_ranges(&ranges_store, HEADER_RANGES_KEY).await?;
+    let mut accepted_ranges = get_ranges(&ranges_store, ACCEPTED_SAMPLING_RANGES_KEY).await?;
+
+    if !header_ranges.contains(height) {
+        return Err(StoreError::NotFound);
+    }
+
+    sampling_store.delete(to_value(&height)?).await?;
+
+    accepted_ranges
+        .remove_relaxed(height..=height)
+        .expect("invalid height");
+
+    set_ranges(
+        &ranges_store,
+        ACCEPTED_SAMPLING_RANGES_KEY,
+        &accepted_ranges,
+    )
+    .await?;
+
+    Ok(())
+}
+
 async fn remove_last_tx_op(tx: &Transaction, _: ()) -> Result<u64> {
     let header_store = tx.store(HEADER_STORE_NAME)?;
     let height_index = header_store.index(HEIGHT_INDEX_NAME)?;
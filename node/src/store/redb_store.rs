@@ -68,6 +68,47 @@ impl RedbStore {
         RedbStore::new(Arc::new(db)).await
     }
 
+    /// Open an existing persistent [`redb`] store without creating or migrating it.
+    ///
+    /// The database file is opened for writing, so [`redb`] may repair it if it
+    /// wasn't closed cleanly. Fails if the database is already opened, e.g. by
+    /// a running node.
+    pub async fn open_existing(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+
+        let db = spawn_blocking(|| Database::open(path))
+            .await?
+            .map_err(|e| match e {
+                redb::DatabaseError::DatabaseAlreadyOpen => {
+                    StoreError::OpenFailed("Database is already opened by another process".into())
+                }
+                e => StoreError::OpenFailed(e.to_string()),
+            })?;
+
+        let store = RedbStore {
+            inner: Arc::new(Inner {
+                db: Arc::new(db),
+                header_added_notifier: Notify::new(),
+            }),
+            task_counter: Counter::new(),
+        };
+
+        let schema_version = store
+            .schema_version()
+            .await
+            .map_err(|e| StoreError::OpenFailed(e.to_string()))?;
+
+        if schema_version != SCHEMA_VERSION {
+            let e = format!(
+                "Incompatible database schema; found {}, expected {}.",
+                schema_version, SCHEMA_VERSION
+            );
+            return Err(StoreError::OpenFailed(e));
+        }
+
+        Ok(store)
+    }
+
     /// Open an in memory [`redb`] store.
     pub async fn in_memory() -> Result<Self> {
         let db = Database::builder()
@@ -143,6 +184,17 @@ impl RedbStore {
         self.inner.db.clone()
     }
 
+    /// Returns the version of the database schema.
+    pub async fn schema_version(&self) -> Result<u64> {
+        self.read_tx(|tx| {
+            let table = tx.open_table(SCHEMA_VERSION_TABLE)?;
+            let version = table.get(())?.map(|guard| guard.value());
+
+            version.ok_or_else(|| StoreError::StoredDataError("schema version missing".into()))
+        })
+        .await
+    }
+
     /// Execute a read transaction.
     async fn read_tx<F, T>(&self, f: F) -> Result<T>
     where
@@ -424,6 +476,53 @@ impl RedbStore {
         .await
     }
 
+    async fn get_sampling_metadata_ranges(&self) -> Result<BlockRanges> {
+        self.read_tx(|tx| {
+            let table = tx.open_table(SAMPLING_METADATA_TABLE)?;
+            let mut ranges = BlockRanges::default();
+
+            // Keys are iterated in ascending order
+            for entry in table.iter()? {
+                let height = entry?.0.value();
+                ranges
+                    .insert_relaxed(height..=height)
+                    .expect("invalid height");
+            }
+
+            Ok(ranges)
+        })
+        .await
+    }
+
+    async fn remove_sampling_metadata(&self, height: u64) -> Result<()> {
+        self.write_tx(move |tx| {
+            let mut sampling_metadata_table = tx.open_table(SAMPLING_METADATA_TABLE)?;
+            let mut ranges_table = tx.open_table(RANGES_TABLE)?;
+
+            let header_ranges = get_ranges(&ranges_table, HEADER_RANGES_KEY)?;
+            let mut sampling_ranges = get_ranges(&ranges_table, ACCEPTED_SAMPING_RANGES_KEY)?;
+
+            if !header_ranges.contains(height) {
+                return Err(StoreError::NotFound);
+            }
+
+            sampling_metadata_table.remove(height)?;
+
+            sampling_ranges
+                .remove_relaxed(height..=height)
+                .expect("invalid height");
+
+            set_ranges(
+                &mut ranges_table,
+                ACCEPTED_SAMPING_RANGES_KEY,
+                &sampling_ranges,
+            )?;
+
+            Ok(())
+        })
+        .await
+    }
+
     async fn remove_last(&self) -> Result<u64> {
         self.write_tx(move |tx| {
             let mut heights_table = tx.open_table(HEIGHTS_TABLE)?;
@@ -549,10 +648,18 @@ impl Store for RedbStore {
         self.get_sampling_ranges().await
     }
 
+    async fn get_sampling_metadata_ranges(&self) -> Result<BlockRanges> {
+        self.get_sampling_metadata_ranges().await
+    }
+
     async fn remove_last(&self) -> Result<u64> {
         self.remove_last().await
     }
 
+    async fn remove_sampling_metadata(&self, height: u64) -> Result<()> {
+        self.remove_sampling_metadata(height).await
+    }
+
     async fn close(mut self) -> Result<()> {
         // Wait all ongoing `spawn_blocking` tasks to finish.
         self.task_counter.wait_guards().await;
@@ -807,6 +914,36 @@ pub mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_open_existing() {
+        let tmp_dir = TempDir::with_prefix("lumina.store.test").unwrap();
+        let db = tmp_dir.path().join("db");
+
+        // Store must exist
+        RedbStore::open_existing(&db).await.unwrap_err();
+
+        let (original_store, _) = gen_filled_store(20, Some(&db)).await;
+        original_store
+            .update_sampling_metadata(10, SamplingStatus::Accepted, vec![])
+            .await
+            .unwrap();
+
+        // Store must not be opened by another instance
+        RedbStore::open_existing(&db).await.unwrap_err();
+        drop(original_store);
+
+        let store = RedbStore::open_existing(&db).await.unwrap();
+        assert_eq!(store.schema_version().await.unwrap(), SCHEMA_VERSION);
+        assert_eq!(
+            store.get_stored_header_ranges().await.unwrap().as_ref(),
+            &[1..=20]
+        );
+        assert_eq!(
+            store.get_accepted_sampling_ranges().await.unwrap().as_ref(),
+            &[10..=10]
+        );
+    }
+
     #[tokio::test]
     async fn test_separate_stores() {
         let (store0, mut gen0) = gen_filled_store(0, None).await;
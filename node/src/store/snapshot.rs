@@ -0,0 +1,604 @@
+//! Portable snapshots of the [`Store`] content.
+//!
+//! Snapshot is a stream of length-delimited protobuf messages. It starts with a
+//! header carrying the format version and the ranges of the exported headers,
+//! followed by an entry for each [`ExtendedHeader`], in descending order of heights.
+//! Entries also carry the sampling metadata of the blocks, if there is any.
+//!
+//! Sampling results can't be verified, so they are restored on import only if
+//! the snapshot comes from a trusted source. Otherwise imported blocks are
+//! sampled again, if they are in the sampling window. Samples themselves are
+//! not part of the snapshot, so only the sampling status is restored.
+//!
+//! Snapshots rely only on the [`Store`] trait, so they can be moved between
+//! different store implementations.
+
+use celestia_proto::header::pb::ExtendedHeader as RawExtendedHeader;
+use celestia_types::hash::Hash;
+use celestia_types::ExtendedHeader;
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use prost::Message;
+use smallvec::SmallVec;
+use thiserror::Error;
+
+use crate::block_ranges::BlockRanges;
+use crate::store::utils::validate_headers;
+use crate::store::{RawSamplingMetadata, SamplingMetadata, SamplingStatus, Store, StoreError};
+
+/// Version of the snapshot format.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Maximum size of a single message in the snapshot.
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Maximum number of headers inserted into the store at once on import.
+const IMPORT_BATCH_SIZE: usize = 512;
+
+type Result<T, E = SnapshotError> = std::result::Result<T, E>;
+
+/// Representation of all the errors that can occur when exporting or importing snapshots.
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    /// An error propagated from the [`Store`].
+    #[error("Store: {0}")]
+    Store(#[from] StoreError),
+
+    /// Reading or writing the snapshot failed.
+    #[error("I/O: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Snapshot was created with an unsupported version of the format.
+    #[error("Unsupported snapshot version: {0}")]
+    UnsupportedVersion(u32),
+
+    /// Snapshot is malformed.
+    #[error("Invalid snapshot: {0}")]
+    InvalidSnapshot(String),
+
+    /// Headers from the snapshot failed verification.
+    #[error("Headers verification failed: {0}")]
+    HeadersVerificationFailed(celestia_types::Error),
+
+    /// Range of headers can't be tied to any trusted header.
+    #[error("Headers ending at height {0} are not anchored to the store or the trusted hash")]
+    NotAnchored(u64),
+}
+
+#[derive(Message)]
+struct RawSnapshotHeader {
+    #[prost(uint32, tag = "1")]
+    version: u32,
+
+    #[prost(message, repeated, tag = "2")]
+    ranges: Vec<RawBlockRange>,
+}
+
+#[derive(Message)]
+struct RawBlockRange {
+    #[prost(uint64, tag = "1")]
+    start: u64,
+
+    #[prost(uint64, tag = "2")]
+    end: u64,
+}
+
+#[derive(Message)]
+struct RawSnapshotEntry {
+    #[prost(message, optional, tag = "1")]
+    header: Option<RawExtendedHeader>,
+
+    #[prost(message, optional, tag = "2")]
+    sampling_metadata: Option<RawSamplingMetadata>,
+}
+
+/// Write the headers of the given `ranges` from the `store` as a snapshot,
+/// together with their sampling metadata.
+///
+/// Returns the number of exported headers.
+///
+/// # Errors
+///
+/// Returns [`StoreError::NotFound`] if any of the headers is not in the store.
+pub async fn export_snapshot<S, W>(store: &S, ranges: &BlockRanges, writer: W) -> Result<u64>
+where
+    S: Store,
+    W: AsyncWrite + Unpin,
+{
+    let mut writer = writer;
+
+    let snapshot_header = RawSnapshotHeader {
+        version: SNAPSHOT_VERSION,
+        ranges: ranges
+            .as_ref()
+            .iter()
+            .map(|range| RawBlockRange {
+                start: *range.start(),
+                end: *range.end(),
+            })
+            .collect(),
+    };
+    write_message(&mut writer, &snapshot_header).await?;
+
+    let mut exported = 0;
+
+    for height in ranges.clone().rev() {
+        let header = store.get_by_height(height).await?;
+        let sampling_metadata = store.get_sampling_metadata(height).await?;
+
+        let entry = RawSnapshotEntry {
+            header: Some(header.into()),
+            sampling_metadata: sampling_metadata.map(Into::into),
+        };
+        write_message(&mut writer, &entry).await?;
+
+        exported += 1;
+    }
+
+    writer.flush().await?;
+
+    Ok(exported)
+}
+
+/// Read a snapshot and insert its headers into the `store`.
+///
+/// Headers are validated and verified against each other, and against their
+/// neighbours already present in the store. Headers which are already in the
+/// store are skipped.
+///
+/// Each range of the snapshot must be anchored at its highest header, either
+/// by the header above it being already in the store, or by the highest header
+/// having the `trusted_hash`. This way imported headers are always tied to a
+/// trusted chain.
+///
+/// Sampling status of the imported headers is restored only if `restore_sampling`
+/// is set, which should be done only for snapshots from a trusted source.
+///
+/// Returns the number of imported headers.
+///
+/// # Errors
+///
+/// Returns [`SnapshotError::NotAnchored`] if a range of headers is not anchored.
+/// Headers imported before the error are kept in the store.
+pub async fn import_snapshot<S, R>(
+    store: &S,
+    reader: R,
+    trusted_hash: Option<Hash>,
+    restore_sampling: bool,
+) -> Result<u64>
+where
+    S: Store,
+    R: AsyncRead + Unpin,
+{
+    let mut reader = reader;
+
+    let snapshot_header: RawSnapshotHeader = read_message(&mut reader)
+        .await?
+        .ok_or_else(|| SnapshotError::InvalidSnapshot("missing header".into()))?;
+
+    if snapshot_header.version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(snapshot_header.version));
+    }
+
+    let ranges = snapshot_header
+        .ranges
+        .iter()
+        .map(|range| range.start..=range.end)
+        .collect::<SmallVec<_>>();
+    let ranges = BlockRanges::from_vec(ranges)
+        .map_err(|e| SnapshotError::InvalidSnapshot(format!("invalid ranges: {e}")))?;
+
+    // Headers in descending order of heights, with the sampling status to restore
+    let mut batch: Vec<(ExtendedHeader, Option<SamplingStatus>)> =
+        Vec::with_capacity(IMPORT_BATCH_SIZE);
+    let mut imported = 0;
+
+    for height in ranges.rev() {
+        let entry: RawSnapshotEntry = read_message(&mut reader)
+            .await?
+            .ok_or_else(|| SnapshotError::InvalidSnapshot(format!("missing header {height}")))?;
+
+        let header: ExtendedHeader = entry
+            .header
+            .ok_or_else(|| SnapshotError::InvalidSnapshot(format!("missing header {height}")))?
+            .try_into()
+            .map_err(SnapshotError::HeadersVerificationFailed)?;
+
+        if header.height().value() != height {
+            return Err(SnapshotError::InvalidSnapshot(format!(
+                "expected header {height}, found {}",
+                header.height()
+            )));
+        }
+
+        let sampling_status = match entry.sampling_metadata {
+            Some(raw) if restore_sampling => {
+                let metadata = SamplingMetadata::try_from(raw).map_err(|e| {
+                    SnapshotError::InvalidSnapshot(format!("sampling metadata {height}: {e}"))
+                })?;
+                Some(metadata.status)
+            }
+            _ => None,
+        };
+
+        if store.has_at(height).await {
+            imported += insert_batch(store, &mut batch).await?;
+            continue;
+        }
+
+        // Batches consist only of adjacent headers
+        if batch
+            .last()
+            .is_some_and(|(prev, _)| prev.height().value() != height + 1)
+        {
+            imported += insert_batch(store, &mut batch).await?;
+        }
+
+        // Header starting a batch must be the trusted one, or be verified
+        // against the stored header above it on insertion.
+        if batch.is_empty()
+            && !store.has_at(height + 1).await
+            && trusted_hash != Some(header.hash())
+        {
+            return Err(SnapshotError::NotAnchored(height));
+        }
+
+        batch.push((header, sampling_status));
+
+        if batch.len() == IMPORT_BATCH_SIZE {
+            imported += insert_batch(store, &mut batch).await?;
+        }
+    }
+
+    imported += insert_batch(store, &mut batch).await?;
+
+    if read_message::<RawSnapshotEntry, _>(&mut reader)
+        .await?
+        .is_some()
+    {
+        return Err(SnapshotError::InvalidSnapshot(
+            "entries outside of the declared ranges".into(),
+        ));
+    }
+
+    Ok(imported)
+}
+
+/// Insert the batch of adjacent headers, given in descending order, into the store
+/// and restore their sampling status.
+async fn insert_batch<S>(
+    store: &S,
+    batch: &mut Vec<(ExtendedHeader, Option<SamplingStatus>)>,
+) -> Result<u64>
+where
+    S: Store,
+{
+    if batch.is_empty() {
+        return Ok(0);
+    }
+
+    let (mut headers, statuses): (Vec<_>, Vec<_>) = std::mem::take(batch).into_iter().unzip();
+    headers.reverse();
+    let count = headers.len() as u64;
+    let tail = headers[0].height().value();
+
+    validate_headers(&headers)
+        .await
+        .map_err(SnapshotError::HeadersVerificationFailed)?;
+
+    // Adjacent headers and neighbours are verified on insertion
+    store.insert(headers).await?;
+
+    for (height, status) in (tail..).zip(statuses.into_iter().rev()) {
+        if let Some(status) = status {
+            store
+                .update_sampling_metadata(height, status, Vec::new())
+                .await?;
+        }
+    }
+
+    Ok(count)
+}
+
+async fn write_message<M, W>(writer: &mut W, msg: &M) -> Result<()>
+where
+    M: Message,
+    W: AsyncWrite + Unpin,
+{
+    writer
+        .write_all(&msg.encode_length_delimited_to_vec())
+        .await?;
+    Ok(())
+}
+
+/// Read the next length-delimited message, or `None` on the end of the stream.
+async fn read_message<M, R>(reader: &mut R) -> Result<Option<M>>
+where
+    M: Message + Default,
+    R: AsyncRead + Unpin,
+{
+    let mut delimiter = Vec::with_capacity(10);
+
+    loop {
+        let mut byte = [0u8];
+
+        if reader.read(&mut byte).await? == 0 {
+            if delimiter.is_empty() {
+                return Ok(None);
+            }
+
+            return Err(SnapshotError::InvalidSnapshot("unexpected end".into()));
+        }
+
+        delimiter.push(byte[0]);
+
+        if byte[0] < 0x80 {
+            break;
+        }
+
+        if delimiter.len() == 10 {
+            return Err(SnapshotError::InvalidSnapshot("invalid delimiter".into()));
+        }
+    }
+
+    let len = prost::decode_length_delimiter(&delimiter[..])
+        .map_err(|_| SnapshotError::InvalidSnapshot("invalid delimiter".into()))?;
+
+    if len > MAX_MESSAGE_SIZE {
+        return Err(SnapshotError::InvalidSnapshot(format!(
+            "message exceeds the size limit: {len}"
+        )));
+    }
+
+    let mut buf = vec![0; len];
+    reader.read_exact(&mut buf).await?;
+
+    M::decode(&buf[..])
+        .map(Some)
+        .map_err(|e| SnapshotError::InvalidSnapshot(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{InMemoryStore, SamplingStatus};
+    use crate::test_utils::{async_test, gen_filled_store, new_block_ranges};
+    use celestia_types::test_utils::ExtendedHeaderGenerator;
+
+    #[async_test]
+    async fn export_import() {
+        let (store, _gen) = gen_filled_store(30).await;
+        store
+            .update_sampling_metadata(10, SamplingStatus::Accepted, vec![])
+            .await
+            .unwrap();
+
+        let ranges = new_block_ranges([1..=30]);
+        let mut snapshot = Vec::new();
+        let exported = export_snapshot(&store, &ranges, &mut snapshot)
+            .await
+            .unwrap();
+        assert_eq!(exported, 30);
+
+        let trusted_hash = store.get_head().await.unwrap().hash();
+        let imported_store = InMemoryStore::new();
+        let imported = import_snapshot(&imported_store, &snapshot[..], Some(trusted_hash), false)
+            .await
+            .unwrap();
+        assert_eq!(imported, 30);
+
+        assert_eq!(
+            imported_store.get_stored_header_ranges().await.unwrap(),
+            ranges
+        );
+
+        for height in ranges {
+            assert_eq!(
+                imported_store.get_by_height(height).await.unwrap(),
+                store.get_by_height(height).await.unwrap()
+            );
+        }
+
+        // Sampling results of untrusted snapshots are not imported, blocks need to be sampled again
+        let metadata = imported_store.get_sampling_metadata(10).await.unwrap();
+        assert!(metadata.is_none());
+        assert!(imported_store
+            .get_accepted_sampling_ranges()
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Sampling status is restored from trusted snapshots
+        let imported_store = InMemoryStore::new();
+        import_snapshot(&imported_store, &snapshot[..], Some(trusted_hash), true)
+            .await
+            .unwrap();
+
+        let metadata = imported_store.get_sampling_metadata(10).await.unwrap();
+        assert_eq!(metadata.unwrap().status, SamplingStatus::Accepted);
+        assert!(imported_store
+            .get_sampling_metadata(11)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            imported_store.get_accepted_sampling_ranges().await.unwrap(),
+            new_block_ranges([10..=10])
+        );
+    }
+
+    #[async_test]
+    async fn import_anchored_to_stored_headers() {
+        let (store, _gen) = gen_filled_store(30).await;
+
+        let mut snapshot = Vec::new();
+        export_snapshot(&store, &new_block_ranges([1..=5, 10..=30]), &mut snapshot)
+            .await
+            .unwrap();
+
+        // Each range of the snapshot ends right below the stored headers
+        let imported_store = InMemoryStore::new();
+        imported_store
+            .insert(store.get_range(6..=9).await.unwrap())
+            .await
+            .unwrap();
+        imported_store
+            .insert(store.get_range(26..=30).await.unwrap())
+            .await
+            .unwrap();
+
+        let imported = import_snapshot(&imported_store, &snapshot[..], None, false)
+            .await
+            .unwrap();
+        assert_eq!(imported, 21);
+        assert_eq!(
+            imported_store.get_stored_header_ranges().await.unwrap(),
+            new_block_ranges([1..=30])
+        );
+    }
+
+    #[async_test]
+    async fn import_rejects_forged_chain() {
+        let (store, _gen) = gen_filled_store(20).await;
+
+        // Self-consistent chain which has nothing to do with the stored one
+        let forged_store = InMemoryStore::new();
+        forged_store
+            .insert(ExtendedHeaderGenerator::new().next_many(20))
+            .await
+            .unwrap();
+
+        let mut snapshot = Vec::new();
+        export_snapshot(&forged_store, &new_block_ranges([1..=20]), &mut snapshot)
+            .await
+            .unwrap();
+
+        // Nothing to anchor the headers to
+        let imported_store = InMemoryStore::new();
+        let e = import_snapshot(&imported_store, &snapshot[..], None, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(e, SnapshotError::NotAnchored(20)));
+
+        // Trusted hash of the real chain
+        let trusted_hash = store.get_head().await.unwrap().hash();
+        let e = import_snapshot(&imported_store, &snapshot[..], Some(trusted_hash), false)
+            .await
+            .unwrap_err();
+        assert!(matches!(e, SnapshotError::NotAnchored(20)));
+        assert!(imported_store
+            .get_stored_header_ranges()
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Stored headers of the real chain
+        let mut snapshot = Vec::new();
+        export_snapshot(&forged_store, &new_block_ranges([1..=10]), &mut snapshot)
+            .await
+            .unwrap();
+        imported_store
+            .insert(store.get_range(11..=20).await.unwrap())
+            .await
+            .unwrap();
+
+        let e = import_snapshot(&imported_store, &snapshot[..], None, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            e,
+            SnapshotError::Store(StoreError::InsertionFailed(_))
+        ));
+        assert_eq!(
+            imported_store.get_stored_header_ranges().await.unwrap(),
+            new_block_ranges([11..=20])
+        );
+    }
+
+    #[async_test]
+    async fn import_skips_stored_headers() {
+        let (store, _gen) = gen_filled_store(20).await;
+
+        let mut snapshot = Vec::new();
+        export_snapshot(&store, &new_block_ranges([1..=20]), &mut snapshot)
+            .await
+            .unwrap();
+
+        let imported_store = InMemoryStore::new();
+        imported_store
+            .insert(store.get_range(11..=20).await.unwrap())
+            .await
+            .unwrap();
+
+        let imported = import_snapshot(&imported_store, &snapshot[..], None, false)
+            .await
+            .unwrap();
+        assert_eq!(imported, 10);
+        assert_eq!(
+            imported_store.get_stored_header_ranges().await.unwrap(),
+            new_block_ranges([1..=20])
+        );
+    }
+
+    #[async_test]
+    async fn import_rejects_another_chain() {
+        let (store, _gen) = gen_filled_store(10).await;
+
+        let mut snapshot = Vec::new();
+        export_snapshot(&store, &new_block_ranges([6..=10]), &mut snapshot)
+            .await
+            .unwrap();
+
+        // Store with headers of a different chain
+        let another_chain = ExtendedHeaderGenerator::new().next_many(5);
+        let imported_store = InMemoryStore::new();
+        imported_store.insert(another_chain).await.unwrap();
+
+        let trusted_hash = store.get_head().await.unwrap().hash();
+        let e = import_snapshot(&imported_store, &snapshot[..], Some(trusted_hash), false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            e,
+            SnapshotError::Store(StoreError::InsertionFailed(_))
+        ));
+    }
+
+    #[async_test]
+    async fn import_invalid_snapshot() {
+        let (store, _gen) = gen_filled_store(10).await;
+        let trusted_hash = store.get_head().await.unwrap().hash();
+
+        let mut snapshot = Vec::new();
+        export_snapshot(&store, &new_block_ranges([1..=10]), &mut snapshot)
+            .await
+            .unwrap();
+
+        // truncated
+        let e = import_snapshot(
+            &InMemoryStore::new(),
+            &snapshot[..snapshot.len() - 10],
+            Some(trusted_hash),
+            false,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(e, SnapshotError::Io(_)));
+
+        // unsupported version
+        let header = RawSnapshotHeader {
+            version: SNAPSHOT_VERSION + 1,
+            ranges: Vec::new(),
+        };
+        let snapshot = header.encode_length_delimited_to_vec();
+        let e = import_snapshot(&InMemoryStore::new(), &snapshot[..], None, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(e, SnapshotError::UnsupportedVersion(2)));
+
+        // empty
+        let e = import_snapshot(&InMemoryStore::new(), &[][..], None, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(e, SnapshotError::InvalidSnapshot(_)));
+    }
+}
@@ -4,10 +4,17 @@
 //! the latest header returned by at least two of them as the initial synchronization target
 //! called `subjective_head`.
 //!
+//! If a [`TrustedCheckpoint`] is configured, the header it points to is fetched first and
+//! the network head is accepted only if it can be verified against it. This protects
+//! the node from being fed a fake chain by malicious peers (weak subjectivity).
+//!
 //! Then it starts synchronizing from the genesis header up to the target requesting headers
 //! on the `header-ex` p2p protocol. In the meantime, it constantly checks for the latest
 //! headers announced on the `header-sub` p2p protocol to keep the `subjective_head` as close
 //! to the `network_head` as possible.
+//!
+//! Headers older than the syncing window are not synchronized, unless the archival mode
+//! is enabled. In that case, headers are backfilled all the way down to the genesis.
 
 use std::marker::PhantomData;
 use std::pin::pin;
@@ -17,10 +24,11 @@ use std::time::Duration;
 use backoff::backoff::Backoff;
 use backoff::ExponentialBackoffBuilder;
 use celestia_tendermint::Time;
+use celestia_types::hash::Hash;
 use celestia_types::ExtendedHeader;
 use serde::{Deserialize, Serialize};
 use tokio::select;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, instrument, warn};
 use web_time::Instant;
@@ -28,14 +36,18 @@ use web_time::Instant;
 use crate::block_ranges::{BlockRange, BlockRangeExt, BlockRanges};
 use crate::events::{EventPublisher, NodeEvent};
 use crate::executor::{sleep, spawn, Interval, JoinHandle};
+use crate::metrics::{Metrics, OpResult};
 use crate::p2p::{P2p, P2pError};
 use crate::store::{Store, StoreError};
 use crate::utils::{FusedReusableFuture, OneshotSenderExt};
 
 type Result<T, E = SyncerError> = std::result::Result<T, E>;
 
+const TRY_INIT_BACKOFF_INITIAL_INTERVAL: Duration = Duration::from_secs(1);
 const TRY_INIT_BACKOFF_MAX_INTERVAL: Duration = Duration::from_secs(60);
-pub const SYNCING_WINDOW: Duration = Duration::from_secs(30 * 24 * 60 * 60); // 30 days
+const NEW_HEADS_CHANNEL_CAPACITY: usize = 128;
+/// Default syncing window, headers older than that are not synced.
+pub const DEFAULT_SYNCING_WINDOW: Duration = Duration::from_secs(30 * 24 * 60 * 60); // 30 days
 
 /// Representation of all the errors that can occur in `Syncer` component.
 #[derive(Debug, thiserror::Error)]
@@ -55,6 +67,19 @@ pub enum SyncerError {
     /// Channel closed unexpectedly.
     #[error("Channel closed unexpectedly")]
     ChannelClosedUnexpectedly,
+
+    /// Header received for the trusted checkpoint has a different hash.
+    #[error("Trusted header hash mismatch: expected {expected}, got {received}")]
+    TrustedHeaderHashMismatch {
+        /// Hash of the trusted checkpoint.
+        expected: Hash,
+        /// Hash of the received header.
+        received: Hash,
+    },
+
+    /// Network head could not be verified against the trusted header.
+    #[error("Network head verification against trusted header failed: {0}")]
+    NetworkHeadVerification(celestia_types::Error),
 }
 
 impl SyncerError {
@@ -63,6 +88,8 @@ impl SyncerError {
             SyncerError::P2p(e) => e.is_fatal(),
             SyncerError::Store(e) => e.is_fatal(),
             SyncerError::WorkerDied | SyncerError::ChannelClosedUnexpectedly => true,
+            SyncerError::TrustedHeaderHashMismatch { .. }
+            | SyncerError::NetworkHeadVerification(_) => false,
         }
     }
 }
@@ -80,6 +107,7 @@ where
     S: Store + 'static,
 {
     cmd_tx: mpsc::Sender<SyncerCmd>,
+    new_heads_tx: broadcast::Sender<ExtendedHeader>,
     cancellation_token: CancellationToken,
     join_handle: JoinHandle,
     _store: PhantomData<S>,
@@ -98,6 +126,14 @@ where
     pub(crate) event_pub: EventPublisher,
     /// Batch size.
     pub(crate) batch_size: u64,
+    /// Optional checkpoint used as the root of trust for the network head.
+    pub(crate) trusted_checkpoint: Option<TrustedCheckpoint>,
+    /// Headers older than this window are not synced.
+    pub(crate) syncing_window: Duration,
+    /// Ignore the syncing window and backfill headers down to the genesis.
+    pub(crate) archival: bool,
+    /// Metrics of the node.
+    pub(crate) metrics: Arc<Metrics>,
 }
 
 #[derive(Debug)]
@@ -107,6 +143,38 @@ enum SyncerCmd {
     },
 }
 
+/// A header that is trusted a priori and used as the root of trust when initializing
+/// the syncing process.
+///
+/// The network head reported by peers is accepted only if it can be verified against
+/// this header with [`ExtendedHeader::verify`]. Because of that, the checkpoint needs to
+/// be within the trusting period of the network, otherwise validators set may have
+/// changed too much for the verification to succeed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustedCheckpoint {
+    /// Hash of the trusted header.
+    pub hash: Hash,
+    /// Height of the trusted header.
+    ///
+    /// If set, the header is requested by its height and then compared against `hash`.
+    pub height: Option<u64>,
+}
+
+impl TrustedCheckpoint {
+    /// Create a checkpoint from the hash of a trusted header.
+    pub fn new(hash: Hash) -> Self {
+        TrustedCheckpoint { hash, height: None }
+    }
+
+    /// Create a checkpoint from the height and hash of a trusted header.
+    pub fn with_height(height: u64, hash: Hash) -> Self {
+        TrustedCheckpoint {
+            hash,
+            height: Some(height),
+        }
+    }
+}
+
 /// Status of the synchronization.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SyncingInfo {
@@ -114,6 +182,10 @@ pub struct SyncingInfo {
     pub stored_headers: BlockRanges,
     /// Syncing target. The latest height seen in the network that was successfully verified.
     pub subjective_head: u64,
+    /// Number of headers missing between the genesis and the store's head.
+    ///
+    /// Reported only in archival mode.
+    pub backfill_remaining: Option<u64>,
 }
 
 impl<S> Syncer<S>
@@ -125,7 +197,13 @@ where
         let cancellation_token = CancellationToken::new();
         let event_pub = args.event_pub.clone();
         let (cmd_tx, cmd_rx) = mpsc::channel(16);
-        let mut worker = Worker::new(args, cancellation_token.child_token(), cmd_rx)?;
+        let (new_heads_tx, _) = broadcast::channel(NEW_HEADS_CHANNEL_CAPACITY);
+        let mut worker = Worker::new(
+            args,
+            cancellation_token.child_token(),
+            cmd_rx,
+            new_heads_tx.clone(),
+        )?;
 
         let join_handle = spawn(async move {
             if let Err(e) = worker.run().await {
@@ -140,6 +218,7 @@ where
         Ok(Syncer {
             cancellation_token,
             cmd_tx,
+            new_heads_tx,
             join_handle,
             _store: PhantomData,
         })
@@ -176,6 +255,13 @@ where
 
         Ok(rx.await?)
     }
+
+    /// Subscribe to the headers appended on top of the store's head.
+    ///
+    /// Only headers received after subscribing are delivered.
+    pub(crate) fn subscribe_new_heads(&self) -> broadcast::Receiver<ExtendedHeader> {
+        self.new_heads_tx.subscribe()
+    }
 }
 
 impl<S> Drop for Syncer<S>
@@ -193,12 +279,17 @@ where
 {
     cancellation_token: CancellationToken,
     cmd_rx: mpsc::Receiver<SyncerCmd>,
+    new_heads_tx: broadcast::Sender<ExtendedHeader>,
     event_pub: EventPublisher,
     p2p: Arc<P2p>,
     store: Arc<S>,
     header_sub_rx: Option<mpsc::Receiver<ExtendedHeader>>,
     subjective_head_height: Option<u64>,
     batch_size: u64,
+    trusted_checkpoint: Option<TrustedCheckpoint>,
+    syncing_window: Duration,
+    archival: bool,
+    metrics: Arc<Metrics>,
     ongoing_batch: Ongoing,
 }
 
@@ -215,16 +306,22 @@ where
         args: SyncerArgs<S>,
         cancellation_token: CancellationToken,
         cmd_rx: mpsc::Receiver<SyncerCmd>,
+        new_heads_tx: broadcast::Sender<ExtendedHeader>,
     ) -> Result<Self> {
         Ok(Worker {
             cancellation_token,
             cmd_rx,
+            new_heads_tx,
             event_pub: args.event_pub,
             p2p: args.p2p,
             store: args.store,
             header_sub_rx: None,
             subjective_head_height: None,
             batch_size: args.batch_size,
+            trusted_checkpoint: args.trusted_checkpoint,
+            syncing_window: args.syncing_window,
+            archival: args.archival,
+            metrics: args.metrics,
             ongoing_batch: Ongoing {
                 range: None,
                 task: FusedReusableFuture::terminated(),
@@ -264,7 +361,8 @@ where
         let mut try_init_fut = pin!(try_init_task(
             self.p2p.clone(),
             self.store.clone(),
-            self.event_pub.clone()
+            self.event_pub.clone(),
+            self.trusted_checkpoint,
         ));
 
         loop {
@@ -283,6 +381,9 @@ where
                     info!("Setting initial subjective head to {network_head_height}");
                     self.set_subjective_head_height(network_head_height);
 
+                    // Initial head is the first new head for the subscribers
+                    let _ = self.new_heads_tx.send(network_head.clone());
+
                     let (header_sub_tx, header_sub_rx) = mpsc::channel(16);
                     self.p2p.init_header_sub(network_head, header_sub_tx).await?;
                     self.header_sub_rx = Some(header_sub_rx);
@@ -362,9 +463,13 @@ where
     }
 
     async fn syncing_info(&self) -> Result<SyncingInfo> {
+        let stored_headers = self.store.get_stored_header_ranges().await?;
+        let backfill_remaining = self.archival.then(|| backfill_remaining(&stored_headers));
+
         Ok(SyncingInfo {
-            stored_headers: self.store.get_stored_header_ranges().await?,
+            stored_headers,
             subjective_head: self.subjective_head_height.unwrap_or(0),
+            backfill_remaining,
         })
     }
 
@@ -373,6 +478,7 @@ where
         let SyncingInfo {
             stored_headers,
             subjective_head,
+            ..
         } = self.syncing_info().await?;
 
         let ongoing_batch = self
@@ -408,10 +514,12 @@ where
             if store_head_height + 1 == new_head_height {
                 // Header is already verified by HeaderSub and will be validated against previous
                 // head on insert
-                if self.store.insert(new_head).await.is_ok() {
+                if self.store.insert(new_head.clone()).await.is_ok() {
                     self.event_pub.send(NodeEvent::AddedHeaderFromHeaderSub {
                         height: new_head_height,
                     });
+                    // Error is produced only if there aren't any subscribers
+                    let _ = self.new_heads_tx.send(new_head);
                 }
             }
         }
@@ -470,14 +578,16 @@ where
         }
 
         // make sure we're inside the syncing window before we start
-        match self.store.get_by_height(next_batch.end() + 1).await {
-            Ok(known_header) => {
-                if !in_syncing_window(&known_header) {
-                    return Ok(());
+        if !self.archival {
+            match self.store.get_by_height(next_batch.end() + 1).await {
+                Ok(known_header) => {
+                    if !in_syncing_window(&known_header, self.syncing_window) {
+                        return Ok(());
+                    }
                 }
+                Err(StoreError::NotFound) => {}
+                Err(e) => return Err(e.into()),
             }
-            Err(StoreError::NotFound) => {}
-            Err(e) => return Err(e.into()),
         }
 
         self.event_pub.send(NodeEvent::FetchingHeadersStarted {
@@ -515,12 +625,17 @@ where
         let to_height = *range.end();
 
         let headers = match res {
-            Ok(headers) => headers,
+            Ok(headers) => {
+                self.metrics.syncer_batch(OpResult::Success, took);
+                headers
+            }
             Err(e) => {
                 if e.is_fatal() {
                     return Err(e.into());
                 }
 
+                self.metrics.syncer_batch(OpResult::Failure, took);
+
                 self.event_pub.send(NodeEvent::FetchingHeadersFailed {
                     from_height,
                     to_height,
@@ -532,6 +647,16 @@ where
             }
         };
 
+        let store_head_height = self.store.head_height().await.ok();
+
+        // Headers extending the store's head are new heads for the subscribers
+        let new_heads = match store_head_height {
+            Some(head) if from_height > head && self.new_heads_tx.receiver_count() > 0 => {
+                headers.clone()
+            }
+            _ => Vec::new(),
+        };
+
         if let Err(e) = self.store.insert(headers).await {
             if e.is_fatal() {
                 return Err(e.into());
@@ -543,6 +668,10 @@ where
                 error: format!("Failed to store headers: {e}"),
                 took,
             });
+        } else {
+            for header in new_heads {
+                let _ = self.new_heads_tx.send(header);
+            }
         }
 
         self.event_pub.send(NodeEvent::FetchingHeadersFinished {
@@ -551,10 +680,46 @@ where
             took,
         });
 
+        // Batches below the store's head are backfilling the history
+        let is_backfill = store_head_height.is_some_and(|head| to_height < head);
+
+        if self.archival && is_backfill {
+            self.report_backfill_progress().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn report_backfill_progress(&mut self) -> Result<()> {
+        let stored_headers = self.store.get_stored_header_ranges().await?;
+        let remaining = backfill_remaining(&stored_headers);
+
+        if remaining == 0 {
+            info!("Archival backfill reached the genesis");
+            self.event_pub.send(NodeEvent::ArchivalBackfillFinished);
+        } else {
+            let lowest_height = stored_headers
+                .as_ref()
+                .last()
+                .map(|range| *range.start())
+                .unwrap_or(0);
+
+            self.event_pub.send(NodeEvent::ArchivalBackfillProgress {
+                lowest_height,
+                remaining,
+            });
+        }
+
         Ok(())
     }
 }
 
+/// Number of headers missing between the genesis and the head of the stored headers.
+fn backfill_remaining(stored_headers: &BlockRanges) -> u64 {
+    let head = stored_headers.head().unwrap_or(0);
+    head - stored_headers.len()
+}
+
 /// based on the stored headers and current network head height, calculate range of headers that
 /// should be fetched from the network, anchored on already existing header range in store
 fn calculate_range_to_fetch(
@@ -583,8 +748,8 @@ fn calculate_range_to_fetch(
     range.truncate_left(limit)
 }
 
-fn in_syncing_window(header: &ExtendedHeader) -> bool {
-    let syncing_window_start = Time::now().checked_sub(SYNCING_WINDOW).unwrap_or_else(|| {
+pub(crate) fn in_syncing_window(header: &ExtendedHeader, syncing_window: Duration) -> bool {
+    let syncing_window_start = Time::now().checked_sub(syncing_window).unwrap_or_else(|| {
         warn!("underflow when computing syncing window start, defaulting to unix epoch");
         Time::unix_epoch()
     });
@@ -597,6 +762,7 @@ async fn try_init_task<S>(
     p2p: Arc<P2p>,
     store: Arc<S>,
     event_pub: EventPublisher,
+    trusted_checkpoint: Option<TrustedCheckpoint>,
 ) -> Result<(ExtendedHeader, Duration)>
 where
     S: Store + 'static,
@@ -604,12 +770,21 @@ where
     let now = Instant::now();
     let mut event_reported = false;
     let mut backoff = ExponentialBackoffBuilder::default()
+        .with_initial_interval(TRY_INIT_BACKOFF_INITIAL_INTERVAL)
         .with_max_interval(TRY_INIT_BACKOFF_MAX_INTERVAL)
         .with_max_elapsed_time(None)
         .build();
 
     loop {
-        match try_init(&p2p, &*store, &event_pub, &mut event_reported).await {
+        match try_init(
+            &p2p,
+            &*store,
+            &event_pub,
+            trusted_checkpoint.as_ref(),
+            &mut event_reported,
+        )
+        .await
+        {
             Ok(network_head) => {
                 return Ok((network_head, now.elapsed()));
             }
@@ -634,6 +809,7 @@ async fn try_init<S>(
     p2p: &P2p,
     store: &S,
     event_pub: &EventPublisher,
+    trusted_checkpoint: Option<&TrustedCheckpoint>,
     event_reported: &mut bool,
 ) -> Result<ExtendedHeader>
 where
@@ -646,8 +822,23 @@ where
         *event_reported = true;
     }
 
+    let trusted_header = match trusted_checkpoint {
+        Some(checkpoint) => Some(get_trusted_header(p2p, store, checkpoint).await?),
+        None => None,
+    };
+
     let network_head = p2p.get_head_header().await?;
 
+    if let Some(trusted_header) = trusted_header {
+        // Network head can be the trusted header itself, in which
+        // case there is nothing to verify.
+        if trusted_header.hash() != network_head.hash() {
+            trusted_header
+                .verify(&network_head)
+                .map_err(SyncerError::NetworkHeadVerification)?;
+        }
+    }
+
     // If the network head and the store head have the same height,
     // then `insert` will error because of insertion contraints.
     // However, if both headers are the exactly the same, we
@@ -671,6 +862,37 @@ where
     Ok(network_head)
 }
 
+/// Get the header pointed by the trusted checkpoint, either from the store or
+/// from the network.
+async fn get_trusted_header<S>(
+    p2p: &P2p,
+    store: &S,
+    checkpoint: &TrustedCheckpoint,
+) -> Result<ExtendedHeader>
+where
+    S: Store,
+{
+    match store.get_by_hash(&checkpoint.hash).await {
+        Ok(header) => return Ok(header),
+        Err(StoreError::NotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let header = match checkpoint.height {
+        Some(height) => p2p.get_header_by_height(height).await?,
+        None => p2p.get_header(checkpoint.hash).await?,
+    };
+
+    if header.hash() != checkpoint.hash {
+        return Err(SyncerError::TrustedHeaderHashMismatch {
+            expected: checkpoint.hash,
+            received: header.hash(),
+        });
+    }
+
+    Ok(header)
+}
+
 async fn header_sub_recv(
     rx: Option<&mut mpsc::Receiver<ExtendedHeader>>,
 ) -> Result<ExtendedHeader> {
@@ -778,6 +1000,10 @@ mod tests {
             store: Arc::new(InMemoryStore::new()),
             event_pub: events.publisher(),
             batch_size: 512,
+            trusted_checkpoint: None,
+            syncing_window: DEFAULT_SYNCING_WINDOW,
+            archival: false,
+            metrics: Arc::default(),
         })
         .unwrap();
 
@@ -812,6 +1038,144 @@ mod tests {
         p2p_mock.expect_no_cmd().await;
     }
 
+    #[async_test]
+    async fn init_with_trusted_checkpoint() {
+        let events = EventChannel::new();
+        let (mock, mut handle) = P2p::mocked();
+        let mut gen = ExtendedHeaderGenerator::new();
+        let headers = gen.next_many(30);
+        let trusted = headers[9].clone();
+        let network_head = headers[29].clone();
+
+        let _syncer = Syncer::start(SyncerArgs {
+            p2p: Arc::new(mock),
+            store: Arc::new(InMemoryStore::new()),
+            event_pub: events.publisher(),
+            batch_size: 512,
+            trusted_checkpoint: Some(TrustedCheckpoint::new(trusted.hash())),
+            syncing_window: DEFAULT_SYNCING_WINDOW,
+            archival: false,
+            metrics: Arc::default(),
+        })
+        .unwrap();
+
+        handle.announce_trusted_peer_connected();
+
+        // Syncer asks for the trusted header first
+        let (hash, respond_to) = handle.expect_header_request_for_hash_cmd().await;
+        assert_eq!(hash, trusted.hash());
+        respond_to.send(Ok(vec![trusted.clone()])).unwrap();
+
+        // Then for the network head
+        let (height, amount, respond_to) = handle.expect_header_request_for_height_cmd().await;
+        assert_eq!(height, 0);
+        assert_eq!(amount, 1);
+        respond_to.send(Ok(vec![network_head.clone()])).unwrap();
+
+        // Network head was verified, so HeaderSub is initialized with it
+        let head_from_syncer = handle.expect_init_header_sub().await;
+        assert_eq!(head_from_syncer, network_head);
+    }
+
+    #[async_test]
+    async fn init_with_trusted_checkpoint_height() {
+        // Paused clock makes the retry backoff elapse deterministically.
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::time::pause();
+
+        let events = EventChannel::new();
+        let (mock, mut handle) = P2p::mocked();
+        let mut gen = ExtendedHeaderGenerator::new();
+        let headers = gen.next_many(30);
+        let trusted = headers[9].clone();
+        let network_head = headers[29].clone();
+
+        let _syncer = Syncer::start(SyncerArgs {
+            p2p: Arc::new(mock),
+            store: Arc::new(InMemoryStore::new()),
+            event_pub: events.publisher(),
+            batch_size: 512,
+            trusted_checkpoint: Some(TrustedCheckpoint::with_height(10, trusted.hash())),
+            syncing_window: DEFAULT_SYNCING_WINDOW,
+            archival: false,
+            metrics: Arc::default(),
+        })
+        .unwrap();
+
+        handle.announce_trusted_peer_connected();
+
+        // Peer responds with a header of a different hash. Syncer should reject it.
+        let (height, amount, respond_to) = handle.expect_header_request_for_height_cmd().await;
+        assert_eq!(height, 10);
+        assert_eq!(amount, 1);
+        respond_to.send(Ok(vec![headers[10].clone()])).unwrap();
+        handle.expect_no_cmd().await;
+
+        // Syncer will request the trusted header again after the backoff.
+        sleep(TRY_INIT_BACKOFF_INITIAL_INTERVAL * 2).await;
+        let (height, amount, respond_to) = handle.expect_header_request_for_height_cmd().await;
+        assert_eq!(height, 10);
+        assert_eq!(amount, 1);
+        respond_to.send(Ok(vec![trusted.clone()])).unwrap();
+
+        let (height, amount, respond_to) = handle.expect_header_request_for_height_cmd().await;
+        assert_eq!(height, 0);
+        assert_eq!(amount, 1);
+        respond_to.send(Ok(vec![network_head.clone()])).unwrap();
+
+        let head_from_syncer = handle.expect_init_header_sub().await;
+        assert_eq!(head_from_syncer, network_head);
+    }
+
+    #[async_test]
+    async fn init_with_trusted_checkpoint_rejects_another_chain() {
+        // Paused clock makes the retry backoff elapse deterministically.
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::time::pause();
+
+        let events = EventChannel::new();
+        let (mock, mut handle) = P2p::mocked();
+        let store = Arc::new(InMemoryStore::new());
+        let headers = ExtendedHeaderGenerator::new().next_many(30);
+        let headers_prime = ExtendedHeaderGenerator::new().next_many(30);
+        let trusted = headers[9].clone();
+
+        let syncer = Syncer::start(SyncerArgs {
+            p2p: Arc::new(mock),
+            store: store.clone(),
+            event_pub: events.publisher(),
+            batch_size: 512,
+            trusted_checkpoint: Some(TrustedCheckpoint::new(trusted.hash())),
+            syncing_window: DEFAULT_SYNCING_WINDOW,
+            archival: false,
+            metrics: Arc::default(),
+        })
+        .unwrap();
+
+        handle.announce_trusted_peer_connected();
+
+        let (hash, respond_to) = handle.expect_header_request_for_hash_cmd().await;
+        assert_eq!(hash, trusted.hash());
+        respond_to.send(Ok(vec![trusted.clone()])).unwrap();
+
+        // Network head from another chain can't be verified with the trusted header.
+        let (height, amount, respond_to) = handle.expect_header_request_for_height_cmd().await;
+        assert_eq!(height, 0);
+        assert_eq!(amount, 1);
+        respond_to
+            .send(Ok(vec![headers_prime[29].clone()]))
+            .unwrap();
+
+        // Nothing was inserted in the store and initialization is retried
+        // from the trusted header, without initializing HeaderSub.
+        handle.expect_no_cmd().await;
+        sleep(TRY_INIT_BACKOFF_INITIAL_INTERVAL * 2).await;
+        let (hash, _respond_to) = handle.expect_header_request_for_hash_cmd().await;
+        assert_eq!(hash, trusted.hash());
+        assert!(store.get_head().await.is_err());
+        assert_eq!(syncer.info().await.unwrap().subjective_head, 0);
+    }
+
     #[async_test]
     async fn syncing() {
         let mut gen = ExtendedHeaderGenerator::new();
@@ -883,6 +1247,63 @@ mod tests {
         p2p_mock.expect_no_cmd().await;
     }
 
+    #[async_test]
+    async fn new_heads_subscription() {
+        let mut gen = ExtendedHeaderGenerator::new();
+        let headers = gen.next_many(25);
+
+        let (syncer, store, mut p2p_mock) = initialized_syncer(headers[24].clone()).await;
+        let mut new_heads = syncer.subscribe_new_heads();
+
+        // Syncing backwards doesn't produce new heads
+        handle_session_batch(&mut p2p_mock, &headers, 1..=24, true).await;
+        assert_syncing(&syncer, &store, &[1..=25], 25).await;
+        assert!(new_heads.try_recv().is_err());
+
+        // Header adjacent to the store's head
+        let header26 = gen.next();
+        p2p_mock.announce_new_head(header26.clone());
+        assert_eq!(new_heads.recv().await.unwrap(), header26);
+
+        // Headers fetched on top of the store's head
+        let headers_27_29 = gen.next_many(3);
+        p2p_mock.announce_new_head(headers_27_29[2].clone());
+        handle_session_batch(&mut p2p_mock, &headers_27_29, 27..=29, true).await;
+        assert_syncing(&syncer, &store, &[1..=29], 29).await;
+
+        for header in headers_27_29 {
+            assert_eq!(new_heads.recv().await.unwrap(), header);
+        }
+        assert!(new_heads.try_recv().is_err());
+    }
+
+    #[async_test]
+    async fn new_heads_subscription_starts_with_initial_head() {
+        let head = ExtendedHeaderGenerator::new().next_many(5).pop().unwrap();
+        let (mock, mut handle) = P2p::mocked();
+
+        let syncer = Syncer::start(SyncerArgs {
+            p2p: Arc::new(mock),
+            store: Arc::new(InMemoryStore::new()),
+            event_pub: EventChannel::new().publisher(),
+            batch_size: 512,
+            trusted_checkpoint: None,
+            syncing_window: DEFAULT_SYNCING_WINDOW,
+            archival: false,
+            metrics: Arc::default(),
+        })
+        .unwrap();
+        let mut new_heads = syncer.subscribe_new_heads();
+
+        handle.announce_trusted_peer_connected();
+        let (height, amount, respond_to) = handle.expect_header_request_for_height_cmd().await;
+        assert_eq!((height, amount), (0, 1));
+        respond_to.send(Ok(vec![head.clone()])).unwrap();
+        handle.expect_init_header_sub().await;
+
+        assert_eq!(new_heads.recv().await.unwrap(), head);
+    }
+
     #[async_test]
     async fn window_edge() {
         let month_and_day_ago = Duration::from_secs(31 * 24 * 60 * 60);
@@ -910,6 +1331,126 @@ mod tests {
         p2p_mock.expect_no_cmd().await;
     }
 
+    #[async_test]
+    async fn custom_window_edge() {
+        let two_days_ago = Duration::from_secs(2 * 24 * 60 * 60);
+        let mut gen = ExtendedHeaderGenerator::new();
+        gen.set_time(
+            (Time::now() - two_days_ago).expect("to not underflow"),
+            Duration::from_secs(1),
+        );
+        let mut headers = gen.next_many(1200);
+        gen.reset_time();
+        headers.append(&mut gen.next_many(2049 - 1200));
+
+        let one_day = Duration::from_secs(24 * 60 * 60);
+        let (syncer, store, mut p2p_mock) =
+            initialized_syncer_with_window(headers[2048].clone(), one_day).await;
+        assert_syncing(&syncer, &store, &[2049..=2049], 2049).await;
+
+        // Syncer requested the first batch
+        handle_session_batch(&mut p2p_mock, &headers, 1537..=2048, true).await;
+        assert_syncing(&syncer, &store, &[1537..=2049], 2049).await;
+
+        // Syncer requested the second batch hitting the syncing window
+        handle_session_batch(&mut p2p_mock, &headers, 1025..=1536, true).await;
+        assert_syncing(&syncer, &store, &[1025..=2049], 2049).await;
+
+        // Syncer is fully synced and awaiting for events
+        p2p_mock.expect_no_cmd().await;
+    }
+
+    #[async_test]
+    async fn archival_backfills_to_genesis() {
+        let month_and_day_ago = Duration::from_secs(31 * 24 * 60 * 60);
+        let mut gen = ExtendedHeaderGenerator::new();
+        gen.set_time(
+            (Time::now() - month_and_day_ago).expect("to not underflow"),
+            Duration::from_secs(1),
+        );
+        let mut headers = gen.next_many(1200);
+        gen.reset_time();
+        headers.append(&mut gen.next_many(2049 - 1200));
+
+        let events = EventChannel::new();
+        let mut event_sub = events.subscribe();
+        let store = Arc::new(InMemoryStore::new());
+        let (syncer, store, mut p2p_mock) = initialized_syncer_with_args(
+            headers[2048].clone(),
+            store,
+            events,
+            DEFAULT_SYNCING_WINDOW,
+            true,
+        )
+        .await;
+        assert_eq!(syncer.info().await.unwrap().backfill_remaining, Some(2048));
+
+        // Syncer doesn't stop at the edge of the syncing window
+        handle_session_batch(&mut p2p_mock, &headers, 1537..=2048, true).await;
+        handle_session_batch(&mut p2p_mock, &headers, 1025..=1536, true).await;
+        handle_session_batch(&mut p2p_mock, &headers, 513..=1024, true).await;
+        assert_syncing(&syncer, &store, &[513..=2049], 2049).await;
+        assert_eq!(syncer.info().await.unwrap().backfill_remaining, Some(512));
+
+        handle_session_batch(&mut p2p_mock, &headers, 1..=512, true).await;
+        assert_syncing(&syncer, &store, &[1..=2049], 2049).await;
+        assert_eq!(syncer.info().await.unwrap().backfill_remaining, Some(0));
+
+        p2p_mock.expect_no_cmd().await;
+
+        let mut progress = Vec::new();
+        let mut finished = 0;
+
+        while let Ok(ev) = event_sub.try_recv() {
+            match ev.event {
+                NodeEvent::ArchivalBackfillProgress {
+                    lowest_height,
+                    remaining,
+                } => progress.push((lowest_height, remaining)),
+                NodeEvent::ArchivalBackfillFinished => finished += 1,
+                _ => {}
+            }
+        }
+
+        assert_eq!(progress, [(1537, 1536), (1025, 1024), (513, 512)]);
+        assert_eq!(finished, 1);
+    }
+
+    #[async_test]
+    async fn archival_resumes_from_stored_ranges() {
+        let month_and_day_ago = Duration::from_secs(31 * 24 * 60 * 60);
+        let mut gen = ExtendedHeaderGenerator::new();
+        gen.set_time(
+            (Time::now() - month_and_day_ago).expect("to not underflow"),
+            Duration::from_secs(1),
+        );
+        let headers = gen.next_many(1100);
+
+        // Store left by the previous run: the oldest headers are already there
+        // and the head range was partially backfilled.
+        let store = Arc::new(InMemoryStore::new());
+        store.insert(headers[..100].to_vec()).await.unwrap();
+        store.insert(headers[600..1099].to_vec()).await.unwrap();
+
+        let (syncer, store, mut p2p_mock) = initialized_syncer_with_args(
+            headers[1099].clone(),
+            store,
+            EventChannel::new(),
+            DEFAULT_SYNCING_WINDOW,
+            true,
+        )
+        .await;
+        assert_syncing(&syncer, &store, &[1..=100, 601..=1100], 1100).await;
+        assert_eq!(syncer.info().await.unwrap().backfill_remaining, Some(500));
+
+        // Only the gap between the stored ranges is fetched
+        handle_session_batch(&mut p2p_mock, &headers, 101..=600, true).await;
+        assert_syncing(&syncer, &store, &[1..=1100], 1100).await;
+        assert_eq!(syncer.info().await.unwrap().backfill_remaining, Some(0));
+
+        p2p_mock.expect_no_cmd().await;
+    }
+
     #[async_test]
     async fn start_with_filled_store() {
         let events = EventChannel::new();
@@ -925,6 +1466,10 @@ mod tests {
             store: store.clone(),
             event_pub: events.publisher(),
             batch_size: 512,
+            trusted_checkpoint: None,
+            syncing_window: DEFAULT_SYNCING_WINDOW,
+            archival: false,
+            metrics: Arc::default(),
         })
         .unwrap();
 
@@ -981,6 +1526,10 @@ mod tests {
 
     #[async_test]
     async fn all_peers_disconnected() {
+        // Paused clock makes the retry backoff elapse deterministically.
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::time::pause();
+
         let mut gen = ExtendedHeaderGenerator::new();
 
         let _gap = gen.next_many(24);
@@ -1017,8 +1566,8 @@ mod tests {
         respond_to.send(Ok(vec![header25])).unwrap();
         assert_syncing(&syncer, &store, &[30..=30], 30).await;
 
-        // Syncer will request HEAD again after some time.
-        sleep(Duration::from_secs(1)).await;
+        // Syncer will request HEAD again after the backoff.
+        sleep(TRY_INIT_BACKOFF_INITIAL_INTERVAL * 2).await;
         let (height, amount, respond_to) = p2p_mock.expect_header_request_for_height_cmd().await;
         assert_eq!(height, 0);
         assert_eq!(amount, 1);
@@ -1154,16 +1703,38 @@ mod tests {
 
     async fn initialized_syncer(
         head: ExtendedHeader,
+    ) -> (Syncer<InMemoryStore>, Arc<InMemoryStore>, MockP2pHandle) {
+        initialized_syncer_with_window(head, DEFAULT_SYNCING_WINDOW).await
+    }
+
+    async fn initialized_syncer_with_window(
+        head: ExtendedHeader,
+        syncing_window: Duration,
     ) -> (Syncer<InMemoryStore>, Arc<InMemoryStore>, MockP2pHandle) {
         let events = EventChannel::new();
-        let (mock, mut handle) = P2p::mocked();
         let store = Arc::new(InMemoryStore::new());
+        initialized_syncer_with_args(head, store, events, syncing_window, false).await
+    }
+
+    async fn initialized_syncer_with_args(
+        head: ExtendedHeader,
+        store: Arc<InMemoryStore>,
+        events: EventChannel,
+        syncing_window: Duration,
+        archival: bool,
+    ) -> (Syncer<InMemoryStore>, Arc<InMemoryStore>, MockP2pHandle) {
+        let (mock, mut handle) = P2p::mocked();
+        let stored_before = store.get_stored_header_ranges().await.unwrap();
 
         let syncer = Syncer::start(SyncerArgs {
             p2p: Arc::new(mock),
             store: store.clone(),
             event_pub: events.publisher(),
             batch_size: 512,
+            trusted_checkpoint: None,
+            syncing_window,
+            archival,
+            metrics: Arc::default(),
         })
         .unwrap();
 
@@ -1184,7 +1755,10 @@ mod tests {
         assert_eq!(head_from_syncer, head);
 
         let head_height = head.height().value();
-        assert_syncing(&syncer, &store, &[head_height..=head_height], head_height).await;
+        let mut expected = stored_before;
+        expected.insert_relaxed(head_height..=head_height).unwrap();
+        let expected: Vec<_> = expected.into_inner().into_iter().collect();
+        assert_syncing(&syncer, &store, &expected, head_height).await;
 
         (syncer, store, handle)
     }
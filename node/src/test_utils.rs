@@ -3,18 +3,22 @@
 use std::time::Duration;
 
 use celestia_proto::p2p::pb::{header_request::Data, HeaderRequest};
+use celestia_proto::share::p2p::shrex::nd::{GetSharesByNamespaceRequest, NamespaceRowResponse};
+use celestia_proto::EdsRequest;
 use celestia_types::hash::Hash;
 use celestia_types::test_utils::ExtendedHeaderGenerator;
 use celestia_types::ExtendedHeader;
 use cid::Cid;
 use libp2p::identity::{self, Keypair};
+use libp2p::PeerId;
 use tokio::sync::{mpsc, watch};
 
 use crate::{
     block_ranges::{BlockRange, BlockRanges},
     blockstore::InMemoryBlockstore,
     executor::timeout,
-    node::NodeConfig,
+    network::Network,
+    node::{ConnectionLimits, NodeConfig, DEFAULT_MAX_SAMPLES_NEEDED, DEFAULT_PEER_BAN_DURATION},
     p2p::{P2pCmd, P2pError},
     peer_tracker::PeerTrackerInfo,
     store::{InMemoryStore, VerifiedExtendedHeaders},
@@ -51,11 +55,21 @@ pub fn new_block_ranges<const N: usize>(ranges: [BlockRange; N]) -> BlockRanges
 pub fn test_node_config() -> NodeConfig<InMemoryBlockstore, InMemoryStore> {
     let node_keypair = identity::Keypair::generate_ed25519();
     NodeConfig {
-        network_id: "private".to_string(),
+        network: Network::Private.into(),
         p2p_local_keypair: node_keypair,
         p2p_bootnodes: vec![],
         p2p_listen_on: vec![],
+        p2p_peer_ban_duration: DEFAULT_PEER_BAN_DURATION,
+        p2p_connection_limits: ConnectionLimits::default(),
         sync_batch_size: 512,
+        sync_trusted_checkpoint: None,
+        sync_window: None,
+        sync_archival: false,
+        sampling_window: None,
+        sampling_max_samples_needed: DEFAULT_MAX_SAMPLES_NEEDED,
+        sampling_full_storage: false,
+        pruning_window: None,
+        pruning_disabled: false,
         blockstore: InMemoryBlockstore::new(),
         store: InMemoryStore::new(),
     }
@@ -227,8 +241,56 @@ impl MockP2pHandle {
     /// [`P2p`]: crate::p2p::P2p
     pub async fn expect_get_shwap_cid(&mut self) -> (Cid, OneshotResultSender<Vec<u8>, P2pError>) {
         match self.expect_cmd().await {
-            P2pCmd::GetShwapCid { cid, respond_to } => (cid, respond_to),
+            P2pCmd::GetShwapCid {
+                cid, respond_to, ..
+            } => (cid, respond_to),
             cmd => panic!("Expecting GetShwapCid, but received: {cmd:?}"),
         }
     }
+
+    /// Assert that a shrex/nd request was sent to the [`P2p`] worker and obtain a response channel.
+    ///
+    /// [`P2p`]: crate::p2p::P2p
+    pub async fn expect_shrex_nd_request(
+        &mut self,
+    ) -> (
+        GetSharesByNamespaceRequest,
+        OneshotResultSender<(PeerId, Vec<NamespaceRowResponse>), P2pError>,
+    ) {
+        match self.expect_cmd().await {
+            P2pCmd::ShrexNdRequest {
+                request,
+                respond_to,
+            } => (request, respond_to),
+            cmd => panic!("Expecting ShrexNdRequest, but received: {cmd:?}"),
+        }
+    }
+
+    /// Assert that a shrex/eds request was sent to the [`P2p`] worker and obtain a response channel.
+    ///
+    /// [`P2p`]: crate::p2p::P2p
+    pub async fn expect_shrex_eds_request(
+        &mut self,
+    ) -> (
+        EdsRequest,
+        OneshotResultSender<(PeerId, Vec<Vec<u8>>), P2pError>,
+    ) {
+        match self.expect_cmd().await {
+            P2pCmd::ShrexEdsRequest {
+                request,
+                respond_to,
+            } => (request, respond_to),
+            cmd => panic!("Expecting ShrexEdsRequest, but received: {cmd:?}"),
+        }
+    }
+
+    /// Assert that a peer was reported to the [`P2p`] worker for an invalid shrex response.
+    ///
+    /// [`P2p`]: crate::p2p::P2p
+    pub async fn expect_shrex_invalid_response(&mut self) -> PeerId {
+        match self.expect_cmd().await {
+            P2pCmd::ShrexInvalidResponse { peer } => peer,
+            cmd => panic!("Expecting ShrexInvalidResponse, but received: {cmd:?}"),
+        }
+    }
 }
@@ -7,7 +7,10 @@ use libp2p::identity;
 use lumina_node::{
     blockstore::RedbBlockstore,
     events::{EventSubscriber, NodeEvent},
-    node::{Node, NodeConfig},
+    network::Network,
+    node::{
+        ConnectionLimits, Node, NodeConfig, DEFAULT_MAX_SAMPLES_NEEDED, DEFAULT_PEER_BAN_DURATION,
+    },
     store::RedbStore,
 };
 use tempfile::tempdir;
@@ -67,11 +70,21 @@ async fn new_node(path: impl AsRef<Path>) -> (Node<RedbBlockstore, RedbStore>, E
     let (_, bridge_ma) = fetch_bridge_info().await;
 
     Node::new_subscribed(NodeConfig {
-        network_id: "private".to_string(),
+        network: Network::Private.into(),
         p2p_local_keypair: identity::Keypair::generate_ed25519(),
         p2p_bootnodes: vec![bridge_ma],
         p2p_listen_on: vec![],
+        p2p_peer_ban_duration: DEFAULT_PEER_BAN_DURATION,
+        p2p_connection_limits: ConnectionLimits::default(),
         sync_batch_size: 512,
+        sync_trusted_checkpoint: None,
+        sync_window: None,
+        sync_archival: false,
+        sampling_window: None,
+        sampling_max_samples_needed: DEFAULT_MAX_SAMPLES_NEEDED,
+        sampling_full_storage: false,
+        pruning_window: None,
+        pruning_disabled: false,
         blockstore,
         store,
     })
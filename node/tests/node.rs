@@ -89,6 +89,23 @@ async fn header_store_access() {
     }
 }
 
+#[tokio::test]
+async fn header_subscription_starts_with_store_head() {
+    let (store, _) = gen_filled_store(10).await;
+    let node = Node::new(NodeConfig {
+        store,
+        ..test_node_config()
+    })
+    .await
+    .unwrap();
+
+    let mut headers = node.subscribe_headers().boxed();
+
+    let head = headers.next().await.unwrap().unwrap();
+    assert_eq!(head, node.get_local_head_header().await.unwrap());
+    assert_eq!(head.height().value(), 10);
+}
+
 #[tokio::test]
 async fn peer_discovery() {
     // Bridge node cannot connect to other nodes because it is behind Docker's NAT.
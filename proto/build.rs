@@ -1,5 +1,7 @@
 //! A build script generating rust types from protobuf definitions.
 
+use std::env;
+
 use anyhow::Result;
 
 const SERIALIZED: &str = r#"#[derive(::serde::Deserialize, ::serde::Serialize)] #[serde(default)]"#;
@@ -56,6 +58,32 @@ static CUSTOM_FIELD_ATTRIBUTES: &[(&str, &str)] = &[
     (".share.p2p.shrex.nd.NamespaceRowResponse.shares", VEC_BASE64STRING),
 ];
 
+#[rustfmt::skip]
+static PROTOS: &[&str] = &[
+    "vendor/celestia/da/data_availability_header.proto",
+    "vendor/celestia/blob/v1/tx.proto",
+    "vendor/header/pb/extended_header.proto",
+    "vendor/share/p2p/shrexnd/pb/share.proto",
+    "vendor/share/p2p/shrexeds/pb/extended_data_square.proto",
+    "vendor/share/p2p/shwap/pb/shwap.proto",
+    "vendor/share/eds/byzantine/pb/share.proto",
+    "vendor/cosmos/auth/v1beta1/query.proto",
+    "vendor/cosmos/bank/v1beta1/query.proto",
+    "vendor/cosmos/base/v1beta1/coin.proto",
+    "vendor/cosmos/base/abci/v1beta1/abci.proto",
+    "vendor/cosmos/base/node/v1beta1/query.proto",
+    "vendor/cosmos/base/tendermint/v1beta1/query.proto",
+    "vendor/cosmos/crypto/multisig/v1beta1/multisig.proto",
+    "vendor/cosmos/crypto/secp256k1/keys.proto",
+    "vendor/cosmos/staking/v1beta1/query.proto",
+    "vendor/cosmos/tx/v1beta1/service.proto",
+    "vendor/cosmos/tx/v1beta1/tx.proto",
+    "vendor/cosmos/vesting/v1beta1/vesting.proto",
+    "vendor/go-header/p2p/pb/header_request.proto",
+];
+
+static INCLUDES: &[&str] = &["vendor", "vendor/nmt"];
+
 fn main() -> Result<()> {
     let mut config = prost_build::Config::new();
 
@@ -79,24 +107,16 @@ fn main() -> Result<()> {
             "::celestia_tendermint_proto::google::protobuf::Duration",
         )
         // Comments in Google's protobuf are causing issues with cargo-test
-        .disable_comments([".google"])
-        .compile_protos(
-            &[
-                "vendor/celestia/da/data_availability_header.proto",
-                "vendor/celestia/blob/v1/tx.proto",
-                "vendor/header/pb/extended_header.proto",
-                "vendor/share/p2p/shrexnd/pb/share.proto",
-                "vendor/share/p2p/shwap/pb/shwap.proto",
-                "vendor/share/eds/byzantine/pb/share.proto",
-                "vendor/cosmos/base/v1beta1/coin.proto",
-                "vendor/cosmos/base/abci/v1beta1/abci.proto",
-                "vendor/cosmos/crypto/multisig/v1beta1/multisig.proto",
-                "vendor/cosmos/staking/v1beta1/query.proto",
-                "vendor/cosmos/tx/v1beta1/tx.proto",
-                "vendor/go-header/p2p/pb/header_request.proto",
-            ],
-            &["vendor", "vendor/nmt"],
-        )?;
+        .disable_comments([".google"]);
+
+    // gRPC services are generated only when requested, as they pull in `tonic`
+    if env::var_os("CARGO_FEATURE_TONIC").is_some() {
+        tonic_build::configure()
+            .build_transport(false)
+            .compile_with_config(config, PROTOS, INCLUDES)?;
+    } else {
+        config.compile_protos(PROTOS, INCLUDES)?;
+    }
 
     Ok(())
 }
@@ -4,4 +4,7 @@
 
 pub mod serializers;
 
+pub use prost;
+pub use prost_types;
+
 include!(concat!(env!("OUT_DIR"), "/mod.rs"));
@@ -65,3 +65,65 @@ pub trait Blob {
     #[subscription(name = "blob.Subscribe", unsubscribe = "blob.Unsubscribe", item = BlobsAtHeight)]
     async fn blob_subscribe(&self, namespace: Namespace) -> SubcriptionResult;
 }
+
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub use self::server::BlobServer;
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+    use jsonrpsee::core::{RpcResult, SubscriptionResult};
+
+    /// Server side of the [`BlobClient`](super::BlobClient) API, taking the arguments by value.
+    #[rpc(server)]
+    pub trait Blob {
+        /// Get retrieves the blob by commitment under the given namespace and height.
+        #[method(name = "blob.Get")]
+        async fn blob_get(
+            &self,
+            height: u64,
+            namespace: Namespace,
+            commitment: Commitment,
+        ) -> RpcResult<Blob>;
+
+        /// GetAll returns all blobs under the given namespaces and height.
+        #[method(name = "blob.GetAll")]
+        async fn blob_get_all(
+            &self,
+            height: u64,
+            namespaces: Vec<Namespace>,
+        ) -> RpcResult<Option<Vec<Blob>>>;
+
+        /// GetProof retrieves proofs in the given namespaces at the given height by commitment.
+        #[method(name = "blob.GetProof")]
+        async fn blob_get_proof(
+            &self,
+            height: u64,
+            namespace: Namespace,
+            commitment: Commitment,
+        ) -> RpcResult<Vec<NamespaceProof>>;
+
+        /// Included checks whether a blob's given commitment(Merkle subtree root) is included at given height and under the namespace.
+        #[method(name = "blob.Included")]
+        async fn blob_included(
+            &self,
+            height: u64,
+            namespace: Namespace,
+            proof: NamespaceProof,
+            commitment: Commitment,
+        ) -> RpcResult<bool>;
+
+        /// Submit sends Blobs and reports the height in which they were included. Allows sending multiple Blobs atomically synchronously. Uses default wallet registered on the Node.
+        #[method(name = "blob.Submit")]
+        async fn blob_submit(&self, blobs: Vec<Blob>, opts: TxConfig) -> RpcResult<u64>;
+
+        /// Subscribe to published blobs from the given namespace as they are included.
+        ///
+        /// # Notes
+        ///
+        /// Unsubscribe is not implemented by Celestia nodes.
+        #[subscription(name = "blob.Subscribe", unsubscribe = "blob.Unsubscribe", item = BlobsAtHeight)]
+        async fn blob_subscribe(&self, namespace: Namespace) -> SubscriptionResult;
+    }
+}
@@ -20,4 +20,26 @@ pub enum Error {
     /// Error propagated from the [`jsonrpsee`].
     #[error(transparent)]
     JsonRpc(#[from] jsonrpsee::core::ClientError),
+
+    /// Error status returned by the gRPC server.
+    #[cfg(feature = "grpc")]
+    #[error(transparent)]
+    Grpc(#[from] tonic::Status),
+
+    /// Error propagated from the gRPC transport.
+    #[cfg(all(feature = "grpc", not(target_arch = "wasm32")))]
+    #[error(transparent)]
+    GrpcTransport(#[from] tonic::transport::Error),
+
+    /// Error propagated from the [`celestia_types`].
+    #[error(transparent)]
+    Types(#[from] celestia_types::Error),
+
+    /// Required field missing in the response.
+    #[error("Missing field in the response: {0}")]
+    MissingResponseField(&'static str),
+
+    /// Gas price returned by the node couldn't be parsed.
+    #[error("Invalid gas price: {0}")]
+    InvalidGasPrice(String),
 }
@@ -0,0 +1,622 @@
+//! Client for the gRPC services of the celestia-app consensus nodes.
+//!
+//! Unlike the [`Client`], which talks to the celestia-node, the [`GrpcClient`]
+//! connects directly to a consensus node. It can query the state of the accounts
+//! and broadcast signed transactions, like the ones created by the `Signer` of
+//! [`celestia_types::signer`].
+//!
+//! On native targets it uses the [`tonic`] HTTP/2 transport, while in wasm the
+//! requests are sent using the gRPC-web protocol, which has to be supported by the
+//! consensus node or a proxy in front of it.
+//!
+//! [`Client`]: crate::Client
+
+use std::convert::Infallible;
+
+use celestia_proto::cosmos::auth::v1beta1::query_client::QueryClient as AuthQueryClient;
+use celestia_proto::cosmos::auth::v1beta1::QueryAccountRequest;
+use celestia_proto::cosmos::bank::v1beta1::query_client::QueryClient as BankQueryClient;
+use celestia_proto::cosmos::bank::v1beta1::{QueryAllBalancesRequest, QueryBalanceRequest};
+use celestia_proto::cosmos::base::node::v1beta1::service_client::ServiceClient as NodeServiceClient;
+use celestia_proto::cosmos::base::node::v1beta1::ConfigRequest;
+use celestia_proto::cosmos::base::query::v1beta1::PageRequest;
+use celestia_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
+use celestia_proto::cosmos::base::tendermint::v1beta1::AbciQueryRequest;
+use celestia_proto::cosmos::tx::v1beta1::service_client::ServiceClient as TxServiceClient;
+use celestia_proto::cosmos::tx::v1beta1::{BroadcastTxRequest, SimulateRequest};
+use celestia_tendermint_proto::Protobuf;
+use celestia_types::signer::BlobTx;
+use celestia_types::state::{
+    balance_store_key, AccAddress, Balance, BaseAccount, BroadcastMode, GasInfo,
+    ProvenQueryResponse, TxResponse, BANK_STORE_NAME,
+};
+
+use crate::{Error, Result};
+
+#[cfg(not(target_arch = "wasm32"))]
+type Transport = tonic::transport::Channel;
+
+#[cfg(target_arch = "wasm32")]
+type Transport = tonic_web_wasm_client::Client;
+
+/// gRPC client of the celestia-app consensus node.
+#[derive(Debug, Clone)]
+pub struct GrpcClient {
+    transport: Transport,
+}
+
+impl GrpcClient {
+    /// Create a new gRPC client connected to the given url.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the url is invalid or the connection
+    /// can't be established.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn new(url: &str) -> Result<Self> {
+        let transport = tonic::transport::Endpoint::from_shared(url.to_owned())?
+            .connect()
+            .await?;
+
+        Ok(GrpcClient { transport })
+    }
+
+    /// Create a new gRPC-web client for the given url.
+    ///
+    /// The connection is established lazily, with the first request.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn new(url: &str) -> Result<Self> {
+        let transport = tonic_web_wasm_client::Client::new(url.to_owned());
+
+        Ok(GrpcClient { transport })
+    }
+
+    /// Get the account with the given address.
+    ///
+    /// Module and vesting accounts are returned as their base account.
+    ///
+    /// # Errors
+    ///
+    /// Besides the transport errors, this function returns an error with
+    /// the [`tonic::Code::NotFound`] status if the account doesn't exist.
+    pub async fn get_account(&self, address: &AccAddress) -> Result<BaseAccount> {
+        let mut client = AuthQueryClient::new(self.transport.clone());
+        let request = QueryAccountRequest {
+            address: address.to_string(),
+        };
+
+        let account = client
+            .account(request)
+            .await?
+            .into_inner()
+            .account
+            .ok_or(Error::MissingResponseField("account"))?;
+
+        Ok(account.try_into()?)
+    }
+
+    /// Get the balance of the account in the given denomination.
+    pub async fn get_balance(&self, address: &AccAddress, denom: &str) -> Result<Balance> {
+        let mut client = BankQueryClient::new(self.transport.clone());
+        let request = QueryBalanceRequest {
+            address: address.to_string(),
+            denom: denom.to_owned(),
+        };
+
+        let balance = client
+            .balance(request)
+            .await?
+            .into_inner()
+            .balance
+            .ok_or(Error::MissingResponseField("balance"))?;
+
+        Ok(balance.try_into()?)
+    }
+
+    /// Get the balance of the account in the given denomination, together with
+    /// the proof of it in the application state of the latest height.
+    ///
+    /// Unlike [`GrpcClient::get_balance`], the response doesn't need to be trusted,
+    /// as it can be verified against the `app_hash` of the header following the
+    /// queried height, see [`ProvenQueryResponse::verify_balance`].
+    pub async fn get_balance_with_proof(
+        &self,
+        address: &AccAddress,
+        denom: &str,
+    ) -> Result<ProvenQueryResponse> {
+        let mut client = TendermintServiceClient::new(self.transport.clone());
+        let request = AbciQueryRequest {
+            data: balance_store_key(address, denom),
+            path: format!("/store/{BANK_STORE_NAME}/key"),
+            height: 0,
+            prove: true,
+        };
+
+        let response = client.abci_query(request).await?.into_inner();
+
+        Ok(response.try_into()?)
+    }
+
+    /// Get the balances of the account in all the denominations it holds.
+    pub async fn get_all_balances(&self, address: &AccAddress) -> Result<Vec<Balance>> {
+        let mut client = BankQueryClient::new(self.transport.clone());
+        let mut balances = Vec::new();
+        let mut next_key = Vec::new();
+
+        loop {
+            let request = QueryAllBalancesRequest {
+                address: address.to_string(),
+                pagination: Some(PageRequest {
+                    key: next_key,
+                    ..Default::default()
+                }),
+            };
+
+            let response = client.all_balances(request).await?.into_inner();
+
+            for coin in response.balances {
+                balances.push(coin.try_into()?);
+            }
+
+            match response.pagination {
+                Some(page) if !page.next_key.is_empty() => next_key = page.next_key,
+                _ => break,
+            }
+        }
+
+        Ok(balances)
+    }
+
+    /// Get the minimum gas price accepted by the consensus node, in `utia` per gas unit.
+    pub async fn get_min_gas_price(&self) -> Result<f64> {
+        let mut client = NodeServiceClient::new(self.transport.clone());
+
+        let min_gas_price = client
+            .config(ConfigRequest {})
+            .await?
+            .into_inner()
+            .minimum_gas_price;
+
+        parse_gas_price(&min_gas_price).ok_or(Error::InvalidGasPrice(min_gas_price))
+    }
+
+    /// Simulate the execution of the encoded transaction, returning the gas it would use.
+    pub async fn simulate(&self, tx_bytes: Vec<u8>) -> Result<GasInfo> {
+        let mut client = TxServiceClient::new(self.transport.clone());
+        #[allow(deprecated)]
+        let request = SimulateRequest { tx: None, tx_bytes };
+
+        client
+            .simulate(request)
+            .await?
+            .into_inner()
+            .gas_info
+            .ok_or(Error::MissingResponseField("gas_info"))
+    }
+
+    /// Broadcast the encoded transaction.
+    ///
+    /// The transaction being rejected is not an error, the returned [`TxResponse`]
+    /// has to be checked for a non-zero `code`.
+    pub async fn broadcast_tx(&self, tx_bytes: Vec<u8>, mode: BroadcastMode) -> Result<TxResponse> {
+        let mut client = TxServiceClient::new(self.transport.clone());
+        let request = BroadcastTxRequest {
+            tx_bytes,
+            mode: mode.into(),
+        };
+
+        client
+            .broadcast_tx(request)
+            .await?
+            .into_inner()
+            .tx_response
+            .ok_or(Error::MissingResponseField("tx_response"))
+    }
+
+    /// Broadcast the transaction paying for blobs, together with the blobs.
+    ///
+    /// See [`GrpcClient::broadcast_tx`] for the details.
+    pub async fn broadcast_blob_tx(
+        &self,
+        blob_tx: BlobTx,
+        mode: BroadcastMode,
+    ) -> Result<TxResponse> {
+        let tx_bytes: std::result::Result<_, Infallible> = blob_tx.encode_vec();
+        self.broadcast_tx(tx_bytes.unwrap(), mode).await
+    }
+}
+
+/// Parse the gas price with an optional denomination suffix, e.g. `0.002utia`.
+fn parse_gas_price(gas_price: &str) -> Option<f64> {
+    gas_price
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+        .parse()
+        .ok()
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::net::SocketAddr;
+    use std::result::Result;
+
+    use celestia_proto::cosmos::auth::v1beta1::query_server::{
+        Query as AuthQuery, QueryServer as AuthQueryServer,
+    };
+    use celestia_proto::cosmos::auth::v1beta1::{
+        AddressBytesToStringRequest, AddressBytesToStringResponse, AddressStringToBytesRequest,
+        AddressStringToBytesResponse, Bech32PrefixRequest, Bech32PrefixResponse,
+        QueryAccountAddressByIdRequest, QueryAccountAddressByIdResponse, QueryAccountResponse,
+        QueryAccountsRequest, QueryAccountsResponse, QueryModuleAccountByNameRequest,
+        QueryModuleAccountByNameResponse, QueryModuleAccountsRequest, QueryModuleAccountsResponse,
+        QueryParamsRequest, QueryParamsResponse,
+    };
+    use celestia_proto::cosmos::bank::v1beta1::query_server::{
+        Query as BankQuery, QueryServer as BankQueryServer,
+    };
+    use celestia_proto::cosmos::bank::v1beta1::{
+        QueryAllBalancesResponse, QueryBalanceResponse, QueryDenomMetadataRequest,
+        QueryDenomMetadataResponse, QueryDenomOwnersRequest, QueryDenomOwnersResponse,
+        QueryDenomsMetadataRequest, QueryDenomsMetadataResponse,
+        QueryParamsRequest as BankQueryParamsRequest,
+        QueryParamsResponse as BankQueryParamsResponse, QuerySpendableBalancesRequest,
+        QuerySpendableBalancesResponse, QuerySupplyOfRequest, QuerySupplyOfResponse,
+        QueryTotalSupplyRequest, QueryTotalSupplyResponse,
+    };
+    use celestia_proto::cosmos::base::abci::v1beta1::TxResponse;
+    use celestia_proto::cosmos::base::node::v1beta1::service_server::{
+        Service as NodeService, ServiceServer as NodeServiceServer,
+    };
+    use celestia_proto::cosmos::base::node::v1beta1::ConfigResponse;
+    use celestia_proto::cosmos::base::query::v1beta1::PageResponse;
+    use celestia_proto::cosmos::base::tendermint::v1beta1::service_server::{
+        Service as TendermintService, ServiceServer as TendermintServiceServer,
+    };
+    use celestia_proto::cosmos::base::tendermint::v1beta1::{
+        AbciQueryResponse, GetBlockByHeightRequest, GetBlockByHeightResponse,
+        GetLatestBlockRequest, GetLatestBlockResponse, GetLatestValidatorSetRequest,
+        GetLatestValidatorSetResponse, GetNodeInfoRequest, GetNodeInfoResponse, GetSyncingRequest,
+        GetSyncingResponse, GetValidatorSetByHeightRequest, GetValidatorSetByHeightResponse,
+        ProofOp, ProofOps,
+    };
+    use celestia_proto::cosmos::base::v1beta1::Coin;
+    use celestia_proto::cosmos::tx::v1beta1::service_server::{
+        Service as TxService, ServiceServer as TxServiceServer,
+    };
+    use celestia_proto::cosmos::tx::v1beta1::{
+        BroadcastTxResponse, GetBlockWithTxsRequest, GetBlockWithTxsResponse, GetTxRequest,
+        GetTxResponse, GetTxsEventRequest, GetTxsEventResponse, SimulateResponse,
+    };
+    use celestia_types::state::Uint;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+    use tonic::{Code, Request, Response, Status};
+
+    use super::*;
+
+    const ACCOUNT: &str = "celestia1qurswpc8qurswpc8qurswpc8qurswpc8zek26v";
+    const UNKNOWN_ACCOUNT: &str = "celestia1qyqszqgpqyqszqgpqyqszqgpqyqszqgpreswh3";
+    const TX_HASH: &str = "2B8B2F4A5A1B6A2E6B1F1E3D64D71E5B5C4C1F0A1E4E8C0E16B5D5C4B3A29180";
+
+    /// Mock of the consensus node, implementing only the methods used by the client.
+    struct MockApp;
+
+    macro_rules! mock_service {
+        (
+            impl $trait:ident {
+                $($items:item)*
+            }
+            unimplemented {
+                $($name:ident($req:ty) -> $resp:ty;)*
+            }
+        ) => {
+            #[tonic::async_trait]
+            impl $trait for MockApp {
+                $($items)*
+
+                $(
+                    async fn $name(&self, _: Request<$req>) -> Result<Response<$resp>, Status> {
+                        Err(Status::unimplemented(stringify!($name)))
+                    }
+                )*
+            }
+        };
+    }
+
+    mock_service! {
+        impl AuthQuery {
+            async fn account(
+                &self,
+                request: Request<QueryAccountRequest>,
+            ) -> Result<Response<QueryAccountResponse>, Status> {
+                let address = request.into_inner().address;
+                if address != ACCOUNT {
+                    return Err(Status::not_found(format!("account {address} not found")));
+                }
+
+                let account = BaseAccount {
+                    address: address.parse().unwrap(),
+                    pub_key: None,
+                    account_number: 7,
+                    sequence: 42,
+                };
+                let account = prost_types::Any {
+                    type_url: "/cosmos.auth.v1beta1.BaseAccount".to_owned(),
+                    value: account.encode_vec().unwrap(),
+                };
+
+                Ok(Response::new(QueryAccountResponse {
+                    account: Some(account),
+                }))
+            }
+        }
+        unimplemented {
+            accounts(QueryAccountsRequest) -> QueryAccountsResponse;
+            account_address_by_id(QueryAccountAddressByIdRequest) -> QueryAccountAddressByIdResponse;
+            params(QueryParamsRequest) -> QueryParamsResponse;
+            module_accounts(QueryModuleAccountsRequest) -> QueryModuleAccountsResponse;
+            module_account_by_name(QueryModuleAccountByNameRequest) -> QueryModuleAccountByNameResponse;
+            bech32_prefix(Bech32PrefixRequest) -> Bech32PrefixResponse;
+            address_bytes_to_string(AddressBytesToStringRequest) -> AddressBytesToStringResponse;
+            address_string_to_bytes(AddressStringToBytesRequest) -> AddressStringToBytesResponse;
+        }
+    }
+
+    mock_service! {
+        impl BankQuery {
+            async fn balance(
+                &self,
+                request: Request<QueryBalanceRequest>,
+            ) -> Result<Response<QueryBalanceResponse>, Status> {
+                let request = request.into_inner();
+
+                Ok(Response::new(QueryBalanceResponse {
+                    balance: Some(Coin {
+                        denom: request.denom,
+                        amount: "1000".to_owned(),
+                    }),
+                }))
+            }
+
+            async fn all_balances(
+                &self,
+                request: Request<QueryAllBalancesRequest>,
+            ) -> Result<Response<QueryAllBalancesResponse>, Status> {
+                let key = request.into_inner().pagination.unwrap_or_default().key;
+
+                // return the balances in two pages
+                let (denom, next_key) = match &key[..] {
+                    b"" => ("utia", b"next".to_vec()),
+                    b"next" => ("uother", vec![]),
+                    _ => return Err(Status::invalid_argument("invalid key")),
+                };
+
+                Ok(Response::new(QueryAllBalancesResponse {
+                    balances: vec![Coin {
+                        denom: denom.to_owned(),
+                        amount: "5".to_owned(),
+                    }],
+                    pagination: Some(PageResponse { next_key, total: 2 }),
+                }))
+            }
+        }
+        unimplemented {
+            spendable_balances(QuerySpendableBalancesRequest) -> QuerySpendableBalancesResponse;
+            total_supply(QueryTotalSupplyRequest) -> QueryTotalSupplyResponse;
+            supply_of(QuerySupplyOfRequest) -> QuerySupplyOfResponse;
+            params(BankQueryParamsRequest) -> BankQueryParamsResponse;
+            denom_metadata(QueryDenomMetadataRequest) -> QueryDenomMetadataResponse;
+            denoms_metadata(QueryDenomsMetadataRequest) -> QueryDenomsMetadataResponse;
+            denom_owners(QueryDenomOwnersRequest) -> QueryDenomOwnersResponse;
+        }
+    }
+
+    mock_service! {
+        impl NodeService {
+            async fn config(
+                &self,
+                _: Request<ConfigRequest>,
+            ) -> Result<Response<ConfigResponse>, Status> {
+                Ok(Response::new(ConfigResponse {
+                    minimum_gas_price: "0.002000000000000000utia".to_owned(),
+                }))
+            }
+        }
+        unimplemented {}
+    }
+
+    mock_service! {
+        impl TendermintService {
+            async fn abci_query(
+                &self,
+                request: Request<AbciQueryRequest>,
+            ) -> Result<Response<AbciQueryResponse>, Status> {
+                let request = request.into_inner();
+                if request.path != "/store/bank/key" || !request.prove {
+                    return Err(Status::invalid_argument("unsupported query"));
+                }
+
+                let proof_op = |r#type: &str, key: &[u8]| ProofOp {
+                    r#type: r#type.to_owned(),
+                    key: key.to_vec(),
+                    data: vec![],
+                };
+
+                Ok(Response::new(AbciQueryResponse {
+                    key: request.data.clone(),
+                    value: b"balance".to_vec(),
+                    proof_ops: Some(ProofOps {
+                        ops: vec![
+                            proof_op("ics23:iavl", &request.data),
+                            proof_op("ics23:simple", b"bank"),
+                        ],
+                    }),
+                    height: 10,
+                    ..Default::default()
+                }))
+            }
+        }
+        unimplemented {
+            get_node_info(GetNodeInfoRequest) -> GetNodeInfoResponse;
+            get_syncing(GetSyncingRequest) -> GetSyncingResponse;
+            get_latest_block(GetLatestBlockRequest) -> GetLatestBlockResponse;
+            get_block_by_height(GetBlockByHeightRequest) -> GetBlockByHeightResponse;
+            get_latest_validator_set(GetLatestValidatorSetRequest) -> GetLatestValidatorSetResponse;
+            get_validator_set_by_height(GetValidatorSetByHeightRequest) -> GetValidatorSetByHeightResponse;
+        }
+    }
+
+    mock_service! {
+        impl TxService {
+            async fn simulate(
+                &self,
+                request: Request<SimulateRequest>,
+            ) -> Result<Response<SimulateResponse>, Status> {
+                let tx_bytes = request.into_inner().tx_bytes;
+
+                Ok(Response::new(SimulateResponse {
+                    gas_info: Some(GasInfo {
+                        gas_wanted: 0,
+                        gas_used: 1000 + tx_bytes.len() as u64,
+                    }),
+                    result: None,
+                }))
+            }
+
+            async fn broadcast_tx(
+                &self,
+                request: Request<BroadcastTxRequest>,
+            ) -> Result<Response<BroadcastTxResponse>, Status> {
+                let request = request.into_inner();
+                if request.mode != BroadcastMode::Sync as i32 {
+                    return Err(Status::invalid_argument("unsupported mode"));
+                }
+
+                // reject anything that is not a blob tx
+                let code = match BlobTx::decode_vec(&request.tx_bytes) {
+                    Ok(_) => 0,
+                    Err(_) => 2,
+                };
+
+                Ok(Response::new(BroadcastTxResponse {
+                    tx_response: Some(TxResponse {
+                        txhash: TX_HASH.to_owned(),
+                        code,
+                        ..Default::default()
+                    }),
+                }))
+            }
+        }
+        unimplemented {
+            get_tx(GetTxRequest) -> GetTxResponse;
+            get_txs_event(GetTxsEventRequest) -> GetTxsEventResponse;
+            get_block_with_txs(GetBlockWithTxsRequest) -> GetBlockWithTxsResponse;
+        }
+    }
+
+    async fn start_mock_app() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(
+            Server::builder()
+                .add_service(AuthQueryServer::new(MockApp))
+                .add_service(BankQueryServer::new(MockApp))
+                .add_service(NodeServiceServer::new(MockApp))
+                .add_service(TendermintServiceServer::new(MockApp))
+                .add_service(TxServiceServer::new(MockApp))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        addr
+    }
+
+    async fn client() -> GrpcClient {
+        let addr = start_mock_app().await;
+        GrpcClient::new(&format!("http://{addr}")).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn get_account() {
+        let client = client().await;
+
+        let account = client.get_account(&ACCOUNT.parse().unwrap()).await.unwrap();
+        assert_eq!(account.address.to_string(), ACCOUNT);
+        assert_eq!(account.pub_key, None);
+        assert_eq!(account.account_number, 7);
+        assert_eq!(account.sequence, 42);
+
+        let unknown = UNKNOWN_ACCOUNT.parse().unwrap();
+        let err = client.get_account(&unknown).await.unwrap_err();
+        assert!(matches!(err, Error::Grpc(status) if status.code() == Code::NotFound));
+    }
+
+    #[tokio::test]
+    async fn get_balances() {
+        let client = client().await;
+        let address = ACCOUNT.parse().unwrap();
+
+        let balance = client.get_balance(&address, "utia").await.unwrap();
+        assert_eq!(balance.denom, "utia");
+        assert_eq!(balance.amount, Uint::from(1000));
+
+        let balances = client.get_all_balances(&address).await.unwrap();
+        let denoms: Vec<_> = balances.iter().map(|b| b.denom.as_str()).collect();
+        assert_eq!(denoms, ["utia", "uother"]);
+    }
+
+    #[tokio::test]
+    async fn get_balance_with_proof() {
+        let client = client().await;
+        let address = ACCOUNT.parse().unwrap();
+
+        let response = client
+            .get_balance_with_proof(&address, "utia")
+            .await
+            .unwrap();
+        assert_eq!(response.height, 10);
+        assert_eq!(response.key, balance_store_key(&address, "utia"));
+        assert_eq!(response.value, b"balance");
+        assert_eq!(response.proof.store_name(), "bank");
+    }
+
+    #[tokio::test]
+    async fn get_min_gas_price() {
+        let client = client().await;
+
+        assert_eq!(client.get_min_gas_price().await.unwrap(), 0.002);
+    }
+
+    #[tokio::test]
+    async fn simulate_and_broadcast() {
+        let client = client().await;
+
+        let gas_info = client.simulate(vec![0; 24]).await.unwrap();
+        assert_eq!(gas_info.gas_used, 1024);
+
+        let blob_tx = BlobTx {
+            tx: vec![1, 2, 3],
+            blobs: vec![],
+        };
+        let response = client
+            .broadcast_blob_tx(blob_tx, BroadcastMode::Sync)
+            .await
+            .unwrap();
+        assert_eq!(response.txhash, TX_HASH);
+        assert_eq!(response.code, 0);
+
+        let response = client
+            .broadcast_tx(vec![1, 2, 3], BroadcastMode::Sync)
+            .await
+            .unwrap();
+        assert_eq!(response.code, 2);
+    }
+
+    #[test]
+    fn gas_price_parsing() {
+        assert_eq!(parse_gas_price("0.002utia"), Some(0.002));
+        assert_eq!(parse_gas_price("0.1"), Some(0.1));
+        assert_eq!(parse_gas_price(""), None);
+        assert_eq!(parse_gas_price("utia"), None);
+    }
+}
@@ -48,3 +48,61 @@ pub trait Header {
     #[method(name = "header.WaitForHeight")]
     async fn header_wait_for_height(&self, height: u64) -> Result<ExtendedHeader, Error>;
 }
+
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub use self::server::HeaderServer;
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+    use jsonrpsee::core::{RpcResult, SubscriptionResult};
+
+    /// Server side of the [`HeaderClient`](super::HeaderClient) API, taking the arguments by value.
+    #[rpc(server)]
+    pub trait Header {
+        /// GetByHash returns the header of the given hash from the node's header store.
+        #[method(name = "header.GetByHash")]
+        async fn header_get_by_hash(&self, hash: Hash) -> RpcResult<ExtendedHeader>;
+
+        /// GetByHeight returns the ExtendedHeader at the given height if it is currently available.
+        #[method(name = "header.GetByHeight")]
+        async fn header_get_by_height(&self, height: u64) -> RpcResult<ExtendedHeader>;
+
+        /// GetRangeByHeight returns the given range (from:to) of ExtendedHeaders from the node's header store and verifies that the returned headers are adjacent to each other.
+        #[method(name = "header.GetRangeByHeight")]
+        async fn header_get_range_by_height(
+            &self,
+            from: ExtendedHeader,
+            to: u64,
+        ) -> RpcResult<Vec<ExtendedHeader>>;
+
+        /// LocalHead returns the ExtendedHeader of the chain head.
+        #[method(name = "header.LocalHead")]
+        async fn header_local_head(&self) -> RpcResult<ExtendedHeader>;
+
+        /// NetworkHead provides the Syncer's view of the current network head.
+        #[method(name = "header.NetworkHead")]
+        async fn header_network_head(&self) -> RpcResult<ExtendedHeader>;
+
+        /// Subscribe to recent ExtendedHeaders from the network.
+        ///
+        /// # Notes
+        ///
+        /// Unsubscribe is not implemented by Celestia nodes.
+        #[subscription(name = "header.Subscribe", unsubscribe = "header.Unsubscribe", item = ExtendedHeader)]
+        async fn header_subscribe(&self) -> SubscriptionResult;
+
+        /// SyncState returns the current state of the header Syncer.
+        #[method(name = "header.SyncState")]
+        async fn header_sync_state(&self) -> RpcResult<SyncState>;
+
+        /// SyncWait blocks until the header Syncer is synced to network head.
+        #[method(name = "header.SyncWait")]
+        async fn header_sync_wait(&self) -> RpcResult<()>;
+
+        /// WaitForHeight blocks until the header at the given height has been processed by the store or context deadline is exceeded.
+        #[method(name = "header.WaitForHeight")]
+        async fn header_wait_for_height(&self, height: u64) -> RpcResult<ExtendedHeader>;
+    }
+}
@@ -4,6 +4,9 @@
 pub mod blob;
 pub mod client;
 mod error;
+#[cfg(feature = "grpc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "grpc")))]
+pub mod grpc;
 mod header;
 #[cfg(feature = "p2p")]
 mod p2p;
@@ -11,6 +14,9 @@ pub mod share;
 mod state;
 
 pub use crate::blob::BlobClient;
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub use crate::blob::BlobServer;
 #[cfg(any(
     not(target_arch = "wasm32"),
     all(target_arch = "wasm32", feature = "wasm-bindgen")
@@ -24,11 +30,23 @@ pub use crate::blob::BlobClient;
 )]
 pub use crate::client::Client;
 pub use crate::error::{Error, Result};
+#[cfg(feature = "grpc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "grpc")))]
+pub use crate::grpc::GrpcClient;
 pub use crate::header::HeaderClient;
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub use crate::header::HeaderServer;
 #[cfg(feature = "p2p")]
 #[cfg_attr(docsrs, doc(cfg(feature = "p2p")))]
 pub use crate::p2p::P2PClient;
+#[cfg(all(feature = "p2p", feature = "server"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "p2p", feature = "server"))))]
+pub use crate::p2p::P2PServer;
 pub use crate::share::ShareClient;
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub use crate::share::ShareServer;
 pub use crate::state::StateClient;
 
 /// Re-exports of all the RPC traits.
@@ -83,3 +83,93 @@ pub trait P2P {
     #[method(name = "p2p.Unprotect")]
     async fn p2p_unprotect(&self, peer_id: &PeerId, tag: &str) -> Result<bool, Error>;
 }
+
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub use self::server::P2PServer;
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+    use jsonrpsee::core::RpcResult;
+
+    /// Server side of the [`P2PClient`](super::P2PClient) API, taking the arguments by value.
+    #[rpc(server)]
+    pub trait P2P {
+        /// BandwidthForPeer returns a Stats struct with bandwidth metrics associated with the given peer.ID. The metrics returned include all traffic sent / received for the peer, regardless of protocol.
+        #[method(name = "p2p.BandwidthForPeer")]
+        async fn p2p_bandwidth_for_peer(&self, peer_id: PeerId) -> RpcResult<BandwidthStats>;
+
+        /// BandwidthForProtocol returns a Stats struct with bandwidth metrics associated with the given protocol.ID.
+        #[method(name = "p2p.BandwidthForProtocol")]
+        async fn p2p_bandwidth_for_protocol(
+            &self,
+            protocol_id: String,
+        ) -> RpcResult<BandwidthStats>;
+
+        /// BandwidthStats returns a Stats struct with bandwidth metrics for all data sent/received by the local peer, regardless of protocol or remote peer IDs.
+        #[method(name = "p2p.BandwidthStats")]
+        async fn p2p_bandwidth_stats(&self) -> RpcResult<BandwidthStats>;
+
+        /// BlockPeer adds a peer to the set of blocked peers.
+        #[method(name = "p2p.BlockPeer")]
+        async fn p2p_block_peer(&self, peer_id: PeerId) -> RpcResult<()>;
+
+        /// ClosePeer closes the connection to a given peer.
+        #[method(name = "p2p.ClosePeer")]
+        async fn p2p_close_peer(&self, peer_id: PeerId) -> RpcResult<()>;
+
+        /// Connect ensures there is a connection between this host and the peer with given peer.
+        #[method(name = "p2p.Connect")]
+        async fn p2p_connect(&self, address: AddrInfo) -> RpcResult<()>;
+
+        /// Connectedness returns a state signaling connection capabilities.
+        #[method(name = "p2p.Connectedness")]
+        async fn p2p_connectedness(&self, peer_id: PeerId) -> RpcResult<Connectedness>;
+
+        /// Info returns address information about the host.
+        #[method(name = "p2p.Info")]
+        async fn p2p_info(&self) -> RpcResult<AddrInfo>;
+
+        /// IsProtected returns whether the given peer is protected.
+        #[method(name = "p2p.IsProtected")]
+        async fn p2p_is_protected(&self, peer_id: PeerId, tag: String) -> RpcResult<bool>;
+
+        /// ListBlockedPeers returns a list of blocked peers.
+        #[method(name = "p2p.ListBlockedPeers")]
+        async fn p2p_list_blocked_peers(&self) -> RpcResult<Vec<PeerId>>;
+
+        /// NATStatus returns the current NAT status.
+        #[method(name = "p2p.NATStatus")]
+        async fn p2p_nat_status(&self) -> RpcResult<Reachability>;
+
+        /// PeerInfo returns a small slice of information Peerstore has on the given peer.
+        #[method(name = "p2p.PeerInfo")]
+        async fn p2p_peer_info(&self, peer_id: PeerId) -> RpcResult<AddrInfo>;
+
+        /// Peers returns connected peers.
+        #[method(name = "p2p.Peers")]
+        async fn p2p_peers(&self) -> RpcResult<Vec<PeerId>>;
+
+        /// Protect adds a peer to the list of peers who have a bidirectional peering agreement that they are protected from being trimmed, dropped or negatively scored.
+        #[method(name = "p2p.Protect")]
+        async fn p2p_protect(&self, peer_id: PeerId, tag: String) -> RpcResult<()>;
+
+        // We might get null in response here, so Option is needed
+        /// PubSubPeers returns the peer IDs of the peers joined on the given topic.
+        #[method(name = "p2p.PubSubPeers")]
+        async fn p2p_pub_sub_peers(&self, topic: String) -> RpcResult<Option<Vec<PeerId>>>;
+
+        /// ResourceState returns the state of the resource manager.
+        #[method(name = "p2p.ResourceState")]
+        async fn p2p_resource_state(&self) -> RpcResult<ResourceManagerStats>;
+
+        /// UnblockPeer removes a peer from the set of blocked peers.
+        #[method(name = "p2p.UnblockPeer")]
+        async fn p2p_unblock_peer(&self, peer_id: PeerId) -> RpcResult<()>;
+
+        /// Unprotect removes a peer from the list of peers who have a bidirectional peering agreement that they are protected from being trimmed, dropped or negatively scored, returning a bool representing whether the given peer is protected or not.
+        #[method(name = "p2p.Unprotect")]
+        async fn p2p_unprotect(&self, peer_id: PeerId, tag: String) -> RpcResult<bool>;
+    }
+}
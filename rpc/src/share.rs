@@ -53,3 +53,51 @@ pub trait Share {
     #[method(name = "share.SharesAvailable")]
     async fn share_shares_available(&self, root: &ExtendedHeader) -> Result<(), Error>;
 }
+
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub use self::server::ShareServer;
+
+#[cfg(feature = "server")]
+mod server {
+    use super::*;
+    use jsonrpsee::core::RpcResult;
+
+    /// Server side of the [`ShareClient`](super::ShareClient) API, taking the arguments by value.
+    #[rpc(server)]
+    pub trait Share {
+        /// GetEDS gets the full EDS identified by the given root.
+        #[method(name = "share.GetEDS")]
+        async fn share_get_eds(&self, root: ExtendedHeader) -> RpcResult<ExtendedDataSquare>;
+
+        /// GetRange gets a list of shares and their corresponding proof.
+        #[method(name = "share.GetRange")]
+        async fn share_get_range(
+            &self,
+            height: u64,
+            start: usize,
+            end: usize,
+        ) -> RpcResult<GetRangeResponse>;
+
+        /// GetShare gets a Share by coordinates in EDS.
+        #[method(name = "share.GetShare")]
+        async fn share_get_share(
+            &self,
+            root: ExtendedHeader,
+            row: u64,
+            col: u64,
+        ) -> RpcResult<Share>;
+
+        /// GetSharesByNamespace gets all shares from an EDS within the given namespace. Shares are returned in a row-by-row order if the namespace spans multiple rows.
+        #[method(name = "share.GetSharesByNamespace")]
+        async fn share_get_shares_by_namespace(
+            &self,
+            root: ExtendedHeader,
+            namespace: Namespace,
+        ) -> RpcResult<NamespacedShares>;
+
+        /// SharesAvailable subjectively validates if Shares committed to the given Root are available on the Network.
+        #[method(name = "share.SharesAvailable")]
+        async fn share_shares_available(&self, root: ExtendedHeader) -> RpcResult<()>;
+    }
+}
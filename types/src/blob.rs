@@ -1,12 +1,16 @@
 //! Types related to creation and submission of blobs.
 
+use std::iter::Peekable;
+
 use celestia_tendermint_proto::v0_34::types::Blob as RawBlob;
 use celestia_tendermint_proto::Protobuf;
 use serde::{Deserialize, Serialize};
 
 mod commitment;
+mod msg_pay_for_blobs;
 
 pub use self::commitment::Commitment;
+pub use self::msg_pay_for_blobs::MsgPayForBlobs;
 use crate::consts::appconsts;
 use crate::nmt::Namespace;
 use crate::{bail_validation, Error, Result, Share};
@@ -108,6 +112,106 @@ impl Blob {
         Ok(())
     }
 
+    /// Reconstruct a [`Blob`] from the sequence of [`Share`]s it was split into.
+    ///
+    /// The first share has to start a sequence and the rest need to be its continuation
+    /// shares. The amount of shares must match exactly the sequence length encoded
+    /// in the first share.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if shares don't form a valid sequence,
+    /// use unsupported share version, or if the [`Commitment`] computed from the shares
+    /// doesn't match the one computed from the reconstructed data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use celestia_types::Blob;
+    /// # use celestia_types::nmt::Namespace;
+    /// # let namespace = Namespace::new_v0(&[1, 2, 3, 4, 5]).expect("Invalid namespace");
+    ///
+    /// let blob = Blob::new(namespace, vec![7; 1024]).unwrap();
+    /// let shares = blob.to_shares().unwrap();
+    ///
+    /// let reconstructed = Blob::reconstruct(&shares).unwrap();
+    ///
+    /// assert_eq!(blob, reconstructed);
+    /// ```
+    pub fn reconstruct<'a, I>(shares: I) -> Result<Blob>
+    where
+        I: IntoIterator<Item = &'a Share>,
+    {
+        let mut shares = shares.into_iter().map(|share| (None, share)).peekable();
+
+        let Some(blob) = reconstruct_next(&mut shares)? else {
+            bail_validation!("no shares provided");
+        };
+
+        if shares.next().is_some() {
+            bail_validation!("shares contain more than a single blob");
+        }
+
+        Ok(blob)
+    }
+
+    /// Reconstruct all the [`Blob`]s from the sequence of [`Share`]s of a namespace.
+    ///
+    /// Shares are parsed as consecutive sequences, skipping the namespace padding shares.
+    /// Produced blobs don't have the `index` set, use [`Blob::reconstruct_all_indexed`] for that.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the sequences is not valid.
+    /// See [`Blob::reconstruct`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use celestia_types::Blob;
+    /// # use celestia_types::nmt::Namespace;
+    /// # let namespace = Namespace::new_v0(&[1, 2, 3, 4, 5]).expect("Invalid namespace");
+    ///
+    /// let blobs = vec![
+    ///     Blob::new(namespace, vec![1; 100]).unwrap(),
+    ///     Blob::new(namespace, vec![2; 1000]).unwrap(),
+    /// ];
+    /// let shares: Vec<_> = blobs
+    ///     .iter()
+    ///     .flat_map(|blob| blob.to_shares().unwrap())
+    ///     .collect();
+    ///
+    /// let reconstructed = Blob::reconstruct_all(&shares).unwrap();
+    ///
+    /// assert_eq!(blobs, reconstructed);
+    /// ```
+    pub fn reconstruct_all<'a, I>(shares: I) -> Result<Vec<Blob>>
+    where
+        I: IntoIterator<Item = &'a Share>,
+    {
+        reconstruct_all(shares.into_iter().map(|share| (None, share)))
+    }
+
+    /// Reconstruct all the [`Blob`]s from the sequence of [`Share`]s with their indexes in the EDS.
+    ///
+    /// Works the same as [`Blob::reconstruct_all`], but sets the `index` of each blob
+    /// to the index of its first share.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the sequences is not valid.
+    /// See [`Blob::reconstruct`] for more details.
+    pub fn reconstruct_all_indexed<'a, I>(shares: I) -> Result<Vec<Blob>>
+    where
+        I: IntoIterator<Item = (u64, &'a Share)>,
+    {
+        reconstruct_all(
+            shares
+                .into_iter()
+                .map(|(index, share)| (Some(index), share)),
+        )
+    }
+
     /// Encode the blob into a sequence of shares.
     ///
     /// Check the [`Share`] documentation for more information about the share format.
@@ -168,6 +272,110 @@ impl From<Blob> for RawBlob {
     }
 }
 
+fn reconstruct_all<'a, I>(shares: I) -> Result<Vec<Blob>>
+where
+    I: Iterator<Item = (Option<u64>, &'a Share)>,
+{
+    let mut shares = shares.peekable();
+    let mut blobs = Vec::new();
+
+    while let Some(blob) = reconstruct_next(&mut shares)? {
+        blobs.push(blob);
+    }
+
+    Ok(blobs)
+}
+
+/// Reconstruct the next blob from the shares, skipping any namespace padding shares.
+///
+/// Returns `None` if there are no more shares.
+fn reconstruct_next<'a, I>(shares: &mut Peekable<I>) -> Result<Option<Blob>>
+where
+    I: Iterator<Item = (Option<u64>, &'a Share)>,
+{
+    // Namespace padding shares start a sequence of zero length
+    while shares
+        .next_if(|(_, share)| share.sequence_length() == Some(0))
+        .is_some()
+    {}
+
+    let Some((index, first_share)) = shares.next() else {
+        return Ok(None);
+    };
+
+    let Some(sequence_len) = first_share.sequence_length() else {
+        bail_validation!("expected share starting a sequence");
+    };
+
+    let namespace = first_share.namespace();
+    let share_version = first_share.info_byte().version();
+
+    if share_version != appconsts::SHARE_VERSION_ZERO {
+        return Err(Error::UnsupportedShareVersion(share_version));
+    }
+
+    let sequence_len = sequence_len as usize;
+    let shares_needed = 1 + sequence_len
+        .saturating_sub(appconsts::FIRST_SPARSE_SHARE_CONTENT_SIZE)
+        .div_ceil(appconsts::CONTINUATION_SPARSE_SHARE_CONTENT_SIZE);
+
+    // Sequence length comes from untrusted share, so don't allocate more
+    // than the shares we actually have can fill.
+    let shares_available = 1 + shares.size_hint().0;
+    let mut blob_shares = Vec::with_capacity(shares_needed.min(shares_available));
+    let mut data = Vec::with_capacity(sequence_len.min(shares_available * appconsts::SHARE_SIZE));
+
+    blob_shares.push(first_share.clone());
+    data.extend_from_slice(
+        &first_share.data[appconsts::SHARE_SIZE - appconsts::FIRST_SPARSE_SHARE_CONTENT_SIZE..],
+    );
+
+    while blob_shares.len() < shares_needed {
+        let Some((_, share)) = shares.next() else {
+            bail_validation!(
+                "missing shares of the sequence, expected {shares_needed}, got {}",
+                blob_shares.len()
+            );
+        };
+
+        if share.namespace() != namespace {
+            bail_validation!("share namespace differs from the sequence namespace");
+        }
+
+        if share.info_byte().is_sequence_start() {
+            bail_validation!("unexpected share starting a new sequence");
+        }
+
+        if share.info_byte().version() != share_version {
+            bail_validation!("share version differs from the sequence version");
+        }
+
+        blob_shares.push(share.clone());
+        data.extend_from_slice(
+            &share.data
+                [appconsts::SHARE_SIZE - appconsts::CONTINUATION_SPARSE_SHARE_CONTENT_SIZE..],
+        );
+    }
+
+    data.truncate(sequence_len);
+
+    let commitment = Commitment::from_blob(namespace, share_version, &data)?;
+
+    // Make sure that shares are exactly the ones that the data would be split into,
+    // including the padding, by comparing their commitments.
+    if commitment != Commitment::from_shares(namespace, &blob_shares)? {
+        bail_validation!("blob commitment != commitment computed from shares");
+    }
+
+    Ok(Some(Blob {
+        namespace,
+        data,
+        share_version,
+        commitment,
+        index,
+    }))
+}
+
 mod index_serde {
     use serde::ser::Error;
     use serde::{Deserialize, Deserializer, Serializer};
@@ -197,6 +405,9 @@ mod index_serde {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::nmt::NS_SIZE;
+    use crate::share::InfoByte;
+    use crate::test_utils::random_bytes;
 
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::wasm_bindgen_test as test;
@@ -236,6 +447,137 @@ mod tests {
         blob.validate().unwrap_err();
     }
 
+    fn namespace_padding_share(namespace: Namespace) -> Share {
+        let mut raw = vec![0; appconsts::SHARE_SIZE];
+        raw[..NS_SIZE].copy_from_slice(namespace.as_bytes());
+        raw[NS_SIZE] = InfoByte::new(appconsts::SHARE_VERSION_ZERO, true)
+            .unwrap()
+            .as_u8();
+        Share::from_raw(&raw).unwrap()
+    }
+
+    #[test]
+    fn reconstruct() {
+        for len in [1, 478, 479, 480, 960, 961, 10_000] {
+            let blob = Blob::new(sample_blob().namespace, random_bytes(len)).unwrap();
+            let shares = blob.to_shares().unwrap();
+
+            let reconstructed = Blob::reconstruct(&shares).unwrap();
+            assert_eq!(reconstructed, blob);
+        }
+    }
+
+    #[test]
+    fn reconstruct_all() {
+        let namespace = sample_blob().namespace;
+        let blobs: Vec<_> = [100, 2000, 10, 512]
+            .into_iter()
+            .map(|len| Blob::new(namespace, random_bytes(len)).unwrap())
+            .collect();
+
+        let mut shares = vec![namespace_padding_share(namespace)];
+        for blob in &blobs {
+            shares.extend(blob.to_shares().unwrap());
+            shares.push(namespace_padding_share(namespace));
+        }
+
+        let reconstructed = Blob::reconstruct_all(&shares).unwrap();
+        assert_eq!(reconstructed, blobs);
+
+        let reconstructed = Blob::reconstruct_all_indexed(
+            shares
+                .iter()
+                .enumerate()
+                .map(|(idx, share)| (idx as u64 + 10, share)),
+        )
+        .unwrap();
+        let indexes: Vec<_> = reconstructed.iter().map(|blob| blob.index).collect();
+        assert_eq!(indexes, vec![Some(11), Some(13), Some(19), Some(21)]);
+    }
+
+    #[test]
+    fn reconstruct_empty() {
+        Blob::reconstruct(&[]).unwrap_err();
+        assert!(Blob::reconstruct_all(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reconstruct_missing_shares() {
+        let blob = Blob::new(sample_blob().namespace, random_bytes(2000)).unwrap();
+        let shares = blob.to_shares().unwrap();
+
+        Blob::reconstruct(&shares[..shares.len() - 1]).unwrap_err();
+        Blob::reconstruct(&shares[1..]).unwrap_err();
+    }
+
+    #[test]
+    fn reconstruct_more_than_single_blob() {
+        let namespace = sample_blob().namespace;
+        let mut shares = Blob::new(namespace, random_bytes(100))
+            .unwrap()
+            .to_shares()
+            .unwrap();
+        shares.extend(
+            Blob::new(namespace, random_bytes(100))
+                .unwrap()
+                .to_shares()
+                .unwrap(),
+        );
+
+        Blob::reconstruct(&shares).unwrap_err();
+    }
+
+    #[test]
+    fn reconstruct_unexpected_sequence_start() {
+        let namespace = sample_blob().namespace;
+        let mut shares = Blob::new(namespace, random_bytes(2000))
+            .unwrap()
+            .to_shares()
+            .unwrap();
+        shares[1] = Blob::new(namespace, random_bytes(100))
+            .unwrap()
+            .to_shares()
+            .unwrap()
+            .remove(0);
+
+        Blob::reconstruct(&shares).unwrap_err();
+    }
+
+    #[test]
+    fn reconstruct_huge_sequence_length() {
+        let mut shares = Blob::new(sample_blob().namespace, random_bytes(2000))
+            .unwrap()
+            .to_shares()
+            .unwrap();
+        shares[0].data[NS_SIZE + 1..NS_SIZE + 5].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        let err = Blob::reconstruct(&shares).unwrap_err();
+        assert!(matches!(err, Error::Validation(_)));
+    }
+
+    #[test]
+    fn reconstruct_namespace_mismatch() {
+        let mut shares = Blob::new(sample_blob().namespace, random_bytes(2000))
+            .unwrap()
+            .to_shares()
+            .unwrap();
+        shares[1].data[NS_SIZE - 1] ^= 1;
+
+        Blob::reconstruct(&shares).unwrap_err();
+    }
+
+    #[test]
+    fn reconstruct_non_zero_padding() {
+        let mut shares = Blob::new(sample_blob().namespace, random_bytes(10))
+            .unwrap()
+            .to_shares()
+            .unwrap();
+        shares[0].data[appconsts::SHARE_SIZE - 1] = 1;
+
+        let err = Blob::reconstruct(&shares).unwrap_err();
+        assert!(matches!(err, Error::Validation(_)));
+    }
+
     #[test]
     fn deserialize_blob_with_missing_index() {
         serde_json::from_str::<Blob>(
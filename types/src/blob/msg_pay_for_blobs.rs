@@ -0,0 +1,189 @@
+use celestia_proto::celestia::blob::v1::MsgPayForBlobs as RawMsgPayForBlobs;
+use celestia_tendermint_proto::Protobuf;
+use serde::{Deserialize, Serialize};
+
+use crate::blob::{Blob, Commitment};
+use crate::nmt::Namespace;
+use crate::state::AccAddress;
+use crate::{bail_validation, Error, Result};
+
+/// A message paying for the inclusion of one or more [`Blob`]s in the block.
+///
+/// It carries only the metadata of the blobs, while their data is attached
+/// next to the signed transaction in the [`BlobTx`].
+///
+/// [`BlobTx`]: crate::signer::BlobTx
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawMsgPayForBlobs", into = "RawMsgPayForBlobs")]
+pub struct MsgPayForBlobs {
+    /// Address of the account paying for the blobs.
+    pub signer: AccAddress,
+    /// [`Namespace`]s of the blobs.
+    pub namespaces: Vec<Namespace>,
+    /// Sizes of the blobs' data in bytes.
+    pub blob_sizes: Vec<u32>,
+    /// [`Commitment`]s of the blobs.
+    pub share_commitments: Vec<Commitment>,
+    /// Share versions used to compute the [`Commitment`]s.
+    pub share_versions: Vec<u32>,
+}
+
+impl MsgPayForBlobs {
+    /// The type URL of the message, used when packing it into the transaction.
+    pub const TYPE_URL: &'static str = "/celestia.blob.v1.MsgPayForBlobs";
+
+    /// Create a new message paying for the given [`Blob`]s.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no blobs are provided or if
+    /// any of them is too large to be paid for.
+    pub fn new(blobs: &[Blob], signer: AccAddress) -> Result<Self> {
+        if blobs.is_empty() {
+            return Err(Error::EmptyBlobList);
+        }
+
+        let blob_sizes = blobs
+            .iter()
+            .map(|blob| {
+                u32::try_from(blob.data.len()).map_err(|_| Error::BlobTooLarge(blob.data.len()))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(MsgPayForBlobs {
+            signer,
+            namespaces: blobs.iter().map(|blob| blob.namespace).collect(),
+            blob_sizes,
+            share_commitments: blobs.iter().map(|blob| blob.commitment).collect(),
+            share_versions: blobs
+                .iter()
+                .map(|blob| u32::from(blob.share_version))
+                .collect(),
+        })
+    }
+}
+
+impl Protobuf<RawMsgPayForBlobs> for MsgPayForBlobs {}
+
+impl TryFrom<RawMsgPayForBlobs> for MsgPayForBlobs {
+    type Error = Error;
+
+    fn try_from(value: RawMsgPayForBlobs) -> Result<Self, Self::Error> {
+        let blobs_count = value.namespaces.len();
+
+        if blobs_count == 0 {
+            return Err(Error::EmptyBlobList);
+        }
+
+        if value.blob_sizes.len() != blobs_count
+            || value.share_commitments.len() != blobs_count
+            || value.share_versions.len() != blobs_count
+        {
+            bail_validation!(
+                "namespaces ({blobs_count}), blob sizes ({}), share commitments ({}) and share versions ({}) lengths differ",
+                value.blob_sizes.len(),
+                value.share_commitments.len(),
+                value.share_versions.len()
+            );
+        }
+
+        let namespaces = value
+            .namespaces
+            .iter()
+            .map(|ns| Namespace::from_raw(ns))
+            .collect::<Result<_>>()?;
+
+        let share_commitments = value
+            .share_commitments
+            .iter()
+            .map(|commitment| {
+                commitment
+                    .as_slice()
+                    .try_into()
+                    .map(Commitment)
+                    .map_err(|_| Error::InvalidCommitmentLength(commitment.len()))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(MsgPayForBlobs {
+            signer: value.signer.parse()?,
+            namespaces,
+            blob_sizes: value.blob_sizes,
+            share_commitments,
+            share_versions: value.share_versions,
+        })
+    }
+}
+
+impl From<MsgPayForBlobs> for RawMsgPayForBlobs {
+    fn from(value: MsgPayForBlobs) -> Self {
+        RawMsgPayForBlobs {
+            signer: value.signer.to_string(),
+            namespaces: value
+                .namespaces
+                .iter()
+                .map(|ns| ns.as_bytes().to_vec())
+                .collect(),
+            blob_sizes: value.blob_sizes,
+            share_commitments: value
+                .share_commitments
+                .iter()
+                .map(|commitment| commitment.0.to_vec())
+                .collect(),
+            share_versions: value.share_versions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use celestia_tendermint::account::Id;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn signer() -> AccAddress {
+        AccAddress::new(Id::new([7; 20]))
+    }
+
+    #[test]
+    fn new_from_blobs() {
+        let ns1 = Namespace::new_v0(&[1]).unwrap();
+        let ns2 = Namespace::new_v0(&[2]).unwrap();
+        let blobs = vec![
+            Blob::new(ns1, vec![1; 100]).unwrap(),
+            Blob::new(ns2, vec![2; 1000]).unwrap(),
+        ];
+
+        let msg = MsgPayForBlobs::new(&blobs, signer()).unwrap();
+
+        assert_eq!(msg.namespaces, vec![ns1, ns2]);
+        assert_eq!(msg.blob_sizes, vec![100, 1000]);
+        assert_eq!(
+            msg.share_commitments,
+            vec![blobs[0].commitment, blobs[1].commitment]
+        );
+        assert_eq!(msg.share_versions, vec![0, 0]);
+
+        let decoded = MsgPayForBlobs::decode_vec(&msg.encode_vec().unwrap()).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn new_without_blobs() {
+        assert!(matches!(
+            MsgPayForBlobs::new(&[], signer()),
+            Err(Error::EmptyBlobList)
+        ));
+    }
+
+    #[test]
+    fn raw_with_mismatched_lengths() {
+        let blob = Blob::new(Namespace::new_v0(&[1]).unwrap(), vec![1; 10]).unwrap();
+        let mut raw = RawMsgPayForBlobs::from(MsgPayForBlobs::new(&[blob], signer()).unwrap());
+        raw.blob_sizes.push(10);
+
+        MsgPayForBlobs::try_from(raw).unwrap_err();
+    }
+}
@@ -2,14 +2,15 @@ use celestia_proto::share::eds::byzantine::pb::BadEncoding as RawBadEncodingFrau
 use celestia_proto::share::eds::byzantine::pb::Share as RawShareWithProof;
 use celestia_tendermint::{block::Height, Hash};
 use celestia_tendermint_proto::Protobuf;
+use nmt_rs::NamespaceProof as NmtNamespaceProof;
 use serde::{Deserialize, Serialize};
 
 use crate::bail_validation;
 use crate::consts::appconsts;
 use crate::fraud_proof::FraudProof;
 use crate::nmt::{Namespace, NamespaceProof, Nmt, NmtExt, NS_SIZE};
-use crate::rsmt2d::AxisType;
-use crate::{Error, ExtendedHeader, Result};
+use crate::rsmt2d::{is_ods_square, AxisType};
+use crate::{Error, ExtendedDataSquare, ExtendedHeader, Result};
 
 /// A proof that the block producer incorrectly encoded [`ExtendedDataSquare`].
 ///
@@ -43,6 +44,80 @@ pub struct BadEncodingFraudProof {
     axis: AxisType,
 }
 
+impl BadEncodingFraudProof {
+    /// Create a [`BadEncodingFraudProof`] for the incorrectly encoded row or column of the
+    /// [`ExtendedDataSquare`].
+    ///
+    /// Each share of the axis is proven with the [`Nmt`] of the orthogonal axis, so
+    /// [`ExtendedDataSquare`] must match the [`DataAvailabilityHeader`] of the `header`.
+    /// Such square can be obtained e.g. with [`ExtendedDataSquare::repair`], which reports
+    /// the incorrectly encoded axis as [`Error::ByzantineData`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if index is out of range of the [`ExtendedDataSquare`], or if
+    /// created proof doesn't pass the validation against `header`, e.g. because given
+    /// axis is encoded correctly.
+    ///
+    /// [`ExtendedDataSquare`]: crate::ExtendedDataSquare
+    /// [`ExtendedDataSquare::repair`]: crate::ExtendedDataSquare::repair
+    /// [`DataAvailabilityHeader`]: crate::DataAvailabilityHeader
+    pub fn create(
+        eds: &ExtendedDataSquare,
+        header: &ExtendedHeader,
+        axis: AxisType,
+        index: u16,
+    ) -> Result<Self> {
+        let square_width = eds.square_width();
+
+        if index >= square_width {
+            return Err(Error::IndexOutOfRange(index.into(), square_width.into()));
+        }
+
+        let proof_axis = match axis {
+            AxisType::Row => AxisType::Col,
+            AxisType::Col => AxisType::Row,
+        };
+
+        let shares = (0..square_width)
+            .map(|share_idx| {
+                let (share, proof) = eds
+                    .axis_nmt(proof_axis, share_idx)?
+                    .get_index_with_proof(index.into());
+
+                // it doesn't matter which is row and which is column as ods is first quadrant
+                let namespace = if is_ods_square(index, share_idx, square_width) {
+                    Namespace::from_raw(&share[..NS_SIZE])?
+                } else {
+                    Namespace::PARITY_SHARE
+                };
+
+                Ok(Some(ShareWithProof {
+                    leaf: NmtLeaf { namespace, share },
+                    proof: NmtNamespaceProof::PresenceProof {
+                        proof,
+                        ignore_max_ns: true,
+                    }
+                    .into(),
+                    proof_axis,
+                }))
+            })
+            .collect::<Result<_>>()?;
+
+        let befp = BadEncodingFraudProof {
+            header_hash: header.hash(),
+            block_height: header.height(),
+            shares,
+            index,
+            axis,
+        };
+
+        befp.validate(header)?;
+
+        Ok(befp)
+    }
+}
+
 impl FraudProof for BadEncodingFraudProof {
     const TYPE: &'static str = "badencoding";
 
@@ -152,10 +227,15 @@ impl FraudProof for BadEncodingFraudProof {
 
         let mut nmt = Nmt::default();
 
-        for (n, share) in rebuilt_shares.iter().enumerate() {
-            let ns = if n < ods_width {
-                // safety: length must be correct
-                Namespace::from_raw(&share[..NS_SIZE]).unwrap()
+        for (n, share) in (0..header.dah.square_width()).zip(&rebuilt_shares) {
+            // it doesn't matter which is row and which is column as ods is first quadrant
+            let ns = if is_ods_square(self.index, n, header.dah.square_width()) {
+                let Ok(ns) = Namespace::from_raw(&share[..NS_SIZE]) else {
+                    // we couldn't rebuild the nmt from reconstructed data
+                    // befp is legit
+                    return Ok(());
+                };
+                ns
             } else {
                 Namespace::PARITY_SHARE
             };
@@ -304,9 +384,8 @@ pub(crate) mod test_utils {
     use rand::Rng;
 
     use crate::consts::appconsts::{FIRST_SPARSE_SHARE_CONTENT_SIZE, SHARE_SIZE};
-    use crate::rsmt2d::is_ods_square;
     use crate::test_utils::{random_bytes, ExtendedHeaderGenerator};
-    use crate::{DataAvailabilityHeader, ExtendedDataSquare};
+    use crate::DataAvailabilityHeader;
 
     use super::*;
 
@@ -417,6 +496,7 @@ pub(crate) mod test_utils {
 mod tests {
     use self::test_utils::befp_from_header_and_eds;
     use super::*;
+    use crate::rsmt2d::axis_nmt;
     use crate::test_utils::{corrupt_eds, generate_eds, ExtendedHeaderGenerator};
     use crate::DataAvailabilityHeader;
 
@@ -474,6 +554,47 @@ mod tests {
         proof.validate(&eh).unwrap_err();
     }
 
+    #[test]
+    fn create_befp() {
+        let mut gen = ExtendedHeaderGenerator::new();
+        let mut eds = generate_eds(8);
+        let width = eds.square_width();
+
+        // corrupt the parity share of the third row
+        eds.share_mut(2, width - 1).unwrap()[0] ^= 0xff;
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let eh = gen.next_with_dah(dah.clone());
+
+        let shares = eds.data_square().iter().cloned().map(Some).collect();
+        let err = ExtendedDataSquare::repair(shares, &dah).unwrap_err();
+        let Error::ByzantineData(axis, index) = err else {
+            panic!("unexpected error: {err}");
+        };
+
+        let proof = BadEncodingFraudProof::create(&eds, &eh, axis, index).unwrap();
+        assert_eq!(proof.axis, AxisType::Row);
+        assert_eq!(proof.index, 2);
+        assert_eq!(proof.header_hash(), eh.hash());
+
+        let decoded = BadEncodingFraudProof::decode(&proof.encode_vec().unwrap()[..]).unwrap();
+        decoded.validate(&eh).unwrap();
+
+        // corrupted share is also a part of the last column
+        BadEncodingFraudProof::create(&eds, &eh, AxisType::Col, width - 1).unwrap();
+    }
+
+    #[test]
+    fn create_befp_over_correct_data() {
+        let mut gen = ExtendedHeaderGenerator::new();
+        let eds = generate_eds(8);
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let eh = gen.next_with_dah(dah);
+
+        BadEncodingFraudProof::create(&eds, &eh, AxisType::Row, 2).unwrap_err();
+        BadEncodingFraudProof::create(&eds, &eh, AxisType::Col, 3).unwrap_err();
+        BadEncodingFraudProof::create(&eds, &eh, AxisType::Row, 8).unwrap_err();
+    }
+
     #[test]
     fn validate_befp_wrong_height() {
         let mut gen = ExtendedHeaderGenerator::new();
@@ -518,4 +639,94 @@ mod tests {
 
         proof.validate(&eh).unwrap_err();
     }
+
+    #[test]
+    fn validate_befp_on_parity_axis() {
+        let mut gen = ExtendedHeaderGenerator::new();
+        let mut eds = generate_eds(8);
+        let width = eds.square_width();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let eh = gen.next_with_dah(dah);
+
+        // all shares of parity axes are leaves of the parity namespace
+        for index in width / 2..width {
+            let proof = befp_from_header_and_eds(&eh, &eds, index, AxisType::Row);
+            proof.validate(&eh).unwrap_err();
+
+            let proof = befp_from_header_and_eds(&eh, &eds, index, AxisType::Col);
+            proof.validate(&eh).unwrap_err();
+        }
+
+        // corrupt the first share of the last row
+        eds.share_mut(width - 1, 0).unwrap()[0] ^= 0xff;
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let eh = gen.next_with_dah(dah);
+
+        let proof = befp_from_header_and_eds(&eh, &eds, width - 1, AxisType::Row);
+        proof.validate(&eh).unwrap();
+    }
+
+    #[test]
+    fn validate_befp_with_invalid_namespace_in_reconstructed_data() {
+        let mut gen = ExtendedHeaderGenerator::new();
+        let eds = generate_eds(8);
+        let width = eds.square_width();
+        let ods_width = usize::from(width / 2);
+
+        // re-encode the first row with an invalid namespace in its first share
+        let mut row = eds.row(0).unwrap();
+        row[0][1] = 0xff;
+        Namespace::from_raw(&row[0][..NS_SIZE]).unwrap_err();
+        leopard_codec::encode(&mut row, ods_width).unwrap();
+
+        // columns with the re-encoded row, except the first one which can't be
+        // built, but it isn't used by the proof
+        let mut dah = DataAvailabilityHeader::from_eds(&eds);
+        let mut column_nmts = Vec::new();
+        for col in 1..width {
+            let mut column = eds.column(col).unwrap();
+            column[0] = row[usize::from(col)].clone();
+            let column: Vec<_> = column.iter().map(Vec::as_slice).collect();
+
+            let nmt = axis_nmt(&column, col, width).unwrap();
+            column_nmts.push(nmt);
+        }
+        let mut column_roots = dah.column_roots().to_vec();
+        for (root, nmt) in column_roots[1..].iter_mut().zip(&mut column_nmts) {
+            *root = nmt.root();
+        }
+        dah = DataAvailabilityHeader::new_unchecked(dah.row_roots().to_vec(), column_roots);
+        let eh = gen.next_with_dah(dah);
+
+        // the share with invalid namespace needs to be reconstructed
+        let mut shares = vec![None];
+        for (col, nmt) in (1..width).zip(&mut column_nmts) {
+            let (share, proof) = nmt.get_index_with_proof(0);
+            let namespace = if is_ods_square(0, col, width) {
+                Namespace::from_raw(&share[..NS_SIZE]).unwrap()
+            } else {
+                Namespace::PARITY_SHARE
+            };
+
+            shares.push(Some(ShareWithProof {
+                leaf: NmtLeaf { namespace, share },
+                proof: NmtNamespaceProof::PresenceProof {
+                    proof,
+                    ignore_max_ns: true,
+                }
+                .into(),
+                proof_axis: AxisType::Col,
+            }));
+        }
+
+        let proof = BadEncodingFraudProof {
+            header_hash: eh.hash(),
+            block_height: eh.height(),
+            shares,
+            index: 0,
+            axis: AxisType::Row,
+        };
+
+        proof.validate(&eh).unwrap();
+    }
 }
@@ -32,6 +32,8 @@ pub mod appconsts {
         pub const SUBTREE_ROOT_THRESHOLD: u64 = 64;
         /// Maximum width of the original data square.
         pub const SQUARE_SIZE_UPPER_BOUND: usize = 128;
+        /// Gas consumed per byte of the blob's shares.
+        pub const GAS_PER_BLOB_BYTE: u64 = 8;
     }
 
     // celestia-app/pkg/appconsts/global_consts
@@ -86,6 +88,30 @@ pub mod appconsts {
 
         /// The maximum value a share version can be.
         pub const MAX_SHARE_VERSION: u8 = 127;
+
+        /// The default minimum gas price accepted by validators, in `utia` per gas unit.
+        pub const DEFAULT_MIN_GAS_PRICE: f64 = 0.002;
+
+        /// The denomination of the native token.
+        pub const BOND_DENOM: &str = "utia";
+
+        /// The type id marking a [`BlobTx`] wire encoding.
+        ///
+        /// [`BlobTx`]: crate::signer::BlobTx
+        pub const BLOB_TX_TYPE_ID: &str = "BLOB";
+
+        /// The type id marking an index wrapper of the transaction paying for blobs,
+        /// as stored in the [`PAY_FOR_BLOB`] namespace shares.
+        ///
+        /// [`PAY_FOR_BLOB`]: crate::nmt::Namespace::PAY_FOR_BLOB
+        pub const INDEX_WRAPPER_TYPE_ID: &str = "INDX";
+
+        // celestia-app/x/blob/types/payforblob
+        /// Fixed gas cost of the `MsgPayForBlobs`, excluding the blobs.
+        pub const PFB_GAS_FIXED_COST: u64 = 75_000;
+
+        /// Approximate number of bytes each blob adds to the `MsgPayForBlobs`.
+        pub const BYTES_PER_BLOB_INFO: u64 = 70;
     }
 }
 
@@ -133,4 +159,7 @@ pub mod cosmos {
 
     /// Bech32PrefixConsPub defines the Bech32 prefix of a consensus node public key.
     pub const BECH32_PREFIX_CONS_PUB: &str = concatcp!(BECH32_PREFIX_CONS_ADDR, PREFIX_PUBLIC);
+
+    /// Default gas consumed per byte of the transaction by the auth module.
+    pub const DEFAULT_TX_SIZE_COST_PER_BYTE: u64 = 10;
 }
@@ -1,4 +1,5 @@
 use crate::consts::appconsts;
+use crate::rsmt2d::AxisType;
 
 /// Alias for a `Result` with the error type [`celestia_types::Error`].
 ///
@@ -176,9 +177,57 @@ pub enum Error {
     #[error("Invalid dimensions of EDS")]
     EdsInvalidDimentions,
 
+    /// Provided shares are not enough to repair the EDS.
+    #[error("Not enough shares to repair EDS")]
+    EdsNotEnoughShares,
+
+    /// Shares of the EDS axis don't match its root in the data availability header.
+    #[error("Byzantine data in {0} {1}")]
+    ByzantineData(AxisType, u16),
+
+    /// Row is a parity row, which doesn't hold any namespaced data.
+    #[error("Row {0} is a parity row")]
+    ParityRow(u16),
+
     /// Zero block height.
     #[error("Invalid zero block height")]
     ZeroBlockHeight,
+
+    /// Paying for an empty list of blobs.
+    #[error("Cannot pay for an empty list of blobs")]
+    EmptyBlobList,
+
+    /// Blob is larger than allowed by the protocol.
+    #[error("Blob too large: {0} bytes")]
+    BlobTooLarge(usize),
+
+    /// Invalid length of the share commitment.
+    #[error("Invalid commitment length: {0}")]
+    InvalidCommitmentLength(usize),
+
+    /// Invalid secp256k1 private key.
+    #[error("Invalid secp256k1 private key")]
+    InvalidPrivateKey,
+
+    /// Signer address of the transaction differs from the one of the signing key.
+    #[error("Signer address {0} doesn't match the signing key address {1}")]
+    SignerAddressMismatch(String, String),
+
+    /// Gas price is not a finite number.
+    #[error("Invalid gas price: {0}")]
+    InvalidGasPrice(f64),
+
+    /// Unsupported type of the account.
+    #[error("Unsupported account type: {0}")]
+    UnsupportedAccountType(String),
+
+    /// Missing base account in the module or vesting account.
+    #[error("Missing base account")]
+    MissingBaseAccount,
+
+    /// Unsupported type of the public key.
+    #[error("Unsupported public key type: {0}")]
+    UnsupportedPublicKeyType(String),
 }
 
 /// Representation of the errors that can occur when validating data.
@@ -20,6 +20,7 @@ pub mod row;
 mod rsmt2d;
 pub mod sample;
 mod share;
+pub mod signer;
 pub mod state;
 mod sync;
 #[cfg(any(test, feature = "test-utils"))]
@@ -54,6 +54,9 @@ pub struct NamespacedData {
 impl NamespacedData {
     /// Verifies proof inside `NamespacedData` using a row root from [`DataAvailabilityHeader`]
     ///
+    /// If `NamespacedData` has no shares, then its proof must be a proof of absence
+    /// of the namespace in the row.
+    ///
     /// #Example
     /// ```no_run
     /// use celestia_types::nmt::Namespace;
@@ -79,7 +82,8 @@ impl NamespacedData {
     ///
     /// [`DataAvailabilityHeader`]: crate::DataAvailabilityHeader
     pub fn verify(&self, dah: &DataAvailabilityHeader) -> Result<()> {
-        if self.shares.is_empty() {
+        // Empty shares are only valid with the proof of absence
+        if self.shares.is_empty() != self.proof.is_of_absence() {
             return Err(Error::WrongProofType);
         }
 
@@ -225,7 +229,10 @@ impl From<NamespacedDataId> for CidGeneric<NAMESPACED_DATA_ID_SIZE> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Share;
+    use crate::consts::appconsts::SHARE_SIZE;
+    use crate::nmt::NS_SIZE;
+    use crate::test_utils::generate_eds;
+    use crate::{ExtendedDataSquare, Share};
 
     #[test]
     fn round_trip() {
@@ -316,4 +323,50 @@ mod tests {
             assert_eq!(s.namespace(), ns);
         }
     }
+
+    #[test]
+    fn verify_presence_proof() {
+        let eds = generate_eds(8);
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let ns = Share::from_raw(eds.share(0, 0).unwrap())
+            .unwrap()
+            .namespace();
+
+        let mut data = eds.get_namespaced_data(ns, &dah, 1).unwrap().remove(0);
+        assert!(!data.proof.is_of_absence());
+        data.verify(&dah).unwrap();
+
+        // presence proof without shares
+        data.shares.clear();
+        let err = data.verify(&dah).unwrap_err();
+        assert!(matches!(err, Error::WrongProofType));
+    }
+
+    #[test]
+    fn verify_absence_proof() {
+        let ns1 = Namespace::new_v0(&[1]).unwrap();
+        let ns2 = Namespace::new_v0(&[2]).unwrap();
+        let ns3 = Namespace::new_v0(&[3]).unwrap();
+
+        let shares = [ns1, ns3, ns3, ns3]
+            .iter()
+            .map(|ns| [ns.as_bytes(), &[0; SHARE_SIZE - NS_SIZE][..]].concat())
+            .collect();
+        let eds = ExtendedDataSquare::from_ods(shares).unwrap();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+
+        let mut data = eds.get_namespaced_data(ns2, &dah, 1).unwrap().remove(0);
+        assert!(data.proof.is_of_absence());
+        assert!(data.shares.is_empty());
+        data.verify(&dah).unwrap();
+
+        // absence proof with shares
+        data.shares = eds
+            .get_namespaced_data(ns1, &dah, 1)
+            .unwrap()
+            .remove(0)
+            .shares;
+        let err = data.verify(&dah).unwrap_err();
+        assert!(matches!(err, Error::WrongProofType));
+    }
 }
@@ -12,13 +12,16 @@ use celestia_proto::share::p2p::shwap::Row as RawRow;
 use celestia_tendermint_proto::Protobuf;
 use cid::CidGeneric;
 use multihash::Multihash;
+use nmt_rs::nmt_proof::NamespaceProof as NmtNamespaceProof;
 use nmt_rs::NamespaceMerkleHasher;
 use serde::{Deserialize, Serialize};
 
 use crate::consts::appconsts::SHARE_SIZE;
+use crate::namespaced_data::{NamespacedData, NamespacedDataId};
 use crate::nmt::NS_SIZE;
 use crate::nmt::{Namespace, NamespacedSha2Hasher, Nmt};
-use crate::rsmt2d::{is_ods_square, ExtendedDataSquare};
+use crate::rsmt2d::{axis_nmt, is_ods_square, AxisType, ExtendedDataSquare};
+use crate::sample::{Sample, SampleId};
 use crate::{DataAvailabilityHeader, Error, Result};
 
 /// Number of bytes needed to represent [`RowId`] in `multihash`.
@@ -84,6 +87,83 @@ impl Row {
 
         Ok(())
     }
+
+    /// Returns the [`Nmt`] of the row.
+    pub fn nmt(&self) -> Result<Nmt> {
+        let square_width =
+            u16::try_from(self.shares.len()).map_err(|_| Error::EdsInvalidDimentions)?;
+        let shares: Vec<_> = self.shares.iter().map(Vec::as_slice).collect();
+
+        axis_nmt(&shares, self.id.index, square_width)
+    }
+
+    /// Create a [`Sample`] of the share in the given column, with a proof against the row root.
+    pub fn sample(&self, column_index: u16) -> Result<Sample> {
+        let share = self
+            .shares
+            .get(usize::from(column_index))
+            .ok_or(Error::EdsIndexOutOfRange(self.id.index, column_index))?
+            .to_owned();
+
+        let range_proof = self
+            .nmt()?
+            .build_range_proof(usize::from(column_index)..usize::from(column_index) + 1);
+
+        let proof = NmtNamespaceProof::PresenceProof {
+            proof: range_proof,
+            ignore_max_ns: true,
+        };
+
+        Ok(Sample {
+            id: SampleId::new(self.id.index, column_index, self.id.block_height)?,
+            proof_type: AxisType::Row,
+            share,
+            proof: proof.into(),
+        })
+    }
+
+    /// Create [`NamespacedData`] with all the shares of the namespace in the row.
+    ///
+    /// If the row doesn't contain the namespace, the result has no shares and
+    /// carries the proof of its absence.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ParityRow`] if the row is not a part of the original data square.
+    pub fn namespaced_data(&self, namespace: Namespace) -> Result<NamespacedData> {
+        let square_width =
+            u16::try_from(self.shares.len()).map_err(|_| Error::EdsInvalidDimentions)?;
+
+        if !is_ods_square(self.id.index, 0, square_width) {
+            return Err(Error::ParityRow(self.id.index));
+        }
+
+        let mut shares = Vec::new();
+
+        for col in 0..square_width {
+            let share = &self.shares[usize::from(col)];
+
+            // Parity shares are never part of any namespace, and shares are
+            // sorted by namespace, so we can stop on the first mismatch after a match.
+            if !is_ods_square(self.id.index, col, square_width) {
+                break;
+            }
+
+            if Namespace::from_raw(&share[..NS_SIZE])? == namespace {
+                shares.push(share.to_owned());
+            } else if !shares.is_empty() {
+                break;
+            }
+        }
+
+        let proof = self.nmt()?.get_namespace_proof(*namespace);
+
+        Ok(NamespacedData {
+            id: NamespacedDataId::new(namespace, self.id.index, self.id.block_height)?,
+            proof: proof.into(),
+            shares,
+        })
+    }
 }
 
 impl Protobuf<RawRow> for Row {}
@@ -341,4 +421,54 @@ mod tests {
             decoded.verify(&dah).unwrap();
         }
     }
+
+    #[test]
+    fn sample_from_row() {
+        let eds = generate_eds(8);
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+
+        for index in 0..eds.square_width() {
+            let row = Row::new(index, &eds, 1).unwrap();
+
+            for column in 0..eds.square_width() {
+                let sample = row.sample(column).unwrap();
+                assert_eq!(sample.id, SampleId::new(index, column, 1).unwrap());
+                sample.verify(&dah).unwrap();
+            }
+
+            row.sample(eds.square_width()).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn namespaced_data_from_row() {
+        let eds = generate_eds(8);
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let namespace = Namespace::from_raw(&eds.share(0, 0).unwrap()[..NS_SIZE]).unwrap();
+        let missing_namespace = Namespace::new_v0(&[1, 2, 3]).unwrap();
+        assert_ne!(namespace, missing_namespace);
+
+        let expected = eds.get_namespaced_data(namespace, &dah, 1).unwrap();
+
+        for expected in expected {
+            let row = Row::new(expected.id.row_index(), &eds, 1).unwrap();
+
+            let data = row.namespaced_data(namespace).unwrap();
+            assert_eq!(data.id, expected.id);
+            assert_eq!(data.shares, expected.shares);
+            data.verify(&dah).unwrap();
+
+            let absent = row.namespaced_data(missing_namespace).unwrap();
+            assert!(absent.shares.is_empty());
+            absent.verify(&dah).unwrap();
+        }
+
+        for index in eds.square_width() / 2..eds.square_width() {
+            let row = Row::new(index, &eds, 1).unwrap();
+            assert!(matches!(
+                row.namespaced_data(namespace).unwrap_err(),
+                Error::ParityRow(i) if i == index
+            ));
+        }
+    }
 }
@@ -299,6 +299,109 @@ impl ExtendedDataSquare {
         ExtendedDataSquare::new(eds_shares, "Leopard".to_string())
     }
 
+    /// Repair the EDS from the partially available shares.
+    ///
+    /// Shares should be provided in a row-major order, with `None` in place of
+    /// the missing ones. Rows and columns are repaired iteratively using the
+    /// [`leopard_codec`], as long as at least a half of their shares is available
+    /// or got repaired already. Provided shares are expected to be already
+    /// verified, e.g. as a parts of [`Sample`]s or [`Row`]s.
+    ///
+    /// After repairing, each row and column is encoded again from its first
+    /// half and its root is compared with the one in [`DataAvailabilityHeader`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    ///  - amount of shares doesn't match the width of the square in [`DataAvailabilityHeader`]
+    ///  - there are not enough shares available to repair the whole square
+    ///  - a repaired row or column doesn't match its root, in which case
+    ///    [`Error::ByzantineData`] is returned with the axis which is badly encoded
+    ///
+    /// Additionally, the same errors as in [`ExtendedDataSquare::new`] applies.
+    ///
+    /// [`Sample`]: crate::sample::Sample
+    /// [`Row`]: crate::row::Row
+    pub fn repair(
+        mut shares: Vec<Option<Vec<u8>>>,
+        dah: &DataAvailabilityHeader,
+    ) -> Result<ExtendedDataSquare> {
+        let square_width = dah.square_width();
+        let width = usize::from(square_width);
+
+        if shares.len() != width * width {
+            return Err(Error::EdsInvalidDimentions);
+        }
+
+        let cell_index = |axis, index: u16, i: u16| {
+            let (row, col) = match axis {
+                AxisType::Row => (index, i),
+                AxisType::Col => (i, index),
+            };
+            usize::from(row) * width + usize::from(col)
+        };
+
+        // Repair rows and columns until there is no more progress
+        let mut progress = true;
+
+        while progress {
+            progress = false;
+
+            for axis in [AxisType::Row, AxisType::Col] {
+                for index in 0..square_width {
+                    let available = (0..square_width)
+                        .filter(|&i| shares[cell_index(axis, index, i)].is_some())
+                        .count();
+
+                    if available == width || available < width / 2 {
+                        continue;
+                    }
+
+                    let mut axis_shares: Vec<_> = (0..square_width)
+                        .map(|i| {
+                            shares[cell_index(axis, index, i)]
+                                .clone()
+                                .unwrap_or_default()
+                        })
+                        .collect();
+
+                    leopard_codec::reconstruct(&mut axis_shares, width / 2)?;
+
+                    for (i, share) in (0..square_width).zip(axis_shares) {
+                        shares[cell_index(axis, index, i)] = Some(share);
+                    }
+
+                    progress = true;
+                }
+            }
+        }
+
+        let data_square = shares
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::EdsNotEnoughShares)?;
+
+        // Check that each row and column is correctly encoded and matches its root
+        for axis in [AxisType::Row, AxisType::Col] {
+            for index in 0..square_width {
+                let mut axis_shares: Vec<_> = (0..square_width)
+                    .map(|i| data_square[cell_index(axis, index, i)].clone())
+                    .collect();
+
+                leopard_codec::encode(&mut axis_shares, width / 2)?;
+
+                let axis_shares: Vec<_> = axis_shares.iter().map(Vec::as_slice).collect();
+                let root = axis_nmt(&axis_shares, index, square_width)?.root();
+
+                if Some(root) != dah.root(axis, index) {
+                    return Err(Error::ByzantineData(axis, index));
+                }
+            }
+        }
+
+        ExtendedDataSquare::new(data_square, "Leopard".to_string())
+    }
+
     /// The raw data of the EDS.
     pub fn data_square(&self) -> &[Vec<u8>] {
         &self.data_square
@@ -366,26 +469,18 @@ impl ExtendedDataSquare {
 
     /// Returns the [`Nmt`] of column or row.
     pub fn axis_nmt(&self, axis: AxisType, index: u16) -> Result<Nmt> {
-        let mut tree = Nmt::default();
-
-        for i in 0..self.square_width {
-            let (row, col) = match axis {
-                AxisType::Row => (index, i),
-                AxisType::Col => (i, index),
-            };
-
-            let share = self.share(row, col)?;
-
-            let ns = if is_ods_square(col, row, self.square_width) {
-                Namespace::from_raw(&share[..NS_SIZE])?
-            } else {
-                Namespace::PARITY_SHARE
-            };
+        let shares = (0..self.square_width)
+            .map(|i| {
+                let (row, col) = match axis {
+                    AxisType::Row => (index, i),
+                    AxisType::Col => (i, index),
+                };
 
-            tree.push_leaf(share, *ns).map_err(Error::Nmt)?;
-        }
+                self.share(row, col)
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        Ok(tree)
+        axis_nmt(&shares, index, self.square_width)
     }
 
     /// Get EDS square length.
@@ -469,6 +564,27 @@ impl<'de> Deserialize<'de> for ExtendedDataSquare {
     }
 }
 
+/// Computes the [`Nmt`] of the row or column with the given index out of its shares.
+pub(crate) fn axis_nmt(shares: &[&[u8]], index: u16, square_width: u16) -> Result<Nmt> {
+    let mut tree = Nmt::default();
+
+    for (i, share) in (0..square_width).zip(shares) {
+        if share.len() != SHARE_SIZE {
+            bail_validation!("share len ({}) != SHARE_SIZE ({})", share.len(), SHARE_SIZE);
+        }
+
+        let ns = if is_ods_square(index, i, square_width) {
+            Namespace::from_raw(&share[..NS_SIZE])?
+        } else {
+            Namespace::PARITY_SHARE
+        };
+
+        tree.push_leaf(share, *ns).map_err(Error::Nmt)?;
+    }
+
+    Ok(tree)
+}
+
 /// Returns true if and only if the provided coordinates belongs to Original Data Square
 /// (i.e. first quadrant of Extended Data Square).
 pub(crate) fn is_ods_square(row: u16, column: u16, square_width: u16) -> bool {
@@ -479,6 +595,7 @@ pub(crate) fn is_ods_square(row: u16, column: u16, square_width: u16) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::generate_eds;
     use crate::ExtendedHeader;
 
     #[test]
@@ -527,6 +644,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn get_namespaced_data_absent() {
+        let ns1 = Namespace::new_v0(&[1]).unwrap();
+        let ns2 = Namespace::new_v0(&[2]).unwrap();
+        let ns3 = Namespace::new_v0(&[3]).unwrap();
+
+        let shares = [ns1, ns3, ns3, ns3]
+            .iter()
+            .map(|ns| [ns.as_bytes(), &[0; SHARE_SIZE - NS_SIZE][..]].concat())
+            .collect();
+        let eds = ExtendedDataSquare::from_ods(shares).unwrap();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+
+        // Namespace is absent, but within the range of the first row.
+        let rows = eds.get_namespaced_data(ns2, &dah, 1).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].shares.is_empty());
+        assert!(rows[0].proof.is_of_absence());
+        rows[0].verify(&dah).unwrap();
+
+        // Absence proof can't be used with shares.
+        let mut row = rows[0].clone();
+        row.shares.push(eds.share(0, 0).unwrap().to_vec());
+        row.verify(&dah).unwrap_err();
+    }
+
     #[test]
     fn nmt_roots() {
         let eds_json = include_str!("../test_data/shwap_samples/eds.json");
@@ -740,4 +883,90 @@ mod tests {
         let dah = DataAvailabilityHeader::from_eds(&eds);
         assert_eq!(dah, genesis.dah);
     }
+
+    #[test]
+    fn repair() {
+        let eds = generate_eds(8);
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let width = usize::from(eds.square_width());
+
+        // only the 4th quadrant
+        let shares = eds
+            .data_square()
+            .iter()
+            .enumerate()
+            .map(|(idx, share)| {
+                let (row, col) = (idx / width, idx % width);
+                (row >= width / 2 && col >= width / 2).then(|| share.clone())
+            })
+            .collect();
+        assert_eq!(ExtendedDataSquare::repair(shares, &dah).unwrap(), eds);
+
+        // requires repairing rows and columns in multiple iterations
+        let shares = eds
+            .data_square()
+            .iter()
+            .enumerate()
+            .map(|(idx, share)| {
+                let (row, col) = (idx / width, idx % width);
+                (row + col >= width - 1 && !(row == width - 1 && col == width - 1))
+                    .then(|| share.clone())
+            })
+            .collect();
+        assert_eq!(ExtendedDataSquare::repair(shares, &dah).unwrap(), eds);
+
+        // all shares available
+        let shares = eds.data_square().iter().cloned().map(Some).collect();
+        assert_eq!(ExtendedDataSquare::repair(shares, &dah).unwrap(), eds);
+    }
+
+    #[test]
+    fn repair_not_enough_shares() {
+        let eds = generate_eds(8);
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let width = usize::from(eds.square_width());
+
+        // (k + 1)^2 missing shares make the square unrepairable
+        let shares = eds
+            .data_square()
+            .iter()
+            .enumerate()
+            .map(|(idx, share)| {
+                let (row, col) = (idx / width, idx % width);
+                (row > width / 2 || col > width / 2).then(|| share.clone())
+            })
+            .collect();
+
+        let err = ExtendedDataSquare::repair(shares, &dah).unwrap_err();
+        assert!(matches!(err, Error::EdsNotEnoughShares));
+
+        let shares = vec![None; width * width - 1];
+        let err = ExtendedDataSquare::repair(shares, &dah).unwrap_err();
+        assert!(matches!(err, Error::EdsInvalidDimentions));
+    }
+
+    #[test]
+    fn repair_byzantine_data() {
+        let mut eds = generate_eds(8);
+        let width = eds.square_width();
+
+        // corrupt the parity share of the first row
+        eds.share_mut(0, width - 1).unwrap()[0] ^= 0xff;
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+
+        let shares = eds.data_square().iter().cloned().map(Some).collect();
+        let err = ExtendedDataSquare::repair(shares, &dah).unwrap_err();
+        assert!(matches!(err, Error::ByzantineData(AxisType::Row, 0)));
+
+        // when the first row is missing, it is repaired correctly, but
+        // then it doesn't match its root
+        let shares = eds
+            .data_square()
+            .iter()
+            .enumerate()
+            .map(|(idx, share)| (idx >= usize::from(width)).then(|| share.clone()))
+            .collect();
+        let err = ExtendedDataSquare::repair(shares, &dah).unwrap_err();
+        assert!(matches!(err, Error::ByzantineData(AxisType::Row, 0)));
+    }
 }
@@ -14,9 +14,11 @@ use crate::nmt::{
 };
 use crate::{Error, Result};
 
+mod compact;
 mod info_byte;
 mod proof;
 
+pub use compact::{parse_compact_shares, parse_pay_for_blobs, PayForBlobsTx};
 pub use info_byte::InfoByte;
 pub use proof::ShareProof;
 
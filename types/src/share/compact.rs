@@ -0,0 +1,367 @@
+use celestia_proto::celestia::blob::v1::MsgPayForBlobs as RawMsgPayForBlobs;
+use celestia_proto::cosmos::tx::v1beta1::{TxBody, TxRaw};
+use celestia_proto::prost::Message;
+use celestia_tendermint_proto::v0_34::types::IndexWrapper;
+
+use crate::blob::MsgPayForBlobs;
+use crate::consts::appconsts;
+use crate::nmt::NS_SIZE;
+use crate::share::Share;
+use crate::{bail_validation, validation_error, Error, Result};
+
+/// Offset of the data in the first compact share of a sequence.
+const FIRST_COMPACT_SHARE_DATA_OFFSET: usize = NS_SIZE
+    + appconsts::SHARE_INFO_BYTES
+    + appconsts::SEQUENCE_LEN_BYTES
+    + appconsts::COMPACT_SHARE_RESERVED_BYTES;
+/// Offset of the data in a continuation compact share of a sequence.
+const CONTINUATION_COMPACT_SHARE_DATA_OFFSET: usize =
+    NS_SIZE + appconsts::SHARE_INFO_BYTES + appconsts::COMPACT_SHARE_RESERVED_BYTES;
+
+/// Parse the transactions out of the compact [`Share`]s of a namespace.
+///
+/// Compact shares are used by the reserved [`Namespace::TRANSACTION`] and
+/// [`Namespace::PAY_FOR_BLOB`] namespaces. Unlike the blob shares, they hold
+/// a sequence of length delimited transactions packed one after another, which
+/// can span share boundaries.
+///
+/// Shares must be provided in the order they appear in the square, e.g. all the
+/// shares of one of those namespaces in the block.
+///
+/// # Errors
+///
+/// This function will return an error if shares are not a valid sequence of compact
+/// shares of a single namespace, or if the transactions length delimiters are malformed.
+///
+/// [`Namespace::TRANSACTION`]: crate::nmt::Namespace::TRANSACTION
+/// [`Namespace::PAY_FOR_BLOB`]: crate::nmt::Namespace::PAY_FOR_BLOB
+pub fn parse_compact_shares<'a, I>(shares: I) -> Result<Vec<Vec<u8>>>
+where
+    I: IntoIterator<Item = &'a Share>,
+{
+    let mut shares = shares.into_iter();
+    let mut txs = Vec::new();
+
+    let Some(mut share) = shares.next() else {
+        return Ok(txs);
+    };
+    let namespace = share.namespace();
+
+    loop {
+        let info_byte = share.info_byte();
+
+        if info_byte.version() != appconsts::SHARE_VERSION_ZERO {
+            return Err(Error::UnsupportedShareVersion(info_byte.version()));
+        }
+
+        let Some(sequence_length) = share.sequence_length() else {
+            bail_validation!("expected sequence start share");
+        };
+        let sequence_length = sequence_length as usize;
+
+        // Sequence length comes from untrusted share, so don't allocate more
+        // than the remaining shares can fill.
+        let max_len = appconsts::FIRST_COMPACT_SHARE_CONTENT_SIZE
+            + shares.size_hint().0 * appconsts::CONTINUATION_COMPACT_SHARE_CONTENT_SIZE;
+        let mut data = Vec::with_capacity(sequence_length.min(max_len));
+        data.extend_from_slice(&share.data[FIRST_COMPACT_SHARE_DATA_OFFSET..]);
+
+        while data.len() < sequence_length {
+            let Some(next) = shares.next() else {
+                bail_validation!(
+                    "sequence length ({sequence_length}) exceeds the shares data ({})",
+                    data.len()
+                );
+            };
+
+            if next.namespace() != namespace {
+                bail_validation!("shares of different namespaces");
+            }
+
+            if next.info_byte().is_sequence_start() {
+                bail_validation!("unexpected sequence start share");
+            }
+
+            data.extend_from_slice(&next.data[CONTINUATION_COMPACT_SHARE_DATA_OFFSET..]);
+        }
+
+        data.truncate(sequence_length);
+        parse_delimited_units(&data, &mut txs)?;
+
+        match shares.next() {
+            Some(next) if next.namespace() == namespace => share = next,
+            Some(_) => bail_validation!("shares of different namespaces"),
+            None => break,
+        }
+    }
+
+    Ok(txs)
+}
+
+/// Split the sequence data into units, each prefixed with its length as `uvarint`.
+fn parse_delimited_units(mut data: &[u8], units: &mut Vec<Vec<u8>>) -> Result<()> {
+    while !data.is_empty() {
+        let len = celestia_proto::prost::encoding::decode_varint(&mut data)
+            .map_err(|_| validation_error!("invalid unit length delimiter"))?;
+
+        let Some(unit) = usize::try_from(len).ok().and_then(|len| data.get(..len)) else {
+            bail_validation!("unit length ({len}) exceeds sequence data ({})", data.len());
+        };
+
+        units.push(unit.to_vec());
+        data = &data[unit.len()..];
+    }
+
+    Ok(())
+}
+
+/// A transaction paying for blobs, as included in the [`Namespace::PAY_FOR_BLOB`] shares.
+///
+/// [`Namespace::PAY_FOR_BLOB`]: crate::nmt::Namespace::PAY_FOR_BLOB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayForBlobsTx {
+    /// The encoded transaction, without the blobs.
+    pub tx: Vec<u8>,
+    /// Indexes of the first shares of the paid blobs in the square.
+    pub share_indexes: Vec<u32>,
+    /// Messages paying for blobs included in the transaction.
+    pub msgs: Vec<MsgPayForBlobs>,
+}
+
+impl PayForBlobsTx {
+    /// Decode the transaction from the unit of the [`Namespace::PAY_FOR_BLOB`] shares.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the unit is not an index wrapper of
+    /// a transaction or if any of the [`MsgPayForBlobs`] in it is invalid.
+    ///
+    /// [`Namespace::PAY_FOR_BLOB`]: crate::nmt::Namespace::PAY_FOR_BLOB
+    pub fn decode(unit: &[u8]) -> Result<Self> {
+        let wrapper =
+            IndexWrapper::decode(unit).map_err(celestia_tendermint_proto::Error::decode_message)?;
+
+        if wrapper.type_id != appconsts::INDEX_WRAPPER_TYPE_ID {
+            bail_validation!(
+                "type id ({}) != {}",
+                wrapper.type_id,
+                appconsts::INDEX_WRAPPER_TYPE_ID
+            );
+        }
+
+        let tx = TxRaw::decode(&wrapper.tx[..])
+            .map_err(celestia_tendermint_proto::Error::decode_message)?;
+        let body = TxBody::decode(&tx.body_bytes[..])
+            .map_err(celestia_tendermint_proto::Error::decode_message)?;
+
+        let msgs = body
+            .messages
+            .into_iter()
+            .filter(|msg| msg.type_url == MsgPayForBlobs::TYPE_URL)
+            .map(|msg| {
+                RawMsgPayForBlobs::decode(&msg.value[..])
+                    .map_err(celestia_tendermint_proto::Error::decode_message)?
+                    .try_into()
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(PayForBlobsTx {
+            tx: wrapper.tx,
+            share_indexes: wrapper.share_indexes,
+            msgs,
+        })
+    }
+}
+
+/// Parse the transactions paying for blobs out of the [`Namespace::PAY_FOR_BLOB`] shares.
+///
+/// See [`parse_compact_shares`] for the requirements on the shares.
+///
+/// # Errors
+///
+/// This function will return an error if shares are not valid compact shares or
+/// if any of the transactions cannot be decoded. See [`PayForBlobsTx::decode`].
+///
+/// [`Namespace::PAY_FOR_BLOB`]: crate::nmt::Namespace::PAY_FOR_BLOB
+pub fn parse_pay_for_blobs<'a, I>(shares: I) -> Result<Vec<PayForBlobsTx>>
+where
+    I: IntoIterator<Item = &'a Share>,
+{
+    parse_compact_shares(shares)?
+        .iter()
+        .map(|unit| PayForBlobsTx::decode(unit))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::appconsts::{
+        CONTINUATION_COMPACT_SHARE_CONTENT_SIZE, FIRST_COMPACT_SHARE_CONTENT_SIZE, SHARE_SIZE,
+    };
+    use crate::nmt::Namespace;
+    use crate::rsmt2d::ExtendedDataSquare;
+    use crate::share::InfoByte;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Split the units into compact shares, the way celestia-app does.
+    fn to_compact_shares(namespace: Namespace, units: &[Vec<u8>]) -> Vec<Share> {
+        let mut data = Vec::new();
+        let mut unit_starts = Vec::new();
+
+        for unit in units {
+            unit_starts.push(data.len());
+            celestia_proto::prost::encoding::encode_varint(unit.len() as u64, &mut data);
+            data.extend_from_slice(unit);
+        }
+
+        let mut shares = Vec::new();
+        let mut offset = 0;
+
+        while offset < data.len() || shares.is_empty() {
+            let is_first = shares.is_empty();
+            let (data_offset, content_size) = if is_first {
+                (
+                    FIRST_COMPACT_SHARE_DATA_OFFSET,
+                    FIRST_COMPACT_SHARE_CONTENT_SIZE,
+                )
+            } else {
+                (
+                    CONTINUATION_COMPACT_SHARE_DATA_OFFSET,
+                    CONTINUATION_COMPACT_SHARE_CONTENT_SIZE,
+                )
+            };
+            let end = data.len().min(offset + content_size);
+
+            let mut share = [0; SHARE_SIZE];
+            share[..NS_SIZE].copy_from_slice(namespace.as_bytes());
+            share[NS_SIZE] = InfoByte::new(0, is_first).unwrap().as_u8();
+            if is_first {
+                share[NS_SIZE + 1..NS_SIZE + 5].copy_from_slice(&(data.len() as u32).to_be_bytes());
+            }
+
+            // location of the first unit starting in the share, if any
+            let reserved = unit_starts
+                .iter()
+                .find(|&&start| start >= offset && start < end)
+                .map_or(0, |start| (data_offset + start - offset) as u32);
+            share[data_offset - 4..data_offset].copy_from_slice(&reserved.to_be_bytes());
+
+            share[data_offset..data_offset + end - offset].copy_from_slice(&data[offset..end]);
+            shares.push(Share::from_raw(&share).unwrap());
+            offset = end;
+        }
+
+        shares
+    }
+
+    #[test]
+    fn parse_transactions() {
+        let txs = vec![vec![1; 100], vec![2; 600], vec![3; 10], vec![4; 1200]];
+        let shares = to_compact_shares(Namespace::TRANSACTION, &txs);
+        assert_eq!(shares.len(), 5);
+
+        assert_eq!(parse_compact_shares(&shares).unwrap(), txs);
+    }
+
+    #[test]
+    fn parse_multiple_sequences() {
+        let mut shares = to_compact_shares(Namespace::TRANSACTION, &[vec![1; 1000]]);
+        shares.extend(to_compact_shares(Namespace::TRANSACTION, &[vec![2; 10]]));
+
+        let txs = parse_compact_shares(&shares).unwrap();
+        assert_eq!(txs, vec![vec![1; 1000], vec![2; 10]]);
+    }
+
+    #[test]
+    fn parse_empty() {
+        assert!(parse_compact_shares(std::iter::empty()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_invalid_sequences() {
+        let shares = to_compact_shares(Namespace::TRANSACTION, &[vec![1; 1000]]);
+
+        // missing continuation share
+        parse_compact_shares(&shares[..2]).unwrap_err();
+        // missing sequence start
+        parse_compact_shares(&shares[1..]).unwrap_err();
+
+        // shares of other namespace
+        let mut mixed = shares.clone();
+        mixed.extend(to_compact_shares(Namespace::PAY_FOR_BLOB, &[vec![2; 10]]));
+        parse_compact_shares(&mixed).unwrap_err();
+
+        // unit length exceeding the sequence
+        let mut share = shares[0].clone();
+        share.data[FIRST_COMPACT_SHARE_DATA_OFFSET..FIRST_COMPACT_SHARE_DATA_OFFSET + 2]
+            .copy_from_slice(&[0xff, 0x7f]);
+        parse_compact_shares(&[share, shares[1].clone(), shares[2].clone()]).unwrap_err();
+    }
+
+    #[test]
+    fn parse_huge_sequence_length() {
+        let mut shares = to_compact_shares(Namespace::TRANSACTION, &[vec![1; 1000]]);
+        shares[0].data[NS_SIZE + 1..NS_SIZE + 5].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        let err = parse_compact_shares(&shares).unwrap_err();
+        assert!(matches!(err, Error::Validation(_)));
+    }
+
+    #[test]
+    fn parse_pay_for_blobs_from_square() {
+        let eds_json = include_str!("../../test_data/shwap_samples/eds.json");
+        let eds: ExtendedDataSquare = serde_json::from_str(eds_json).unwrap();
+
+        let ods_width = eds.square_width() / 2;
+
+        let shares: Vec<_> = (0..ods_width)
+            .flat_map(|row| (0..ods_width).map(move |col| (row, col)))
+            .map(|(row, col)| Share::from_raw(eds.share(row, col).unwrap()).unwrap())
+            .filter(|share| share.namespace() == Namespace::PAY_FOR_BLOB)
+            .collect();
+        assert!(!shares.is_empty());
+
+        let pfbs = parse_pay_for_blobs(&shares).unwrap();
+        assert_eq!(pfbs.len(), 1);
+
+        let pfb = &pfbs[0];
+        assert_eq!(pfb.msgs.len(), 1);
+
+        let msg = &pfb.msgs[0];
+        assert_eq!(
+            msg.signer.to_string(),
+            "celestia1ym22zdyvqct9f8dky3vukplz9us4u50dpkqqwk"
+        );
+        assert_eq!(
+            msg.namespaces,
+            vec![
+                Namespace::new_v0(&[1, 170]).unwrap(),
+                Namespace::new_v0(&[1, 187]).unwrap()
+            ]
+        );
+        assert_eq!(msg.blob_sizes, vec![492, 2028]);
+        assert_eq!(msg.share_commitments.len(), 2);
+        assert_eq!(pfb.share_indexes, vec![1, 3]);
+
+        // blobs paid for are in the square at the given indexes
+        for (ns, index) in msg.namespaces.iter().zip(&pfb.share_indexes) {
+            let index = *index as u16;
+            let share = eds.share(index / ods_width, index % ods_width).unwrap();
+            assert_eq!(&share[..NS_SIZE], ns.as_bytes());
+        }
+    }
+
+    #[test]
+    fn decode_invalid_pay_for_blobs() {
+        let wrapper = IndexWrapper {
+            tx: vec![],
+            share_indexes: vec![],
+            type_id: "BLOB".to_owned(),
+        };
+        PayForBlobsTx::decode(&wrapper.encode_to_vec()).unwrap_err();
+        PayForBlobsTx::decode(&[0xff; 10]).unwrap_err();
+    }
+}
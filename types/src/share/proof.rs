@@ -5,8 +5,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::consts::appconsts::SHARE_SIZE;
 use crate::nmt::NamespaceProof;
-use crate::{bail_verification, validation_error, RowProof};
+use crate::row::Row;
+use crate::rsmt2d::{axis_nmt, ExtendedDataSquare};
+use crate::{bail_validation, bail_verification, validation_error, RowProof};
 use crate::{nmt::Namespace, Error, Result};
+use crate::{Blob, DataAvailabilityHeader, ExtendedHeader};
 
 /// A proof of inclusion of a continouous range of shares of some namespace
 /// in a [`DataAvailabilityHeader`].
@@ -25,6 +28,54 @@ pub struct ShareProof {
 }
 
 impl ShareProof {
+    /// Create a proof of the [`Blob`] inclusion in the [`ExtendedDataSquare`].
+    ///
+    /// The proof covers all the shares of the blob and can be verified against the
+    /// hash of the [`DataAvailabilityHeader`] of the square, which is the `data_hash`
+    /// of the block.
+    ///
+    /// Returns `None` if the square doesn't contain the blob or the blob has no shares.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the blob cannot be split into shares
+    /// or if the [`DataAvailabilityHeader`] doesn't match the square dimensions.
+    pub fn for_blob(
+        blob: &Blob,
+        eds: &ExtendedDataSquare,
+        dah: &DataAvailabilityHeader,
+    ) -> Result<Option<ShareProof>> {
+        let square_width = usize::from(eds.square_width());
+        // only the rows of the original data can contain the blob
+        let rows = eds
+            .data_square()
+            .chunks(square_width)
+            .take(square_width / 2)
+            .zip(0..)
+            .map(|(shares, index)| (index, shares));
+
+        blob_proof(blob, dah, rows)
+    }
+
+    /// Create a proof of the [`Blob`] inclusion out of the [`Row`]s of the square.
+    ///
+    /// Works the same as [`ShareProof::for_blob`], but doesn't need the whole square.
+    /// Rows need to be sorted by their index and include all the rows spanned by the blob.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`ShareProof::for_blob`], and additionally if the rows spanned by the
+    /// blob are not consecutive.
+    pub fn for_blob_in_rows(
+        blob: &Blob,
+        rows: &[Row],
+        dah: &DataAvailabilityHeader,
+    ) -> Result<Option<ShareProof>> {
+        let rows = rows.iter().map(|row| (row.id.index(), &row.shares[..]));
+
+        blob_proof(blob, dah, rows)
+    }
+
     /// Get the shares proven by this proof.
     pub fn shares(&self) -> &[[u8; SHARE_SIZE]] {
         &self.data
@@ -86,6 +137,112 @@ impl ShareProof {
 
         Ok(())
     }
+
+    /// Verify the proof of the [`Blob`] inclusion in the block of the [`ExtendedHeader`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the proven shares are not the shares of
+    /// the blob or if the proof fails the verification against the `data_hash` of the
+    /// block. See [`ShareProof::verify`] for more details.
+    pub fn verify_blob(&self, blob: &Blob, header: &ExtendedHeader) -> Result<()> {
+        if self.namespace_id != blob.namespace {
+            bail_verification!(
+                "proof namespace ({:?}) != blob namespace ({:?})",
+                self.namespace_id,
+                blob.namespace
+            );
+        }
+
+        let shares = blob.to_shares()?;
+
+        if shares.len() != self.data.len()
+            || shares
+                .iter()
+                .zip(&self.data)
+                .any(|(share, data)| share.data != *data)
+        {
+            bail_verification!("proven shares don't match the blob");
+        }
+
+        self.verify(header.header.data_hash)
+    }
+}
+
+/// Find the shares of the blob in the original data of the given rows and prove them.
+fn blob_proof<'a, I>(
+    blob: &Blob,
+    dah: &DataAvailabilityHeader,
+    rows: I,
+) -> Result<Option<ShareProof>>
+where
+    I: IntoIterator<Item = (u16, &'a [Vec<u8>])>,
+{
+    let blob_shares = blob.to_shares()?;
+    if blob_shares.is_empty() {
+        return Ok(None);
+    }
+    let rows: Vec<_> = rows.into_iter().collect();
+
+    // Shares of the original data square in row-major order, with their coordinates.
+    let ods_shares: Vec<_> = rows
+        .iter()
+        .flat_map(|&(row, shares)| {
+            let ods_width = shares.len() / 2;
+            shares[..ods_width]
+                .iter()
+                .enumerate()
+                .map(move |(col, share)| (row, col, share))
+        })
+        .collect();
+
+    let Some(start) = ods_shares.windows(blob_shares.len()).position(|window| {
+        window
+            .iter()
+            .zip(&blob_shares)
+            .all(|((_, _, share), blob_share)| share[..] == blob_share.data[..])
+    }) else {
+        return Ok(None);
+    };
+    let blob_ods_shares = &ods_shares[start..start + blob_shares.len()];
+
+    if blob_ods_shares
+        .windows(2)
+        .any(|pair| pair[1].0 != pair[0].0 && pair[1].0 != pair[0].0 + 1)
+    {
+        bail_validation!("rows spanned by the blob are not consecutive");
+    }
+
+    let mut share_proofs = Vec::new();
+
+    for &(row, shares) in &rows {
+        let mut cols = blob_ods_shares
+            .iter()
+            .filter(|(share_row, ..)| *share_row == row)
+            .map(|(_, col, _)| *col);
+
+        let Some(first_col) = cols.next() else {
+            continue;
+        };
+        let last_col = cols.next_back().unwrap_or(first_col);
+
+        let square_width = u16::try_from(shares.len()).map_err(|_| Error::EdsInvalidDimentions)?;
+        let shares: Vec<_> = shares.iter().map(Vec::as_slice).collect();
+        let (_, proof) =
+            axis_nmt(&shares, row, square_width)?.get_range_with_proof(first_col..last_col + 1);
+
+        share_proofs.push(proof.into());
+    }
+
+    let first_row = blob_ods_shares[0].0;
+    let last_row = blob_ods_shares[blob_ods_shares.len() - 1].0;
+
+    Ok(Some(ShareProof {
+        data: blob_shares.into_iter().map(|share| share.data).collect(),
+        namespace_id: blob.namespace,
+        share_proofs,
+        row_proof: dah.row_proof(first_row..=last_row)?,
+    }))
 }
 
 impl Protobuf<RawShareProof> for ShareProof {}
@@ -135,9 +292,184 @@ impl From<ShareProof> for RawShareProof {
 
 #[cfg(test)]
 mod tests {
+    use crate::nmt::NS_SIZE;
+    use crate::share::InfoByte;
+    use crate::test_utils::ExtendedHeaderGenerator;
     use crate::DataAvailabilityHeader;
 
-    use super::ShareProof;
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Square with a single share blob and a blob spanning 3 rows of the 4x4 ODS.
+    fn square_with_blobs() -> (Vec<Blob>, ExtendedDataSquare) {
+        let blobs = vec![
+            Blob::new(Namespace::new_v0(&[1]).unwrap(), vec![1; 100]).unwrap(),
+            Blob::new(Namespace::new_v0(&[2]).unwrap(), vec![2; 4000]).unwrap(),
+        ];
+
+        let mut shares: Vec<_> = blobs
+            .iter()
+            .flat_map(|blob| blob.to_shares().unwrap())
+            .map(|share| share.to_vec())
+            .collect();
+        assert_eq!(shares.len(), 10);
+
+        let tail_padding = [
+            Namespace::TAIL_PADDING.as_bytes(),
+            &[InfoByte::new(0, true).unwrap().as_u8()],
+            &[0; SHARE_SIZE - NS_SIZE - 1],
+        ]
+        .concat();
+        shares.resize(16, tail_padding);
+
+        (blobs, ExtendedDataSquare::from_ods(shares).unwrap())
+    }
+
+    #[test]
+    fn blob_proof() {
+        let (blobs, eds) = square_with_blobs();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let header = ExtendedHeaderGenerator::new().next_with_dah(dah.clone());
+
+        for blob in &blobs {
+            let proof = ShareProof::for_blob(blob, &eds, &dah).unwrap().unwrap();
+            proof.verify_blob(blob, &header).unwrap();
+        }
+
+        let proof = ShareProof::for_blob(&blobs[1], &eds, &dah)
+            .unwrap()
+            .unwrap();
+        assert_eq!(proof.shares().len(), 10 - 1);
+        assert_eq!(proof.row_proof.row_roots().len(), 3);
+    }
+
+    #[test]
+    fn blob_proof_from_rows() {
+        let (blobs, eds) = square_with_blobs();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let header = ExtendedHeaderGenerator::new().next_with_dah(dah.clone());
+
+        let rows: Vec<_> = (0..3).map(|i| Row::new(i, &eds, 1).unwrap()).collect();
+        let proof = ShareProof::for_blob_in_rows(&blobs[1], &rows, &dah)
+            .unwrap()
+            .unwrap();
+        proof.verify_blob(&blobs[1], &header).unwrap();
+
+        let eds_proof = ShareProof::for_blob(&blobs[1], &eds, &dah)
+            .unwrap()
+            .unwrap();
+        assert_eq!(proof, eds_proof);
+
+        // missing row spanned by the blob
+        let rows = [rows[0].clone(), rows[2].clone()];
+        assert!(ShareProof::for_blob_in_rows(&blobs[1], &rows, &dah)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn blob_not_in_square() {
+        let (blobs, eds) = square_with_blobs();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+
+        let blob = Blob::new(blobs[1].namespace, vec![3; 4000]).unwrap();
+        assert!(ShareProof::for_blob(&blob, &eds, &dah).unwrap().is_none());
+    }
+
+    #[test]
+    fn empty_blob_proof() {
+        let (blobs, eds) = square_with_blobs();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+
+        let blob = Blob::new(blobs[0].namespace, vec![]).unwrap();
+        assert!(ShareProof::for_blob(&blob, &eds, &dah).unwrap().is_none());
+
+        let rows: Vec<_> = (0..4).map(|i| Row::new(i, &eds, 1).unwrap()).collect();
+        assert!(ShareProof::for_blob_in_rows(&blob, &rows, &dah)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn blob_in_parity_rows_is_not_found() {
+        let share = |ns: u8, data: u8| {
+            let ns = Namespace::new_v0(&[ns]).unwrap();
+            Blob::new(ns, vec![data; 100]).unwrap().to_shares().unwrap()[0].to_vec()
+        };
+        let tail_padding = [
+            Namespace::TAIL_PADDING.as_bytes(),
+            &[InfoByte::new(0, true).unwrap().as_u8()],
+            &[0; SHARE_SIZE - NS_SIZE - 1],
+        ]
+        .concat();
+
+        // The encoding is linear, so the parity of a column of v0 namespaces
+        // has a valid v0 namespace too.
+        let mut column = vec![
+            share(1, 1),
+            share(2, 1),
+            vec![0; SHARE_SIZE],
+            vec![0; SHARE_SIZE],
+        ];
+        leopard_codec::encode(&mut column, 2).unwrap();
+        let parity_ns = Namespace::from_raw(&column[2][..NS_SIZE]).unwrap();
+
+        // Choose the second share of the column, so that the first parity share
+        // of the column is the share of the blob.
+        let blob = Blob::new(parity_ns, vec![2; 100]).unwrap();
+        let blob_share = blob.to_shares().unwrap()[0].to_vec();
+        let mut column = vec![share(1, 1), vec![], blob_share.clone(), vec![]];
+        leopard_codec::reconstruct(&mut column, 2).unwrap();
+
+        let ods = vec![
+            column[0].clone(),
+            tail_padding.clone(),
+            column[1].clone(),
+            tail_padding,
+        ];
+        let eds = ExtendedDataSquare::from_ods(ods).unwrap();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        assert_eq!(eds.share(2, 0).unwrap(), &blob_share[..]);
+
+        assert!(ShareProof::for_blob(&blob, &eds, &dah).unwrap().is_none());
+    }
+
+    #[test]
+    fn verify_blob_mismatch() {
+        let (blobs, eds) = square_with_blobs();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let mut gen = ExtendedHeaderGenerator::new();
+        let header = gen.next_with_dah(dah.clone());
+
+        let proof = ShareProof::for_blob(&blobs[0], &eds, &dah)
+            .unwrap()
+            .unwrap();
+
+        // other blob
+        proof.verify_blob(&blobs[1], &header).unwrap_err();
+
+        // same namespace, different data
+        let blob = Blob::new(blobs[0].namespace, vec![3; 100]).unwrap();
+        proof.verify_blob(&blob, &header).unwrap_err();
+
+        // other block
+        let other_header = gen.next();
+        proof.verify_blob(&blobs[0], &other_header).unwrap_err();
+    }
+
+    #[test]
+    fn blob_proof_in_minimal_square() {
+        let blob = Blob::new(Namespace::new_v0(&[1]).unwrap(), vec![1; 100]).unwrap();
+        let eds =
+            ExtendedDataSquare::from_ods(vec![blob.to_shares().unwrap()[0].to_vec()]).unwrap();
+        let dah = DataAvailabilityHeader::from_eds(&eds);
+        let header = ExtendedHeaderGenerator::new().next_with_dah(dah.clone());
+
+        let proof = ShareProof::for_blob(&blob, &eds, &dah).unwrap().unwrap();
+        proof.verify_blob(&blob, &header).unwrap();
+    }
 
     #[test]
     fn share_proof_serde() {
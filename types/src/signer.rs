@@ -0,0 +1,157 @@
+//! Building and signing of the transactions paying for blobs.
+//!
+//! The `Signer`, available with the `signer` feature, creates a [`MsgPayForBlobs`] for
+//! the given [`Blob`]s, wraps it in a cosmos transaction signed with the `SIGN_MODE_DIRECT`
+//! and attaches the blobs to it, producing a [`BlobTx`] ready to be broadcasted to the
+//! celestia-app consensus nodes.
+//!
+//! [`MsgPayForBlobs`]: crate::blob::MsgPayForBlobs
+
+use celestia_tendermint_proto::v0_34::types::BlobTx as RawBlobTx;
+use celestia_tendermint_proto::Protobuf;
+
+use crate::blob::Blob;
+use crate::consts::{appconsts, cosmos};
+use crate::{bail_validation, Error, Result};
+
+#[cfg(any(test, feature = "signer"))]
+mod key;
+
+#[cfg(any(test, feature = "signer"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "signer")))]
+pub use self::key::Signer;
+
+/// The on-chain state of the signing account needed to sign a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountInfo {
+    /// ID of the chain the transaction is meant for.
+    pub chain_id: String,
+    /// Number of the account in the chain state.
+    pub account_number: u64,
+    /// The sequence number of the next transaction of the account.
+    pub sequence: u64,
+}
+
+/// A transaction paying for the blobs, together with the blobs themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobTx {
+    /// The encoded and signed transaction carrying the [`MsgPayForBlobs`].
+    ///
+    /// [`MsgPayForBlobs`]: crate::blob::MsgPayForBlobs
+    pub tx: Vec<u8>,
+    /// The [`Blob`]s paid for by the transaction.
+    pub blobs: Vec<Blob>,
+}
+
+/// Estimate the gas needed to pay for the blobs of the given sizes.
+///
+/// It follows the estimation of the `celestia-app`, charging for all the bytes
+/// of the shares occupied by the blobs and for the size of the transaction itself.
+pub fn estimate_gas(blob_sizes: &[u32]) -> u64 {
+    let shares: u64 = blob_sizes
+        .iter()
+        .map(|&size| sparse_shares_needed(size))
+        .sum();
+    let blobs_count = blob_sizes.len() as u64;
+
+    shares * appconsts::SHARE_SIZE as u64 * appconsts::GAS_PER_BLOB_BYTE
+        + cosmos::DEFAULT_TX_SIZE_COST_PER_BYTE * appconsts::BYTES_PER_BLOB_INFO * blobs_count
+        + appconsts::PFB_GAS_FIXED_COST
+}
+
+/// Calculate the fee, in `utia`, of the transaction with the given gas limit.
+///
+/// Missing or negative gas price falls back to the [`DEFAULT_MIN_GAS_PRICE`].
+///
+/// # Errors
+///
+/// This function will return an error if the gas price is not a finite number.
+///
+/// [`DEFAULT_MIN_GAS_PRICE`]: appconsts::DEFAULT_MIN_GAS_PRICE
+pub fn calculate_fee(gas: u64, gas_price: Option<f64>) -> Result<u64> {
+    let gas_price = match gas_price {
+        Some(price) if !price.is_finite() => return Err(Error::InvalidGasPrice(price)),
+        Some(price) if price >= 0.0 => price,
+        _ => appconsts::DEFAULT_MIN_GAS_PRICE,
+    };
+
+    Ok((gas as f64 * gas_price).ceil() as u64)
+}
+
+/// Number of the sparse shares needed to hold a blob of the given size.
+fn sparse_shares_needed(size: u32) -> u64 {
+    let size = size as u64;
+    let first = appconsts::FIRST_SPARSE_SHARE_CONTENT_SIZE as u64;
+    let continuation = appconsts::CONTINUATION_SPARSE_SHARE_CONTENT_SIZE as u64;
+
+    if size == 0 {
+        0
+    } else if size <= first {
+        1
+    } else {
+        1 + (size - first).div_ceil(continuation)
+    }
+}
+
+impl Protobuf<RawBlobTx> for BlobTx {}
+
+impl TryFrom<RawBlobTx> for BlobTx {
+    type Error = Error;
+
+    fn try_from(value: RawBlobTx) -> Result<Self, Self::Error> {
+        if value.type_id != appconsts::BLOB_TX_TYPE_ID {
+            bail_validation!("invalid blob tx type id: {}", value.type_id);
+        }
+
+        let blobs = value
+            .blobs
+            .into_iter()
+            .map(Blob::try_from)
+            .collect::<Result<_>>()?;
+
+        Ok(BlobTx {
+            tx: value.tx,
+            blobs,
+        })
+    }
+}
+
+impl From<BlobTx> for RawBlobTx {
+    fn from(value: BlobTx) -> Self {
+        RawBlobTx {
+            tx: value.tx,
+            blobs: value.blobs.into_iter().map(Into::into).collect(),
+            type_id: appconsts::BLOB_TX_TYPE_ID.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn gas_estimation() {
+        // 1 share: 512 * 8 + 10 * 70 + 75000
+        assert_eq!(estimate_gas(&[100]), 79_796);
+        // 1 + 3 shares: 4 * 512 * 8 + 2 * 10 * 70 + 75000
+        assert_eq!(estimate_gas(&[100, 1000]), 92_784);
+        assert_eq!(sparse_shares_needed(0), 0);
+        assert_eq!(sparse_shares_needed(478), 1);
+        assert_eq!(sparse_shares_needed(479), 2);
+        assert_eq!(sparse_shares_needed(960), 2);
+        assert_eq!(sparse_shares_needed(961), 3);
+    }
+
+    #[test]
+    fn fee_calculation() {
+        assert_eq!(calculate_fee(92_784, None).unwrap(), 186);
+        assert_eq!(calculate_fee(92_784, Some(-1.0)).unwrap(), 186);
+        assert_eq!(calculate_fee(100_000, Some(0.1)).unwrap(), 10_000);
+        assert_eq!(calculate_fee(100_000, Some(0.0)).unwrap(), 0);
+        calculate_fee(100_000, Some(f64::NAN)).unwrap_err();
+    }
+}
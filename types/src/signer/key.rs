@@ -0,0 +1,386 @@
+use std::fmt;
+
+use celestia_proto::celestia::blob::v1::MsgPayForBlobs as RawMsgPayForBlobs;
+use celestia_proto::cosmos::base::v1beta1::Coin as RawCoin;
+use celestia_proto::cosmos::crypto::secp256k1::PubKey as RawPubKey;
+use celestia_proto::cosmos::tx::signing::v1beta1::SignMode;
+use celestia_proto::cosmos::tx::v1beta1::mode_info::{Single, Sum};
+use celestia_proto::cosmos::tx::v1beta1::{
+    AuthInfo, Fee, ModeInfo, SignDoc, SignerInfo, TxBody, TxRaw,
+};
+use celestia_proto::prost::Message;
+use celestia_proto::prost_types::Any;
+use celestia_tendermint::account::Id;
+use k256::ecdsa::signature::Signer as _;
+use k256::ecdsa::{Signature, SigningKey};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use super::{calculate_fee, estimate_gas, AccountInfo, BlobTx};
+use crate::blob::{Blob, MsgPayForBlobs};
+use crate::consts::appconsts;
+use crate::state::AccAddress;
+use crate::{Error, Result, TxConfig};
+
+/// The type URL of the secp256k1 public key.
+const SECP256K1_PUBKEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+
+/// A secp256k1 key signing the transactions of a celestia account.
+#[derive(Clone)]
+pub struct Signer {
+    key: SigningKey,
+}
+
+impl Signer {
+    /// Create a new [`Signer`] from the raw bytes of the secp256k1 private key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the bytes are not a valid private key.
+    pub fn from_bytes(private_key: &[u8]) -> Result<Self> {
+        let key = SigningKey::from_slice(private_key).map_err(|_| Error::InvalidPrivateKey)?;
+        Ok(Signer { key })
+    }
+
+    /// Get the compressed, 33 bytes long, public key of the [`Signer`].
+    pub fn public_key(&self) -> Vec<u8> {
+        self.key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec()
+    }
+
+    /// Get the address of the account which belongs to the [`Signer`].
+    pub fn address(&self) -> AccAddress {
+        address_of(&self.public_key())
+    }
+
+    /// Sign the message, returning the 64 bytes long signature in the `r || s` format.
+    ///
+    /// The message is hashed with sha256 before signing and the signature
+    /// is normalized to the lower `s` value, as required by cosmos.
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        let signature: Signature = self.key.sign(message);
+        signature.to_bytes().to_vec()
+    }
+
+    /// Build and sign a transaction paying for the given [`Blob`]s.
+    ///
+    /// The gas limit and the fee are taken from the [`TxConfig`] if provided, otherwise
+    /// the gas is estimated with [`estimate_gas`] and the fee is computed using
+    /// [`DEFAULT_MIN_GAS_PRICE`]. [`TxConfig::key_name`] is ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the [`TxConfig::signer_address`] doesn't
+    /// match the address of the [`Signer`], the gas price is invalid or the blobs
+    /// can't be paid for.
+    ///
+    /// [`DEFAULT_MIN_GAS_PRICE`]: appconsts::DEFAULT_MIN_GAS_PRICE
+    pub fn sign_pay_for_blobs(
+        &self,
+        blobs: Vec<Blob>,
+        account: &AccountInfo,
+        cfg: &TxConfig,
+    ) -> Result<BlobTx> {
+        let address = self.address();
+
+        if let Some(signer_address) = &cfg.signer_address {
+            if *signer_address != address {
+                return Err(Error::SignerAddressMismatch(
+                    signer_address.to_string(),
+                    address.to_string(),
+                ));
+            }
+        }
+
+        let msg = MsgPayForBlobs::new(&blobs, address)?;
+
+        let gas = match cfg.gas {
+            Some(gas) if gas > 0 => gas,
+            _ => estimate_gas(&msg.blob_sizes),
+        };
+        let fee = calculate_fee(gas, cfg.gas_price)?;
+
+        let body = pay_for_blobs_body(msg);
+        let auth_info = auth_info(
+            self.public_key(),
+            account.sequence,
+            gas,
+            fee,
+            cfg.fee_granter_address.as_ref(),
+        );
+
+        let body_bytes = body.encode_to_vec();
+        let auth_info_bytes = auth_info.encode_to_vec();
+
+        let sign_doc = SignDoc {
+            body_bytes: body_bytes.clone(),
+            auth_info_bytes: auth_info_bytes.clone(),
+            chain_id: account.chain_id.clone(),
+            account_number: account.account_number,
+        };
+        let signature = self.sign(&sign_doc.encode_to_vec());
+
+        let tx = TxRaw {
+            body_bytes,
+            auth_info_bytes,
+            signatures: vec![signature],
+        };
+
+        Ok(BlobTx {
+            tx: tx.encode_to_vec(),
+            blobs,
+        })
+    }
+}
+
+impl fmt::Debug for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signer")
+            .field("address", &self.address())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Get the address of the account with the given compressed secp256k1 public key.
+fn address_of(public_key: &[u8]) -> AccAddress {
+    let sha = Sha256::digest(public_key);
+    let hash: [u8; 20] = Ripemd160::digest(sha).into();
+
+    AccAddress::new(Id::new(hash))
+}
+
+/// Create the body of a transaction with a single [`MsgPayForBlobs`].
+fn pay_for_blobs_body(msg: MsgPayForBlobs) -> TxBody {
+    TxBody {
+        messages: vec![Any {
+            type_url: MsgPayForBlobs::TYPE_URL.to_owned(),
+            value: RawMsgPayForBlobs::from(msg).encode_to_vec(),
+        }],
+        ..Default::default()
+    }
+}
+
+/// Create the auth info of a transaction signed in the `SIGN_MODE_DIRECT` by a single signer.
+fn auth_info(
+    public_key: Vec<u8>,
+    sequence: u64,
+    gas: u64,
+    fee: u64,
+    fee_granter: Option<&AccAddress>,
+) -> AuthInfo {
+    AuthInfo {
+        signer_infos: vec![SignerInfo {
+            public_key: Some(Any {
+                type_url: SECP256K1_PUBKEY_TYPE_URL.to_owned(),
+                value: RawPubKey { key: public_key }.encode_to_vec(),
+            }),
+            mode_info: Some(ModeInfo {
+                sum: Some(Sum::Single(Single {
+                    mode: SignMode::Direct.into(),
+                })),
+            }),
+            sequence,
+        }],
+        fee: Some(Fee {
+            amount: vec![RawCoin {
+                denom: appconsts::BOND_DENOM.to_owned(),
+                amount: fee.to_string(),
+            }],
+            gas_limit: gas,
+            payer: String::new(),
+            granter: fee_granter.map(ToString::to_string).unwrap_or_default(),
+        }),
+        tip: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nmt::Namespace;
+    use celestia_tendermint_proto::v0_34::types::BlobTx as RawBlobTx;
+    use celestia_tendermint_proto::Protobuf;
+    use k256::ecdsa::signature::Verifier;
+    use k256::ecdsa::VerifyingKey;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn signer() -> Signer {
+        Signer::from_bytes(&[0x2a; 32]).unwrap()
+    }
+
+    fn account() -> AccountInfo {
+        AccountInfo {
+            chain_id: "private".to_owned(),
+            account_number: 4,
+            sequence: 9,
+        }
+    }
+
+    fn blobs() -> Vec<Blob> {
+        vec![
+            Blob::new(Namespace::new_v0(&[1]).unwrap(), vec![1; 100]).unwrap(),
+            Blob::new(Namespace::new_v0(&[2]).unwrap(), vec![2; 1000]).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn invalid_private_key() {
+        Signer::from_bytes(&[0; 32]).unwrap_err();
+        Signer::from_bytes(&[1; 33]).unwrap_err();
+    }
+
+    #[test]
+    fn sign_pay_for_blobs() {
+        let signer = signer();
+        let blobs = blobs();
+        let granter = AccAddress::new(Id::new([3; 20]));
+        let mut cfg = TxConfig::default();
+        cfg.with_gas_price(0.1)
+            .with_fee_granter_address(granter.clone());
+
+        let blob_tx = signer
+            .sign_pay_for_blobs(blobs.clone(), &account(), &cfg)
+            .unwrap();
+
+        let raw_blob_tx = RawBlobTx::decode(&blob_tx.encode_vec().unwrap()[..]).unwrap();
+        assert_eq!(raw_blob_tx.type_id, "BLOB");
+        let decoded = BlobTx::try_from(raw_blob_tx).unwrap();
+        assert_eq!(decoded.blobs, blobs);
+
+        let tx = TxRaw::decode(&decoded.tx[..]).unwrap();
+        let body = TxBody::decode(&tx.body_bytes[..]).unwrap();
+        let auth_info = AuthInfo::decode(&tx.auth_info_bytes[..]).unwrap();
+
+        assert_eq!(body.messages.len(), 1);
+        assert_eq!(body.messages[0].type_url, MsgPayForBlobs::TYPE_URL);
+        let msg = MsgPayForBlobs::decode_vec(&body.messages[0].value).unwrap();
+        assert_eq!(msg, MsgPayForBlobs::new(&blobs, signer.address()).unwrap());
+
+        let signer_info = &auth_info.signer_infos[0];
+        assert_eq!(signer_info.sequence, 9);
+        let pubkey = signer_info.public_key.as_ref().unwrap();
+        assert_eq!(pubkey.type_url, SECP256K1_PUBKEY_TYPE_URL);
+        assert_eq!(
+            RawPubKey::decode(&pubkey.value[..]).unwrap().key,
+            signer.public_key()
+        );
+        assert_eq!(
+            signer_info.mode_info,
+            Some(ModeInfo {
+                sum: Some(Sum::Single(Single { mode: 1 }))
+            })
+        );
+
+        let fee = auth_info.fee.unwrap();
+        assert_eq!(fee.gas_limit, 92_784);
+        assert_eq!(fee.amount[0].denom, "utia");
+        assert_eq!(fee.amount[0].amount, "9279");
+        assert_eq!(fee.granter, granter.to_string());
+
+        // signature must verify against the sign doc
+        let sign_doc = SignDoc {
+            body_bytes: tx.body_bytes,
+            auth_info_bytes: tx.auth_info_bytes,
+            chain_id: "private".to_owned(),
+            account_number: 4,
+        };
+        let signature = Signature::from_slice(&tx.signatures[0]).unwrap();
+        assert!(signature.normalize_s().is_none());
+        VerifyingKey::from_sec1_bytes(&signer.public_key())
+            .unwrap()
+            .verify(&sign_doc.encode_to_vec(), &signature)
+            .unwrap();
+    }
+
+    #[test]
+    fn sign_with_explicit_gas() {
+        let mut cfg = TxConfig::default();
+        cfg.with_gas(200_000);
+
+        let blob_tx = signer()
+            .sign_pay_for_blobs(blobs(), &account(), &cfg)
+            .unwrap();
+
+        let tx = TxRaw::decode(&blob_tx.tx[..]).unwrap();
+        let fee = AuthInfo::decode(&tx.auth_info_bytes[..])
+            .unwrap()
+            .fee
+            .unwrap();
+        assert_eq!(fee.gas_limit, 200_000);
+        assert_eq!(fee.amount[0].amount, "400");
+    }
+
+    #[test]
+    fn signer_address_mismatch() {
+        let mut cfg = TxConfig::default();
+        cfg.with_signer_address(AccAddress::new(Id::new([3; 20])));
+
+        let err = signer()
+            .sign_pay_for_blobs(blobs(), &account(), &cfg)
+            .unwrap_err();
+        assert!(matches!(err, Error::SignerAddressMismatch(..)));
+
+        cfg.with_signer_address(signer().address());
+        signer()
+            .sign_pay_for_blobs(blobs(), &account(), &cfg)
+            .unwrap();
+    }
+
+    #[test]
+    fn celestia_app_signed_pay_for_blobs() {
+        let tx = hex_decode(include_str!(
+            "../../test_data/celestia_app/pay_for_blobs_tx.hex"
+        ));
+        let tx = TxRaw::decode(&tx[..]).unwrap();
+        let msg = TxBody::decode(&tx.body_bytes[..])
+            .unwrap()
+            .messages
+            .remove(0);
+        assert_eq!(msg.type_url, MsgPayForBlobs::TYPE_URL);
+        let msg = MsgPayForBlobs::decode_vec(&msg.value).unwrap();
+
+        let public_key =
+            hex_decode("027978062ff9b6cf54da01814a9b1b53bc5251eef557de90755ad643dfd1254ce6");
+        assert_eq!(address_of(&public_key), msg.signer);
+
+        // Transaction must be built exactly the same way
+        let body = pay_for_blobs_body(msg);
+        assert_eq!(body.encode_to_vec(), tx.body_bytes);
+        let fee = calculate_fee(105_072, Some(0.1)).unwrap();
+        let auth_info = auth_info(public_key.clone(), 24, 105_072, fee, None);
+        assert_eq!(auth_info.encode_to_vec(), tx.auth_info_bytes);
+
+        let sign_doc = SignDoc {
+            body_bytes: tx.body_bytes,
+            auth_info_bytes: tx.auth_info_bytes,
+            chain_id: "private".to_owned(),
+            account_number: 7,
+        };
+        let signature = Signature::from_slice(&tx.signatures[0]).unwrap();
+        assert_eq!(
+            signature.to_bytes().as_slice(),
+            hex_decode(
+                "ad1fca649a35ac278d7496d878bdc49dbb1f733084862ae05da588fbaa4d6dc3\
+                 2900c6104008d1a0c7203e3c59424b3f9bffe7d03641952fe5631a665faba48f"
+            )
+        );
+        assert!(signature.normalize_s().is_none());
+        VerifyingKey::from_sec1_bytes(&public_key)
+            .unwrap()
+            .verify(&sign_doc.encode_to_vec(), &signature)
+            .unwrap();
+    }
+
+    fn hex_decode(s: &str) -> Vec<u8> {
+        let s = s.trim();
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}
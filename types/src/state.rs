@@ -1,16 +1,20 @@
 //! Types and interfaces for accessing Celestia's state-relevant information.
 
 mod address;
+mod auth;
 mod balance;
+mod proof;
 mod query_delegation;
 mod tx;
 
 pub use self::address::{AccAddress, Address, AddressKind, AddressTrait, ConsAddress, ValAddress};
+pub use self::auth::BaseAccount;
 pub use self::balance::Balance;
+pub use self::proof::{balance_store_key, ProvenQueryResponse, StateProof, BANK_STORE_NAME};
 pub use self::query_delegation::{
     QueryDelegationResponse, QueryRedelegationsResponse, QueryUnbondingDelegationResponse,
 };
-pub use self::tx::{RawTx, TxResponse};
+pub use self::tx::{BroadcastMode, GasInfo, RawTx, TxResponse};
 
 /// A 256-bit unsigned integer.
 pub type Uint = ruint::aliases::U256;
@@ -0,0 +1,255 @@
+use celestia_proto::cosmos::auth::v1beta1::{
+    BaseAccount as RawBaseAccount, ModuleAccount as RawModuleAccount,
+};
+use celestia_proto::cosmos::crypto::secp256k1::PubKey as RawPubKey;
+use celestia_proto::cosmos::vesting::v1beta1::{
+    BaseVestingAccount as RawBaseVestingAccount,
+    ContinuousVestingAccount as RawContinuousVestingAccount,
+    DelayedVestingAccount as RawDelayedVestingAccount,
+    PeriodicVestingAccount as RawPeriodicVestingAccount,
+    PermanentLockedAccount as RawPermanentLockedAccount,
+};
+use celestia_proto::prost::Message;
+use celestia_proto::prost_types::Any;
+use celestia_tendermint_proto::Protobuf;
+
+use crate::state::AccAddress;
+use crate::{Error, Result};
+
+const BASE_ACCOUNT_TYPE_URL: &str = "/cosmos.auth.v1beta1.BaseAccount";
+const MODULE_ACCOUNT_TYPE_URL: &str = "/cosmos.auth.v1beta1.ModuleAccount";
+const CONTINUOUS_VESTING_ACCOUNT_TYPE_URL: &str =
+    "/cosmos.vesting.v1beta1.ContinuousVestingAccount";
+const DELAYED_VESTING_ACCOUNT_TYPE_URL: &str = "/cosmos.vesting.v1beta1.DelayedVestingAccount";
+const PERIODIC_VESTING_ACCOUNT_TYPE_URL: &str = "/cosmos.vesting.v1beta1.PeriodicVestingAccount";
+const PERMANENT_LOCKED_ACCOUNT_TYPE_URL: &str = "/cosmos.vesting.v1beta1.PermanentLockedAccount";
+const SECP256K1_PUBKEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+
+/// An account in the state of the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseAccount {
+    /// Address of the account.
+    pub address: AccAddress,
+    /// Compressed secp256k1 public key of the account.
+    ///
+    /// It is known only after the account signed its first transaction.
+    pub pub_key: Option<Vec<u8>>,
+    /// Number of the account in the chain state.
+    pub account_number: u64,
+    /// The sequence number of the next transaction of the account.
+    pub sequence: u64,
+}
+
+impl Protobuf<RawBaseAccount> for BaseAccount {}
+
+impl TryFrom<RawBaseAccount> for BaseAccount {
+    type Error = Error;
+
+    fn try_from(value: RawBaseAccount) -> Result<Self, Self::Error> {
+        let pub_key = match value.pub_key {
+            Some(any) if any.type_url == SECP256K1_PUBKEY_TYPE_URL => {
+                let key = RawPubKey::decode(&any.value[..])
+                    .map_err(celestia_tendermint_proto::Error::decode_message)?;
+                Some(key.key)
+            }
+            Some(any) => return Err(Error::UnsupportedPublicKeyType(any.type_url)),
+            None => None,
+        };
+
+        Ok(BaseAccount {
+            address: value.address.parse()?,
+            pub_key,
+            account_number: value.account_number,
+            sequence: value.sequence,
+        })
+    }
+}
+
+impl From<BaseAccount> for RawBaseAccount {
+    fn from(value: BaseAccount) -> Self {
+        RawBaseAccount {
+            address: value.address.to_string(),
+            pub_key: value.pub_key.map(|key| Any {
+                type_url: SECP256K1_PUBKEY_TYPE_URL.to_owned(),
+                value: RawPubKey { key }.encode_to_vec(),
+            }),
+            account_number: value.account_number,
+            sequence: value.sequence,
+        }
+    }
+}
+
+impl TryFrom<Any> for BaseAccount {
+    type Error = Error;
+
+    /// Decode the account as returned by the `auth` module queries.
+    ///
+    /// Module and vesting accounts are unwrapped to their base account.
+    fn try_from(value: Any) -> Result<Self, Self::Error> {
+        let raw = match value.type_url.as_str() {
+            BASE_ACCOUNT_TYPE_URL => decode::<RawBaseAccount>(&value.value)?,
+            MODULE_ACCOUNT_TYPE_URL => decode::<RawModuleAccount>(&value.value)?
+                .base_account
+                .ok_or(Error::MissingBaseAccount)?,
+            CONTINUOUS_VESTING_ACCOUNT_TYPE_URL => vesting_base_account(
+                decode::<RawContinuousVestingAccount>(&value.value)?.base_vesting_account,
+            )?,
+            DELAYED_VESTING_ACCOUNT_TYPE_URL => vesting_base_account(
+                decode::<RawDelayedVestingAccount>(&value.value)?.base_vesting_account,
+            )?,
+            PERIODIC_VESTING_ACCOUNT_TYPE_URL => vesting_base_account(
+                decode::<RawPeriodicVestingAccount>(&value.value)?.base_vesting_account,
+            )?,
+            PERMANENT_LOCKED_ACCOUNT_TYPE_URL => vesting_base_account(
+                decode::<RawPermanentLockedAccount>(&value.value)?.base_vesting_account,
+            )?,
+            _ => return Err(Error::UnsupportedAccountType(value.type_url)),
+        };
+
+        raw.try_into()
+    }
+}
+
+/// Decode the protobuf message of the account.
+fn decode<M>(bytes: &[u8]) -> Result<M>
+where
+    M: Message + Default,
+{
+    Ok(M::decode(bytes).map_err(celestia_tendermint_proto::Error::decode_message)?)
+}
+
+/// Get the base account wrapped in the vesting account.
+fn vesting_base_account(value: Option<RawBaseVestingAccount>) -> Result<RawBaseAccount> {
+    value
+        .and_then(|vesting| vesting.base_account)
+        .ok_or(Error::MissingBaseAccount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use celestia_proto::cosmos::base::v1beta1::Coin as RawCoin;
+    use celestia_proto::cosmos::vesting::v1beta1::Period as RawPeriod;
+    use celestia_tendermint::account::Id;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn account() -> BaseAccount {
+        BaseAccount {
+            address: AccAddress::new(Id::new([5; 20])),
+            pub_key: Some(vec![2; 33]),
+            account_number: 12,
+            sequence: 3,
+        }
+    }
+
+    #[test]
+    fn decode_base_account() {
+        let any = Any {
+            type_url: BASE_ACCOUNT_TYPE_URL.to_owned(),
+            value: account().encode_vec().unwrap(),
+        };
+
+        assert_eq!(BaseAccount::try_from(any).unwrap(), account());
+    }
+
+    #[test]
+    fn decode_module_account() {
+        let module = RawModuleAccount {
+            base_account: Some(account().into()),
+            name: "fee_collector".to_owned(),
+            permissions: vec![],
+        };
+        let any = Any {
+            type_url: MODULE_ACCOUNT_TYPE_URL.to_owned(),
+            value: module.encode_to_vec(),
+        };
+
+        assert_eq!(BaseAccount::try_from(any).unwrap(), account());
+    }
+
+    #[test]
+    fn decode_vesting_accounts() {
+        let base_vesting_account = RawBaseVestingAccount {
+            base_account: Some(account().into()),
+            original_vesting: vec![RawCoin {
+                denom: "utia".to_owned(),
+                amount: "1000".to_owned(),
+            }],
+            delegated_free: vec![],
+            delegated_vesting: vec![],
+            end_time: 1_800_000_000,
+        };
+
+        let accounts = [
+            (
+                CONTINUOUS_VESTING_ACCOUNT_TYPE_URL,
+                RawContinuousVestingAccount {
+                    base_vesting_account: Some(base_vesting_account.clone()),
+                    start_time: 1_700_000_000,
+                }
+                .encode_to_vec(),
+            ),
+            (
+                DELAYED_VESTING_ACCOUNT_TYPE_URL,
+                RawDelayedVestingAccount {
+                    base_vesting_account: Some(base_vesting_account.clone()),
+                }
+                .encode_to_vec(),
+            ),
+            (
+                PERIODIC_VESTING_ACCOUNT_TYPE_URL,
+                RawPeriodicVestingAccount {
+                    base_vesting_account: Some(base_vesting_account.clone()),
+                    start_time: 1_700_000_000,
+                    vesting_periods: vec![RawPeriod {
+                        length: 3600,
+                        amount: base_vesting_account.original_vesting.clone(),
+                    }],
+                }
+                .encode_to_vec(),
+            ),
+            (
+                PERMANENT_LOCKED_ACCOUNT_TYPE_URL,
+                RawPermanentLockedAccount {
+                    base_vesting_account: Some(base_vesting_account.clone()),
+                }
+                .encode_to_vec(),
+            ),
+        ];
+
+        for (type_url, value) in accounts {
+            let any = Any {
+                type_url: type_url.to_owned(),
+                value,
+            };
+            assert_eq!(BaseAccount::try_from(any).unwrap(), account());
+        }
+
+        let any = Any {
+            type_url: DELAYED_VESTING_ACCOUNT_TYPE_URL.to_owned(),
+            value: RawDelayedVestingAccount {
+                base_vesting_account: None,
+            }
+            .encode_to_vec(),
+        };
+        assert!(matches!(
+            BaseAccount::try_from(any),
+            Err(Error::MissingBaseAccount)
+        ));
+    }
+
+    #[test]
+    fn decode_unsupported_account() {
+        let any = Any {
+            type_url: "/cosmos.auth.v1beta1.UnknownAccount".to_owned(),
+            value: vec![],
+        };
+
+        assert!(matches!(
+            BaseAccount::try_from(any),
+            Err(Error::UnsupportedAccountType(_))
+        ));
+    }
+}
@@ -0,0 +1,567 @@
+use celestia_proto::cosmos::base::tendermint::v1beta1::{
+    AbciQueryResponse as RawAbciQueryResponse, ProofOps as RawProofOps,
+};
+use celestia_proto::cosmos::base::v1beta1::Coin as RawCoin;
+use celestia_proto::prost::Message;
+use ics23::commitment_proof::Proof;
+use ics23::{CommitmentProof, ExistenceProof, HostFunctionsManager};
+
+use crate::state::{AccAddress, AddressTrait, Balance};
+use crate::{
+    bail_validation, bail_verification, validation_error, verification_error, Error,
+    ExtendedHeader, Result,
+};
+
+/// Type of the proof operation proving a key in the IAVL tree of a module store.
+const IAVL_PROOF_OP: &str = "ics23:iavl";
+/// Type of the proof operation proving a module store root in the multistore.
+const SIMPLE_PROOF_OP: &str = "ics23:simple";
+
+/// Name of the `bank` module store.
+pub const BANK_STORE_NAME: &str = "bank";
+/// Prefix of the keys holding the balances in the `bank` module store.
+const BALANCES_PREFIX: u8 = 0x02;
+
+/// Get the key under which the balance of the given denomination is stored
+/// for the account in the `bank` module store.
+pub fn balance_store_key(address: &AccAddress, denom: &str) -> Vec<u8> {
+    let address = address.as_bytes();
+    let mut key = Vec::with_capacity(2 + address.len() + denom.len());
+
+    key.push(BALANCES_PREFIX);
+    // address length always fits in a byte, it is 20 or 32 bytes
+    key.push(address.len() as u8);
+    key.extend_from_slice(address);
+    key.extend_from_slice(denom.as_bytes());
+
+    key
+}
+
+/// A proof of a key in the application state, anchored in the `app_hash`.
+///
+/// Application state is a multistore, where each module keeps its own IAVL
+/// tree and the roots of those trees are merkelized into the `app_hash`.
+/// The proof consists of two steps, first proving a key (or its absence)
+/// in the module store and then proving the store root in the multistore.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateProof {
+    store_proof: CommitmentProof,
+    store_name: String,
+    multistore_proof: CommitmentProof,
+}
+
+impl StateProof {
+    /// Name of the module store the proof is for.
+    pub fn store_name(&self) -> &str {
+        &self.store_name
+    }
+
+    /// Verify that the `key` is set to the `value` in the module `store`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the proof is for a different store
+    /// or if it doesn't prove the `value` under the `app_hash`.
+    pub fn verify_membership(
+        &self,
+        app_hash: &[u8],
+        store: &str,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<()> {
+        let store_root = self.store_root(store)?;
+
+        if !ics23::verify_membership::<HostFunctionsManager>(
+            &self.store_proof,
+            &ics23::iavl_spec(),
+            &store_root,
+            key,
+            value,
+        ) {
+            bail_verification!("value not proven in the '{store}' store");
+        }
+
+        self.verify_store_root(app_hash, &store_root)
+    }
+
+    /// Verify that the `key` is absent in the module `store`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the proof is for a different store
+    /// or if it doesn't prove the absence of the `key` under the `app_hash`.
+    pub fn verify_non_membership(&self, app_hash: &[u8], store: &str, key: &[u8]) -> Result<()> {
+        let store_root = self.store_root(store)?;
+
+        if !ics23::verify_non_membership::<HostFunctionsManager>(
+            &self.store_proof,
+            &ics23::iavl_spec(),
+            &store_root,
+            key,
+        ) {
+            bail_verification!("key absence not proven in the '{store}' store");
+        }
+
+        self.verify_store_root(app_hash, &store_root)
+    }
+
+    /// Verify the balance of the given denomination for the account.
+    ///
+    /// The `value` is the raw value of the balance key, as returned by the query.
+    /// Zero balances are not kept in the store, so an empty `value` is verified
+    /// as the absence of the key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the proof is invalid or if the
+    /// `value` is not a balance of the given denomination.
+    pub fn verify_balance(
+        &self,
+        app_hash: &[u8],
+        address: &AccAddress,
+        denom: &str,
+        value: &[u8],
+    ) -> Result<Balance> {
+        let key = balance_store_key(address, denom);
+
+        if value.is_empty() {
+            self.verify_non_membership(app_hash, BANK_STORE_NAME, &key)?;
+
+            return Ok(Balance {
+                denom: denom.to_owned(),
+                amount: Default::default(),
+            });
+        }
+
+        self.verify_membership(app_hash, BANK_STORE_NAME, &key, value)?;
+
+        let coin =
+            RawCoin::decode(value).map_err(celestia_tendermint_proto::Error::decode_message)?;
+        let balance = Balance::try_from(coin)?;
+
+        if balance.denom != denom {
+            bail_verification!(
+                "proven balance denom ({}) != requested denom ({denom})",
+                balance.denom
+            );
+        }
+
+        Ok(balance)
+    }
+
+    /// Calculate the root of the module store from the existence proof (or the
+    /// neighbour of a non-existence proof).
+    fn store_root(&self, store: &str) -> Result<Vec<u8>> {
+        if self.store_name != store {
+            bail_verification!(
+                "proof store ({}) != expected store ({store})",
+                self.store_name
+            );
+        }
+
+        let existence = match &self.store_proof.proof {
+            Some(Proof::Exist(proof)) => proof,
+            Some(Proof::Nonexist(proof)) => match (&proof.left, &proof.right) {
+                (Some(proof), _) | (None, Some(proof)) => proof,
+                (None, None) => bail_verification!("non-existence proof without neighbours"),
+            },
+            _ => bail_verification!("unsupported store proof type"),
+        };
+
+        calculate_root(existence)
+    }
+
+    fn verify_store_root(&self, app_hash: &[u8], store_root: &[u8]) -> Result<()> {
+        if !ics23::verify_membership::<HostFunctionsManager>(
+            &self.multistore_proof,
+            &ics23::tendermint_spec(),
+            &app_hash.to_vec(),
+            self.store_name.as_bytes(),
+            store_root,
+        ) {
+            bail_verification!("'{}' store root not proven in app hash", self.store_name);
+        }
+
+        Ok(())
+    }
+}
+
+fn calculate_root(proof: &ExistenceProof) -> Result<Vec<u8>> {
+    ics23::calculate_existence_root::<HostFunctionsManager>(proof)
+        .map_err(|e| verification_error!("calculating store root: {e}").into())
+}
+
+impl TryFrom<RawProofOps> for StateProof {
+    type Error = Error;
+
+    fn try_from(value: RawProofOps) -> Result<Self, Self::Error> {
+        let [store_op, multistore_op] = <[_; 2]>::try_from(value.ops)
+            .map_err(|ops| validation_error!("expected 2 proof operations, got {}", ops.len()))?;
+
+        if store_op.r#type != IAVL_PROOF_OP {
+            bail_validation!("store proof type ({}) != {IAVL_PROOF_OP}", store_op.r#type);
+        }
+
+        if multistore_op.r#type != SIMPLE_PROOF_OP {
+            bail_validation!(
+                "multistore proof type ({}) != {SIMPLE_PROOF_OP}",
+                multistore_op.r#type
+            );
+        }
+
+        let store_name = String::from_utf8(multistore_op.key)
+            .map_err(|_| validation_error!("store name is not valid utf8"))?;
+
+        Ok(StateProof {
+            store_proof: CommitmentProof::decode(&store_op.data[..])
+                .map_err(celestia_tendermint_proto::Error::decode_message)?,
+            store_name,
+            multistore_proof: CommitmentProof::decode(&multistore_op.data[..])
+                .map_err(celestia_tendermint_proto::Error::decode_message)?,
+        })
+    }
+}
+
+/// A response to the state query, together with the proof of the value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvenQueryResponse {
+    /// Height of the state the value was queried at.
+    ///
+    /// The state is committed in the `app_hash` of the next block.
+    pub height: u64,
+    /// Queried key.
+    pub key: Vec<u8>,
+    /// Value under the key, empty if the key is absent.
+    pub value: Vec<u8>,
+    /// Proof of the value.
+    pub proof: StateProof,
+}
+
+impl ProvenQueryResponse {
+    /// Verify the queried balance against the `app_hash` of the given header.
+    ///
+    /// The header must be the one following the queried state [`height`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the header is not for the right
+    /// height, if the response is not for the given balance or if the proof
+    /// is invalid.
+    ///
+    /// [`height`]: ProvenQueryResponse::height
+    pub fn verify_balance(
+        &self,
+        header: &ExtendedHeader,
+        address: &AccAddress,
+        denom: &str,
+    ) -> Result<Balance> {
+        let expected_height = self.height + 1;
+
+        if header.height().value() != expected_height {
+            bail_verification!(
+                "header height ({}) != query height + 1 ({expected_height})",
+                header.height()
+            );
+        }
+
+        if self.key != balance_store_key(address, denom) {
+            bail_verification!("queried key is not the balance of {address} in {denom}");
+        }
+
+        self.proof.verify_balance(
+            header.header.app_hash.as_bytes(),
+            address,
+            denom,
+            &self.value,
+        )
+    }
+}
+
+impl TryFrom<RawAbciQueryResponse> for ProvenQueryResponse {
+    type Error = Error;
+
+    fn try_from(value: RawAbciQueryResponse) -> Result<Self, Self::Error> {
+        if value.code != 0 {
+            bail_validation!("query failed with code {}: {}", value.code, value.log);
+        }
+
+        let height = value
+            .height
+            .try_into()
+            .map_err(|_| validation_error!("negative query height ({})", value.height))?;
+
+        Ok(ProvenQueryResponse {
+            height,
+            key: value.key,
+            value: value.value,
+            proof: value.proof_ops.ok_or(Error::MissingProof)?.try_into()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::ExtendedHeaderGenerator;
+    use celestia_proto::cosmos::base::tendermint::v1beta1::ProofOp as RawProofOp;
+    use celestia_tendermint::account::Id;
+    use ics23::{InnerOp, NonExistenceProof};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Leaf of the IAVL tree at height 0, size 1 and version 1.
+    fn iavl_leaf(key: Vec<u8>, value: Vec<u8>) -> ExistenceProof {
+        let mut leaf = ics23::iavl_spec().leaf_spec.unwrap();
+        leaf.prefix = vec![0, 2, 2];
+
+        ExistenceProof {
+            key,
+            value,
+            leaf: Some(leaf),
+            path: vec![],
+        }
+    }
+
+    /// Leaf of the simple merkle tree of the multistore.
+    fn simple_leaf(key: &str, value: Vec<u8>) -> ExistenceProof {
+        ExistenceProof {
+            key: key.as_bytes().to_vec(),
+            value,
+            leaf: ics23::tendermint_spec().leaf_spec,
+            path: vec![],
+        }
+    }
+
+    /// Make both leaves children of the same inner node.
+    fn join(left: &mut ExistenceProof, right: &mut ExistenceProof, iavl: bool) {
+        let left_hash = calculate_root(left).unwrap();
+        let right_hash = calculate_root(right).unwrap();
+
+        if iavl {
+            // height 1, size 2, version 1, followed by length prefixed children
+            left.path.push(InnerOp {
+                hash: ics23::HashOp::Sha256.into(),
+                prefix: vec![2, 4, 2, 32],
+                suffix: [&[32][..], &right_hash].concat(),
+            });
+            right.path.push(InnerOp {
+                hash: ics23::HashOp::Sha256.into(),
+                prefix: [&[2, 4, 2, 32][..], &left_hash, &[32]].concat(),
+                suffix: vec![],
+            });
+        } else {
+            left.path.push(InnerOp {
+                hash: ics23::HashOp::Sha256.into(),
+                prefix: vec![1],
+                suffix: right_hash,
+            });
+            right.path.push(InnerOp {
+                hash: ics23::HashOp::Sha256.into(),
+                prefix: [&[1][..], &left_hash].concat(),
+                suffix: vec![],
+            });
+        }
+    }
+
+    fn address() -> AccAddress {
+        AccAddress::new(Id::new([7; 20]))
+    }
+
+    fn coin(denom: &str, amount: u64) -> Vec<u8> {
+        RawCoin {
+            denom: denom.to_owned(),
+            amount: amount.to_string(),
+        }
+        .encode_to_vec()
+    }
+
+    /// Build a bank store with balances of the `address()` in `atia` and `utia`
+    /// and a multistore of it and the `acc` store.
+    ///
+    /// Returns the app hash and the proof ops for both balances, and for
+    /// the absent balance in `btia`.
+    fn state() -> (Vec<u8>, [RawProofOps; 3]) {
+        let mut atia = iavl_leaf(balance_store_key(&address(), "atia"), coin("atia", 5));
+        let mut utia = iavl_leaf(balance_store_key(&address(), "utia"), coin("utia", 1000));
+        join(&mut atia, &mut utia, true);
+
+        let bank_root = calculate_root(&atia).unwrap();
+        let mut acc = simple_leaf("acc", vec![1; 32]);
+        let mut bank = simple_leaf(BANK_STORE_NAME, bank_root);
+        join(&mut acc, &mut bank, false);
+
+        let app_hash = calculate_root(&bank).unwrap();
+        let multistore_op = RawProofOp {
+            r#type: SIMPLE_PROOF_OP.to_owned(),
+            key: BANK_STORE_NAME.as_bytes().to_vec(),
+            data: CommitmentProof {
+                proof: Some(Proof::Exist(bank)),
+            }
+            .encode_to_vec(),
+        };
+
+        let ops = |key: Vec<u8>, proof| RawProofOps {
+            ops: vec![
+                RawProofOp {
+                    r#type: IAVL_PROOF_OP.to_owned(),
+                    key,
+                    data: CommitmentProof { proof: Some(proof) }.encode_to_vec(),
+                },
+                multistore_op.clone(),
+            ],
+        };
+
+        let absent_key = balance_store_key(&address(), "btia");
+        let nonexist = NonExistenceProof {
+            key: absent_key.clone(),
+            left: Some(atia.clone()),
+            right: Some(utia.clone()),
+        };
+
+        (
+            app_hash,
+            [
+                ops(atia.key.clone(), Proof::Exist(atia)),
+                ops(utia.key.clone(), Proof::Exist(utia)),
+                ops(absent_key, Proof::Nonexist(nonexist)),
+            ],
+        )
+    }
+
+    #[test]
+    fn verify_balances() {
+        let (app_hash, [atia, utia, btia]) = state();
+
+        let proof = StateProof::try_from(atia).unwrap();
+        let balance = proof
+            .verify_balance(&app_hash, &address(), "atia", &coin("atia", 5))
+            .unwrap();
+        assert_eq!(balance.amount, 5u64.try_into().unwrap());
+
+        let proof = StateProof::try_from(utia).unwrap();
+        let balance = proof
+            .verify_balance(&app_hash, &address(), "utia", &coin("utia", 1000))
+            .unwrap();
+        assert_eq!(balance.amount, 1000u64.try_into().unwrap());
+
+        let proof = StateProof::try_from(btia).unwrap();
+        let balance = proof
+            .verify_balance(&app_hash, &address(), "btia", &[])
+            .unwrap();
+        assert_eq!(balance.denom, "btia");
+        assert_eq!(balance.amount, Default::default());
+    }
+
+    #[test]
+    fn verify_tampered_balance() {
+        let (app_hash, [_, utia, btia]) = state();
+
+        let proof = StateProof::try_from(utia).unwrap();
+        proof
+            .verify_balance(&app_hash, &address(), "utia", &coin("utia", 1001))
+            .unwrap_err();
+        // hiding the existing balance
+        proof
+            .verify_balance(&app_hash, &address(), "utia", &[])
+            .unwrap_err();
+        // proving other account
+        let other = AccAddress::new(Id::new([8; 20]));
+        proof
+            .verify_balance(&app_hash, &other, "utia", &coin("utia", 1000))
+            .unwrap_err();
+
+        let proof = StateProof::try_from(btia).unwrap();
+        proof
+            .verify_balance(&app_hash, &address(), "btia", &coin("btia", 1))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn verify_with_wrong_app_hash() {
+        let (mut app_hash, [atia, ..]) = state();
+        app_hash[0] ^= 1;
+
+        let proof = StateProof::try_from(atia).unwrap();
+        let err = proof
+            .verify_balance(&app_hash, &address(), "atia", &coin("atia", 5))
+            .unwrap_err();
+        assert!(matches!(err, Error::Verification(_)));
+    }
+
+    #[test]
+    fn verify_wrong_store() {
+        let (app_hash, [atia, ..]) = state();
+
+        let proof = StateProof::try_from(atia).unwrap();
+        let key = balance_store_key(&address(), "atia");
+        proof
+            .verify_membership(&app_hash, "acc", &key, &coin("atia", 5))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn invalid_proof_ops() {
+        let (_, [mut atia, ..]) = state();
+
+        atia.ops.swap(0, 1);
+        StateProof::try_from(atia.clone()).unwrap_err();
+
+        atia.ops.pop();
+        StateProof::try_from(atia).unwrap_err();
+    }
+
+    #[test]
+    fn verify_query_response() {
+        let (app_hash, [_, utia, _]) = state();
+
+        let raw = RawAbciQueryResponse {
+            key: balance_store_key(&address(), "utia"),
+            value: coin("utia", 1000),
+            proof_ops: Some(utia),
+            height: 10,
+            ..Default::default()
+        };
+        let response = ProvenQueryResponse::try_from(raw).unwrap();
+
+        let mut header = ExtendedHeaderGenerator::new_from_height(11).next();
+        header.header.app_hash = app_hash.try_into().unwrap();
+
+        let balance = response
+            .verify_balance(&header, &address(), "utia")
+            .unwrap();
+        assert_eq!(balance.amount, 1000u64.try_into().unwrap());
+
+        // app hash of the queried height doesn't commit the queried state
+        let mut prev_header = ExtendedHeaderGenerator::new_from_height(10).next();
+        prev_header.header.app_hash = header.header.app_hash.clone();
+        response
+            .verify_balance(&prev_header, &address(), "utia")
+            .unwrap_err();
+
+        // response is not for the requested balance
+        response
+            .verify_balance(&header, &address(), "atia")
+            .unwrap_err();
+    }
+
+    #[test]
+    fn failed_query_response() {
+        let raw = RawAbciQueryResponse {
+            code: 1,
+            log: "failed".to_owned(),
+            height: 10,
+            ..Default::default()
+        };
+        ProvenQueryResponse::try_from(raw).unwrap_err();
+
+        let raw = RawAbciQueryResponse {
+            height: 10,
+            ..Default::default()
+        };
+        assert!(matches!(
+            ProvenQueryResponse::try_from(raw),
+            Err(Error::MissingProof)
+        ));
+    }
+}
@@ -1,4 +1,7 @@
-use celestia_proto::cosmos::base::abci::v1beta1::TxResponse as RawTxResponse;
+use celestia_proto::cosmos::base::abci::v1beta1::{
+    GasInfo as RawGasInfo, TxResponse as RawTxResponse,
+};
+pub use celestia_proto::cosmos::tx::v1beta1::BroadcastMode;
 use serde::{Deserialize, Serialize};
 
 /// Raw transaction data.
@@ -15,3 +18,6 @@ pub struct RawTx {
 
 /// Raw transaction response.
 pub type TxResponse = RawTxResponse;
+
+/// Gas used by the simulated or executed transaction.
+pub type GasInfo = RawGasInfo;
@@ -1,6 +1,6 @@
 use serde::{
     ser::{SerializeStruct, Serializer},
-    Serialize,
+    Deserialize, Deserializer, Serialize,
 };
 
 use crate::state::AccAddress;
@@ -110,3 +110,61 @@ impl Serialize for TxConfig {
         state.end()
     }
 }
+
+impl<'de> Deserialize<'de> for TxConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct RawTxConfig {
+            signer_address: Option<AccAddress>,
+            key_name: Option<String>,
+            gas_price: Option<f64>,
+            #[serde(default)]
+            is_gas_price_set: bool,
+            gas: Option<u64>,
+            fee_granter_address: Option<AccAddress>,
+        }
+
+        let raw = RawTxConfig::deserialize(deserializer)?;
+
+        Ok(TxConfig {
+            signer_address: raw.signer_address,
+            key_name: raw.key_name,
+            gas_price: raw.gas_price.filter(|_| raw.is_gas_price_set),
+            gas: raw.gas,
+            fee_granter_address: raw.fee_granter_address,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn serde_roundtrip() {
+        let mut config = TxConfig::default();
+        config
+            .with_gas_price(0.002)
+            .with_gas(1000)
+            .with_key_name("key");
+
+        let json = serde_json::to_string(&config).unwrap();
+        let decoded: TxConfig = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.gas_price, Some(0.002));
+        assert_eq!(decoded.gas, Some(1000));
+        assert_eq!(decoded.key_name.as_deref(), Some("key"));
+        assert_eq!(decoded.signer_address, None);
+
+        // gas price is ignored unless explicitly set
+        let decoded: TxConfig =
+            serde_json::from_str(r#"{"gas_price": 0.002, "is_gas_price_set": false}"#).unwrap();
+        assert_eq!(decoded.gas_price, None);
+    }
+}